@@ -0,0 +1,240 @@
+use {
+  super::*,
+  bitcoin::{
+    consensus::{self, encode, Decodable, Encodable},
+    BlockHeader, TxMerkleNode,
+  },
+};
+
+/// Set in the version of blocks that were merge mined, which carry an AuxPoW
+/// header between their header and their transactions.
+const VERSION_AUXPOW: i32 = 1 << 8;
+
+const HEADER_SIZE: usize = 80;
+
+/// Proof that a block was merge mined: the coinbase of the parent chain block
+/// whose proof of work the block borrows, the merkle branch from that coinbase
+/// to the parent block's merkle root, and the branch from the block's hash to
+/// the merged mining root the coinbase commits to.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AuxPow {
+  pub(crate) coinbase_tx: Transaction,
+  pub(crate) parent_hash: BlockHash,
+  pub(crate) coinbase_branch: Vec<TxMerkleNode>,
+  pub(crate) coinbase_index: i32,
+  pub(crate) chain_branch: Vec<TxMerkleNode>,
+  pub(crate) chain_index: i32,
+  pub(crate) parent_block: BlockHeader,
+}
+
+impl AuxPow {
+  pub(crate) fn is_merge_mined(header: &BlockHeader) -> bool {
+    header.version & VERSION_AUXPOW != 0
+  }
+
+  /// Decodes a serialized block, returning its AuxPoW header separately,
+  /// since `Block` has no room for it.
+  pub(crate) fn decode_block(bytes: &[u8]) -> Result<(Block, Option<Self>)> {
+    ensure!(
+      bytes.len() >= HEADER_SIZE,
+      "block of {} bytes is shorter than a header",
+      bytes.len()
+    );
+
+    let header: BlockHeader = consensus::deserialize(&bytes[..HEADER_SIZE])?;
+
+    let mut rest = &bytes[HEADER_SIZE..];
+
+    let auxpow = if Self::is_merge_mined(&header) {
+      Some(Self::consensus_decode(&mut rest)?)
+    } else {
+      None
+    };
+
+    let txdata = Vec::<Transaction>::consensus_decode(&mut rest)?;
+
+    ensure!(
+      rest.is_empty(),
+      "{} trailing bytes after block {}",
+      rest.len(),
+      header.block_hash()
+    );
+
+    Ok((Block { header, txdata }, auxpow))
+  }
+
+  /// Serializes a block the way the node does, with its AuxPoW header, if it
+  /// has one, between its header and its transactions.
+  pub(crate) fn encode_block(block: &Block, auxpow: Option<&Self>) -> Vec<u8> {
+    let mut bytes = consensus::serialize(&block.header);
+
+    if let Some(auxpow) = auxpow {
+      bytes.extend(consensus::serialize(auxpow));
+    }
+
+    bytes.extend(consensus::serialize(&block.txdata));
+
+    bytes
+  }
+}
+
+impl Encodable for AuxPow {
+  fn consensus_encode<W: io::Write + ?Sized>(&self, writer: &mut W) -> Result<usize, io::Error> {
+    let mut len = self.coinbase_tx.consensus_encode(writer)?;
+    len += self.parent_hash.consensus_encode(writer)?;
+    len += self.coinbase_branch.consensus_encode(writer)?;
+    len += self.coinbase_index.consensus_encode(writer)?;
+    len += self.chain_branch.consensus_encode(writer)?;
+    len += self.chain_index.consensus_encode(writer)?;
+    len += self.parent_block.consensus_encode(writer)?;
+    Ok(len)
+  }
+}
+
+impl Decodable for AuxPow {
+  fn consensus_decode<R: io::Read + ?Sized>(reader: &mut R) -> Result<Self, encode::Error> {
+    let coinbase_tx = Decodable::consensus_decode(reader)?;
+    let parent_hash = Decodable::consensus_decode(reader)?;
+    let coinbase_branch = Decodable::consensus_decode(reader)?;
+    let coinbase_index = Decodable::consensus_decode(reader)?;
+    let chain_branch = Decodable::consensus_decode(reader)?;
+    let chain_index = Decodable::consensus_decode(reader)?;
+
+    let mut parent_block = [0; HEADER_SIZE];
+    reader.read_exact(&mut parent_block)?;
+
+    Ok(Self {
+      coinbase_tx,
+      parent_hash,
+      coinbase_branch,
+      coinbase_index,
+      chain_branch,
+      chain_index,
+      parent_block: consensus::deserialize(&parent_block)?,
+    })
+  }
+}
+
+#[derive(Serialize)]
+struct AuxPowJson<'a> {
+  parent_block_hash: BlockHash,
+  parent_previous_blockhash: BlockHash,
+  parent_merkle_root: TxMerkleNode,
+  parent_time: u32,
+  parent_coinbase_txid: Txid,
+  parent_coinbase_script: Option<String>,
+  coinbase_branch: &'a [TxMerkleNode],
+  coinbase_index: i32,
+  chain_branch: &'a [TxMerkleNode],
+  chain_index: i32,
+}
+
+impl Serialize for AuxPow {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    AuxPowJson {
+      parent_block_hash: self.parent_block.block_hash(),
+      parent_previous_blockhash: self.parent_block.prev_blockhash,
+      parent_merkle_root: self.parent_block.merkle_root,
+      parent_time: self.parent_block.time,
+      parent_coinbase_txid: self.coinbase_tx.txid(),
+      parent_coinbase_script: self
+        .coinbase_tx
+        .input
+        .first()
+        .map(|input| hex::encode(input.script_sig.as_bytes())),
+      coinbase_branch: &self.coinbase_branch,
+      coinbase_index: self.coinbase_index,
+      chain_branch: &self.chain_branch,
+      chain_index: self.chain_index,
+    }
+    .serialize(serializer)
+  }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use {super::*, bitcoin::hashes::Hash};
+
+  pub(crate) fn auxpow() -> AuxPow {
+    let parent = Chain::Mainnet.genesis_block();
+
+    AuxPow {
+      coinbase_tx: parent.txdata[0].clone(),
+      parent_hash: BlockHash::all_zeros(),
+      coinbase_branch: Vec::new(),
+      coinbase_index: 0,
+      chain_branch: vec![TxMerkleNode::all_zeros(), parent.header.merkle_root],
+      chain_index: 2,
+      parent_block: parent.header,
+    }
+  }
+
+  fn merge_mined_block() -> (Block, AuxPow, Vec<u8>) {
+    let mut block = Chain::Mainnet.genesis_block();
+    block.header.version |= VERSION_AUXPOW;
+
+    let auxpow = auxpow();
+
+    let mut bytes = consensus::serialize(&block.header);
+    bytes.extend(consensus::serialize(&auxpow));
+    bytes.extend(consensus::serialize(&block.txdata));
+
+    (block, auxpow, bytes)
+  }
+
+  #[test]
+  fn round_trip() {
+    let auxpow = auxpow();
+    assert_eq!(
+      consensus::deserialize::<AuxPow>(&consensus::serialize(&auxpow)).unwrap(),
+      auxpow
+    );
+  }
+
+  #[test]
+  fn blocks_without_auxpow_are_decoded() {
+    let block = Chain::Mainnet.genesis_block();
+    assert!(!AuxPow::is_merge_mined(&block.header));
+    assert_eq!(
+      AuxPow::decode_block(&consensus::serialize(&block)).unwrap(),
+      (block, None)
+    );
+  }
+
+  #[test]
+  fn merge_mined_blocks_are_decoded() {
+    let (block, auxpow, bytes) = merge_mined_block();
+    assert!(AuxPow::is_merge_mined(&block.header));
+    assert_eq!(AuxPow::decode_block(&bytes).unwrap(), (block, Some(auxpow)));
+  }
+
+  #[test]
+  fn malformed_blocks_are_rejected() {
+    let (_, _, bytes) = merge_mined_block();
+    assert!(AuxPow::decode_block(&bytes[..HEADER_SIZE - 1]).is_err());
+    assert!(AuxPow::decode_block(&bytes[..bytes.len() - 1]).is_err());
+
+    let mut trailing = bytes;
+    trailing.push(0);
+    assert!(AuxPow::decode_block(&trailing).is_err());
+  }
+
+  #[test]
+  fn json() {
+    let auxpow = auxpow();
+    let json = serde_json::to_value(&auxpow).unwrap();
+    assert_eq!(
+      json["parent_block_hash"],
+      auxpow.parent_block.block_hash().to_string()
+    );
+    assert_eq!(
+      json["parent_coinbase_txid"],
+      auxpow.coinbase_tx.txid().to_string()
+    );
+    assert_eq!(json["chain_index"], 2);
+    assert_eq!(json["chain_branch"].as_array().unwrap().len(), 2);
+  }
+}
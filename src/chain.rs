@@ -1,7 +1,7 @@
 use bitcoin::util::address;
 use {super::*, clap::ValueEnum};
 
-#[derive(Default, ValueEnum, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Default, ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub(crate) enum Chain {
   #[default]
@@ -75,6 +75,16 @@ impl Chain {
     Address::from_script(script, self.network())
   }
 
+  /// Message start bytes that prefix every record in the node's blk*.dat
+  /// files. Dogecoin has no signet, so signet shares testnet's magic.
+  pub(crate) fn network_magic(self) -> [u8; 4] {
+    match self {
+      Self::Mainnet => [0xc0, 0xc0, 0xc0, 0xc0],
+      Self::Regtest => [0xfa, 0xbf, 0xb5, 0xda],
+      Self::Signet | Self::Testnet => [0xfc, 0xc1, 0xb7, 0xdc],
+    }
+  }
+
   pub(crate) fn join_with_data_dir(self, data_dir: &Path) -> PathBuf {
     match self {
       Self::Mainnet => data_dir.to_owned(),
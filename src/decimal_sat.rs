@@ -2,8 +2,8 @@ use super::*;
 
 #[derive(PartialEq, Debug)]
 pub(crate) struct DecimalSat {
-  height: Height,
-  offset: u64,
+  pub(crate) height: Height,
+  pub(crate) offset: u64,
 }
 
 impl From<Sat> for DecimalSat {
@@ -16,3 +16,42 @@ impl Balance {
         }
     }
 }
+
+/// Formats a raw token amount with `decimal_places` decimals, trimming
+/// trailing zeros.
+pub fn format_balance(balance: u128, decimal_places: u8) -> String {
+    let factor = 10u128.pow(u32::from(decimal_places));
+    let integer_part = balance / factor; // Get the integer part
+    let fractional_part = balance % factor; // Get the fractional part
+
+    // If balance is zero or the fractional part is zero, return just the integer part
+    if fractional_part == 0 {
+        return format!("{}", integer_part);
+    }
+
+    // Format the fractional part, trimming trailing zeros
+    let mut fractional_string = format!(
+        "{:0>width$}",
+        fractional_part,
+        width = decimal_places as usize
+    );
+
+    // Remove trailing zeros from the fractional part
+    while fractional_string.ends_with('0') {
+        fractional_string.pop();
+    }
+
+    // Combine integer and cleaned-up fractional part
+    format!("{}.{}", integer_part, fractional_string)
+}
+
+/// Formats a signed change of a raw token amount like `format_balance`.
+pub fn format_balance_change(change: i128, decimal_places: u8) -> String {
+    let amount = format_balance(change.unsigned_abs(), decimal_places);
+
+    if change < 0 {
+        format!("-{}", amount)
+    } else {
+        amount
+    }
+}
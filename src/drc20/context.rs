@@ -1,22 +1,30 @@
 use anyhow::{anyhow, Result};
 use bitcoin::{Network, Txid};
-use redb::{ReadableTable, Table};
 
+use crate::chain::Chain;
 use crate::drc20::operation::{Action, deserialize_drc20_operation, InscriptionOp, Operation};
+use crate::drc20::rules::Ruleset;
 use crate::drc20::transfer::Transfer;
 use crate::drc20::TransferInfo;
 use crate::index::entry::{Entry, InscriptionIdValue};
-use crate::inscription::Inscription;
+use crate::index::WritableTable;
 use crate::inscription_id::InscriptionId;
 use crate::sat_point::SatPoint;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct BlockContext {
+  pub(crate) chain: Chain,
   pub network: Network,
   pub blockheight: u64,
   pub blocktime: u32,
 }
 
+impl BlockContext {
+  pub(crate) fn rules(&self) -> &'static Ruleset {
+    Ruleset::at(self.chain, self.blockheight)
+  }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Message {
   pub txid: Txid,
@@ -29,28 +37,20 @@ pub struct Message {
 }
 
 impl Message {
-  pub(crate) fn resolve<'a, 'db, 'tx>(
-    drc20_inscribe_transfer: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, &'static [u8]>,
-    new_inscriptions: &[Inscription],
+  pub(crate) fn resolve(
+    drc20_inscribe_transfer: &dyn WritableTable<&'static InscriptionIdValue, &'static [u8]>,
     op: &InscriptionOp,
+    rules: &Ruleset,
   ) -> Result<Option<Message>> {
     let sat_in_outputs = op
       .new_satpoint
       .map(|satpoint| satpoint.outpoint.txid == op.txid)
       .unwrap_or(false);
 
-    let drc20_operation = match op.action {
-      Action::New { inscription: _ } if sat_in_outputs => {
-        match deserialize_drc20_operation(
-          new_inscriptions
-            .get(usize::try_from(op.inscription_id.index).unwrap())
-            .unwrap_or(&Inscription {
-              content_type: None,
-              body: None,
-              delegate: None,
-            }),
-          &op.action,
-        ) {
+    let drc20_operation = match &op.action {
+      // New inscription, read from every transaction it was revealed over.
+      Action::New { inscription } if sat_in_outputs => {
+        match deserialize_drc20_operation(inscription, &op.action, rules) {
           Ok(drc20_operation) => drc20_operation,
           _ => return Ok(None),
         }
@@ -88,8 +88,8 @@ impl Message {
   }
 }
 
-fn get_inscribe_transfer_inscription<'a, 'db, 'tx>(
-  drc20_inscribe_transfer: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, &'static [u8]>,
+fn get_inscribe_transfer_inscription(
+  drc20_inscribe_transfer: &dyn WritableTable<&'static InscriptionIdValue, &'static [u8]>,
   inscription_id: InscriptionId,
 ) -> Result<Option<TransferInfo>, redb::Error> {
   Ok(
@@ -21,6 +21,7 @@ pub struct Receipt {
     pub from: ScriptKey,
     pub to: ScriptKey,
     pub result: Result<Event, DRC20Error>,
+    pub rule_version: u32,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -29,6 +30,18 @@ pub enum Event {
     Mint(MintEvent),
     InscribeTransfer(InscribeTransferEvent),
     Transfer(TransferEvent),
+    Burn(BurnEvent),
+}
+
+/// A successful mint, transfer or burn of a tick, kept so explorers can show
+/// recent activity without replaying blocks.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TickActivity {
+  pub height: u64,
+  pub inscription_id: InscriptionId,
+  pub event: Event,
+  /// Version of the `Ruleset` the operation was executed under.
+  pub rule_version: u32,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -70,3 +83,13 @@ pub struct TransferEvent {
   pub tick: Tick,
   pub amount: u128,
 }
+
+/// A transfer whose inscription was spent as fee or sent to an unspendable
+/// output. The amount leaves circulation for good.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BurnEvent {
+  pub txid: Option<Txid>,
+  pub from: ScriptKey,
+  pub tick: Tick,
+  pub amount: u128,
+}
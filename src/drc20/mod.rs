@@ -11,15 +11,17 @@ mod read_write;
 mod deploy;
 mod mint;
 pub(crate) mod params;
+pub(crate) mod rules;
+pub(crate) mod state_hash;
 mod num;
 mod transferable_log;
 
 pub use self::{
-    balance::Balance, errors::DRC20Error, events::*, tick::*, token_info::TokenInfo,
+    balance::{format_balance, format_balance_change, Balance}, errors::DRC20Error, events::*, tick::*, token_info::TokenInfo,
     transfer::TransferInfo,
     context::BlockContext, context::Message,
     num::Num, deploy::Deploy, mint::Mint, transfer::Transfer,
-    transferable_log::TransferableLog,
+    transferable_log::TransferableLog, state_hash::StateHash, rules::Ruleset,
 };
 use crate::Result;
 use std::fmt::{Debug, Display};
@@ -11,6 +11,7 @@ use crate::drc20::errors::JSONError;
 use crate::drc20::mint::Mint;
 use crate::drc20::OperationType;
 use crate::drc20::params::PROTOCOL_LITERAL;
+use crate::drc20::rules::Ruleset;
 use crate::drc20::transfer::Transfer;
 
 // collect the inscription operation.
@@ -64,9 +65,10 @@ enum RawOperation {
 pub(crate) fn deserialize_drc20_operation(
   inscription: &Inscription,
   action: &Action,
+  rules: &Ruleset,
 ) -> anyhow::Result<Operation> {
   let content_body = std::str::from_utf8(inscription.body().ok_or(JSONError::InvalidJson)?)?;
-  if content_body.len() < 40 {
+  if content_body.len() < rules.min_content_length {
     return Err(JSONError::NotDRC20Json.into());
   }
 
@@ -74,7 +76,7 @@ pub(crate) fn deserialize_drc20_operation(
       .content_type()
       .ok_or(JSONError::InvalidContentType)?;
 
-  if !content_type.starts_with("text/plain") && !content_type.starts_with("application/json") {
+  if !rules.allows_content_type(content_type) {
     return Err(JSONError::UnSupportContentType.into());
   }
   let raw_operation = match deserialize_drc20(content_body) {
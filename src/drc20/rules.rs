@@ -0,0 +1,164 @@
+use {
+  super::{params::MAX_DECIMAL_WIDTH, TICK_BYTE_COUNT},
+  crate::chain::Chain,
+};
+
+/// The DRC-20 rules in force from `activation_height` on. Changing a rule
+/// means appending a ruleset with the next version, so blocks indexed under
+/// the old rules keep their outcome.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ruleset {
+  pub version: u32,
+  pub activation_height: u64,
+  /// Length of a tick in bytes. `Tick` stores exactly `TICK_BYTE_COUNT`
+  /// bytes, so another length also needs a new tick encoding.
+  pub tick_byte_count: usize,
+  /// Largest `dec` a deploy may set. `Num` can't parse more than
+  /// `MAX_DECIMAL_WIDTH` decimals.
+  pub max_decimal_width: u8,
+  /// Inscriptions with shorter bodies are skipped without being parsed.
+  pub min_content_length: usize,
+  /// Content type prefixes of inscriptions that may carry operations.
+  pub content_types: &'static [&'static str],
+  /// Transfers spent as fee or sent to an unspendable output are burned.
+  /// Without burns, transfers spent as fee go back to the sender and
+  /// unspendable outputs are credited like any other. DRC-20 indexers haven't
+  /// agreed on a height to start burning at, so burns apply from genesis.
+  pub burns: bool,
+}
+
+const V1: Ruleset = Ruleset {
+  version: 1,
+  activation_height: 0,
+  tick_byte_count: TICK_BYTE_COUNT,
+  max_decimal_width: MAX_DECIMAL_WIDTH,
+  min_content_length: 40,
+  content_types: &["text/plain", "application/json"],
+  burns: true,
+};
+
+const MAINNET: &[Ruleset] = &[V1];
+const TESTNET: &[Ruleset] = &[V1];
+const SIGNET: &[Ruleset] = &[V1];
+const REGTEST: &[Ruleset] = &[V1];
+
+impl Ruleset {
+  /// Every ruleset of `chain`, ordered by activation height.
+  pub(crate) fn all(chain: Chain) -> &'static [Ruleset] {
+    match chain {
+      Chain::Mainnet => MAINNET,
+      Chain::Testnet => TESTNET,
+      Chain::Signet => SIGNET,
+      Chain::Regtest => REGTEST,
+    }
+  }
+
+  /// Returns the rules for the block at `height` on `chain`.
+  pub(crate) fn at(chain: Chain, height: u64) -> &'static Ruleset {
+    Self::all(chain)
+      .iter()
+      .rev()
+      .find(|ruleset| ruleset.activation_height <= height)
+      .expect("every chain has a ruleset from genesis")
+  }
+
+  pub(crate) fn allows_content_type(&self, content_type: &str) -> bool {
+    self
+      .content_types
+      .iter()
+      .any(|prefix| content_type.starts_with(prefix))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    crate::{
+      drc20::operation::{deserialize_drc20_operation, Action, Operation},
+      Inscription,
+    },
+  };
+
+  const CHAINS: [Chain; 4] = [
+    Chain::Mainnet,
+    Chain::Testnet,
+    Chain::Signet,
+    Chain::Regtest,
+  ];
+
+  #[test]
+  fn rulesets_are_ordered_and_start_at_genesis() {
+    for chain in CHAINS {
+      let rulesets = Ruleset::all(chain);
+
+      assert_eq!(rulesets[0].activation_height, 0, "{chain}");
+      assert_eq!(rulesets[0].version, 1, "{chain}");
+
+      for pair in rulesets.windows(2) {
+        assert!(
+          pair[0].activation_height < pair[1].activation_height,
+          "{chain}"
+        );
+        assert_eq!(pair[0].version + 1, pair[1].version, "{chain}");
+      }
+    }
+  }
+
+  #[test]
+  fn rulesets_fit_the_ledger_types() {
+    for chain in CHAINS {
+      for ruleset in Ruleset::all(chain) {
+        assert_eq!(ruleset.tick_byte_count, TICK_BYTE_COUNT, "{chain}");
+        assert!(ruleset.max_decimal_width <= MAX_DECIMAL_WIDTH, "{chain}");
+      }
+    }
+  }
+
+  #[test]
+  fn latest_activated_ruleset_applies() {
+    assert_eq!(Ruleset::at(Chain::Mainnet, 0), &V1);
+    assert_eq!(
+      Ruleset::at(Chain::Mainnet, u64::MAX),
+      MAINNET.last().unwrap()
+    );
+  }
+
+  #[test]
+  fn burns_apply_from_genesis() {
+    for chain in CHAINS {
+      assert!(Ruleset::at(chain, 0).burns, "{chain}");
+    }
+  }
+
+  #[test]
+  fn content_types_are_matched_by_prefix() {
+    assert!(V1.allows_content_type("text/plain;charset=utf-8"));
+    assert!(V1.allows_content_type("application/json"));
+    assert!(!V1.allows_content_type("image/png"));
+  }
+
+  #[test]
+  fn operations_are_parsed_under_the_given_rules() {
+    let inscription = Inscription::new(
+      Some("text/html".into()),
+      Some(br#"{"p":"drc-20","op":"mint","tick":"doge","amt":"1"}"#.to_vec()),
+    );
+    let action = Action::New {
+      inscription: inscription.clone(),
+    };
+
+    assert!(deserialize_drc20_operation(&inscription, &action, &V1).is_err());
+
+    let html = Ruleset {
+      version: 2,
+      content_types: &["text/html"],
+      ..V1
+    };
+
+    assert!(matches!(
+      deserialize_drc20_operation(&inscription, &action, &html).unwrap(),
+      Operation::Mint(_)
+    ));
+  }
+}
@@ -11,10 +11,12 @@ pub enum ScriptKey {
 }
 
 impl ScriptKey {
+  /// Parses the `Display` form of either variant, an address or the hex of
+  /// a script hash.
   pub fn from_str(script: &str, network: Network) -> Option<Self> {
     match Address::from_str(script) {
       Ok(address) => Some(ScriptKey::from_address(address, network)),
-      Err(_) => None,
+      Err(_) => ScriptHash::from_str(script).ok().map(ScriptKey::ScriptHash),
     }
   }
   pub fn from_address(address: Address, network: Network) -> Self {
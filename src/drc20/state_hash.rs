@@ -0,0 +1,159 @@
+use {
+  super::*,
+  crate::{drc20::script_key::ScriptKey, InscriptionId},
+  bitcoin::hashes::{sha256, Hash, HashEngine},
+  serde::{Deserialize, Serialize},
+};
+
+/// Digest of every DRC-20 receipt and balance change up to and including
+/// `height`. Each block's digest covers the previous one, so two indexers that
+/// agree at some height agreed at every height before it.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct StateHash {
+  pub height: u32,
+  pub hash: sha256::Hash,
+}
+
+const RECEIPT: u8 = 0;
+const BALANCE: u8 = 1;
+
+pub(crate) struct StateHasher(sha256::HashEngine);
+
+impl StateHasher {
+  /// Starts the digest of the block at `height`. The first block indexed
+  /// with DRC-20 has no previous digest and chains from all zeros.
+  pub(crate) fn new(height: u32, previous: Option<sha256::Hash>) -> Self {
+    let mut engine = sha256::Hash::engine();
+    engine.input(
+      &previous
+        .unwrap_or_else(sha256::Hash::all_zeros)
+        .into_inner(),
+    );
+    engine.input(&height.to_le_bytes());
+    Self(engine)
+  }
+
+  fn input_bytes(&mut self, bytes: &[u8]) {
+    self
+      .0
+      .input(&u64::try_from(bytes.len()).unwrap().to_le_bytes());
+    self.0.input(bytes);
+  }
+
+  /// Adds the outcome of an operation and the version of the rules it was
+  /// executed under. Rejected operations are included with their error, so
+  /// indexers that reject the same operation for different reasons are told
+  /// apart.
+  pub(crate) fn receipt<E: Display>(
+    &mut self,
+    inscription_id: InscriptionId,
+    rule_version: u32,
+    result: &std::result::Result<Event, E>,
+  ) {
+    self.0.input(&[RECEIPT]);
+    self.0.input(inscription_id.txid.as_ref());
+    self.0.input(&inscription_id.index.to_le_bytes());
+    self.0.input(&rule_version.to_le_bytes());
+
+    match result {
+      Ok(event) => {
+        self.0.input(&[0]);
+        self.input_bytes(&bincode::serialize(event).unwrap());
+      }
+      Err(err) => {
+        self.0.input(&[1]);
+        self.input_bytes(err.to_string().as_bytes());
+      }
+    }
+  }
+
+  /// Adds the new balance of `script_key`, in the order balances are written.
+  pub(crate) fn balance(&mut self, script_key: &ScriptKey, balance: &Balance) {
+    self.0.input(&[BALANCE]);
+    self.input_bytes(script_key.to_string().as_bytes());
+    self.input_bytes(&bincode::serialize(balance).unwrap());
+  }
+
+  pub(crate) fn finish(self) -> sha256::Hash {
+    sha256::Hash::from_engine(self.0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, std::str::FromStr};
+
+  fn balance() -> (ScriptKey, Balance) {
+    let script_key = ScriptKey::from_script(
+      &bitcoin::Script::new_op_return(&[]),
+      bitcoin::Network::Bitcoin,
+    );
+    let tick = Tick::from_str("doge").unwrap();
+    (
+      script_key,
+      Balance {
+        overall_balance: 100,
+        transferable_balance: 40,
+        ..Balance::new(&tick)
+      },
+    )
+  }
+
+  fn receipt(hasher: &mut StateHasher, error: &str) {
+    hasher.receipt(
+      InscriptionId::from(bitcoin::Txid::all_zeros()),
+      1,
+      &Err::<Event, _>(error.to_string()),
+    );
+  }
+
+  #[test]
+  fn digests_are_deterministic() {
+    let (script_key, balance) = balance();
+
+    let digest = || {
+      let mut hasher = StateHasher::new(1, None);
+      receipt(&mut hasher, "tick: doge not found");
+      hasher.balance(&script_key, &balance);
+      hasher.finish()
+    };
+
+    assert_eq!(digest(), digest());
+  }
+
+  #[test]
+  fn digests_are_chained() {
+    let previous = StateHasher::new(1, None).finish();
+
+    assert_ne!(
+      StateHasher::new(2, Some(previous)).finish(),
+      StateHasher::new(2, None).finish()
+    );
+    assert_ne!(
+      StateHasher::new(2, Some(previous)).finish(),
+      StateHasher::new(3, Some(previous)).finish()
+    );
+  }
+
+  #[test]
+  fn digests_depend_on_order_and_content() {
+    let (script_key, balance) = balance();
+
+    let mut receipt_first = StateHasher::new(1, None);
+    receipt(&mut receipt_first, "tick: doge not found");
+    receipt_first.balance(&script_key, &balance);
+
+    let mut balance_first = StateHasher::new(1, None);
+    balance_first.balance(&script_key, &balance);
+    receipt(&mut balance_first, "tick: doge not found");
+
+    let mut other_error = StateHasher::new(1, None);
+    receipt(&mut other_error, "zero amount not allowed");
+    other_error.balance(&script_key, &balance);
+
+    let receipt_first = receipt_first.finish();
+
+    assert_ne!(receipt_first, balance_first.finish());
+    assert_ne!(receipt_first, other_error.finish());
+  }
+}
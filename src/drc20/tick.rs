@@ -131,6 +131,76 @@ pub fn max_script_tick_key(script: &ScriptKey) -> String {
   format!("{}_{}", script, LowerTick::max_hex())
 }
 
+/// Key of the `message`th DRC-20 message of transaction `tx_index` at
+/// `height` in `DRC20_TICK_ACTIVITY`, so a tick's activity sorts in execution
+/// order. Entries recorded before schema 18 are keyed by inscription id
+/// instead of `tx_index` and `message`.
+pub fn tick_activity_key(tick: &Tick, height: u64, tx_index: u32, message: u32) -> String {
+  format!(
+    "{}_{:010}_{:010}_{:010}",
+    tick.to_lowercase().hex(),
+    height,
+    tx_index,
+    message
+  )
+}
+
+pub fn min_tick_activity_key(tick: &Tick) -> String {
+  format!("{}_", tick.to_lowercase().hex())
+}
+
+pub fn max_tick_activity_key(tick: &Tick) -> String {
+  // `_` is followed by `` ` `` in byte order, so this bounds every key of the tick
+  format!("{}`", tick.to_lowercase().hex())
+}
+
+pub fn tick_holder_key(tick: &Tick, script: &ScriptKey) -> String {
+  format!("{}_{}", tick.to_lowercase().hex(), script)
+}
+
+/// Key of `script`'s overall `balance` of `tick` in `DRC20_TICK_BALANCE_HOLDER`.
+/// Balances are zero-padded to the 39 digits of `u128::MAX`, so a tick's keys
+/// sort by balance.
+pub fn tick_balance_holder_key(tick: &Tick, balance: u128, script: &str) -> String {
+  format!("{}_{:039}_{}", tick.to_lowercase().hex(), balance, script)
+}
+
+/// Returns the script key of a `DRC20_TICK_BALANCE_HOLDER` key.
+pub fn deserialize_tick_balance_holder_key(serialized: &str) -> Option<&str> {
+  serialized.splitn(3, '_').nth(2)
+}
+
+pub fn min_tick_holder_key(tick: &Tick) -> String {
+  min_tick_activity_key(tick)
+}
+
+pub fn max_tick_holder_key(tick: &Tick) -> String {
+  max_tick_activity_key(tick)
+}
+
+pub fn balance_history_key(tick: &Tick, script: &ScriptKey, height: u32) -> String {
+  format!("{}_{}_{:010}", tick.to_lowercase().hex(), script, height)
+}
+
+pub fn min_balance_history_key(tick: &Tick, script: &ScriptKey) -> String {
+  format!("{}_{}_", tick.to_lowercase().hex(), script)
+}
+
+pub fn min_tick_balance_history_key(tick: &Tick) -> String {
+  min_tick_activity_key(tick)
+}
+
+pub fn max_tick_balance_history_key(tick: &Tick) -> String {
+  max_tick_activity_key(tick)
+}
+
+/// Splits a balance history key into its script key and height.
+pub fn deserialize_balance_history_key(serialized: &str) -> Option<(&str, u32)> {
+  let (tick_and_script, height) = serialized.rsplit_once('_')?;
+  let (_, script) = tick_and_script.split_once('_')?;
+  Some((script, height.parse().ok()?))
+}
+
 pub fn deserialize_script_tick_key(
   serialized: &str,
   network: Network,
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 
@@ -14,6 +14,8 @@ pub struct TokenInfo {
   pub inscription_number: u64,
   pub supply: u128,
   pub minted: u128,
+  /// Amount sent to unspendable outputs or spent as fee.
+  pub burned: u128,
   pub limit_per_mint: u128,
   pub decimal: u8,
   pub deploy_by: ScriptKey,
@@ -22,9 +24,32 @@ pub struct TokenInfo {
   pub latest_mint_number: u64,
 }
 
+impl TokenInfo {
+  pub fn circulating(&self) -> u128 {
+    self.minted.saturating_sub(self.burned)
+  }
+}
+
+/// `TokenInfo` as served by the tick endpoints.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TickInfo {
+  #[serde(flatten)]
+  pub token: TokenInfo,
+  pub circulating: u128,
+}
+
+impl From<TokenInfo> for TickInfo {
+  fn from(token: TokenInfo) -> Self {
+    Self {
+      circulating: token.circulating(),
+      token,
+    }
+  }
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct ExtendedTokenInfo {
-  pub token_info: Option<TokenInfo>,
+  pub token_info: Option<TickInfo>,
   pub holder_info: HoldersInfoForTick,
 }
 
@@ -37,6 +62,13 @@ pub struct HolderBalanceForTick {
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct HoldersInfoForTick {
-  pub holder_to_balance: HashMap<String, HolderBalanceForTick>,
-  pub nr_of_holder: usize,
+  pub holder_to_balance: BTreeMap<String, HolderBalanceForTick>,
+  /// Left out of pages at a past height, which would have to read the
+  /// tick's whole balance history to count them.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub nr_of_holder: Option<usize>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub height: Option<u32>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub next_cursor: Option<String>,
 }
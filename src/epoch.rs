@@ -1,78 +1,248 @@
-use super::*;
-use serde_json;
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufReader;
-use std::path::Path;
-use lazy_static::lazy_static;
-use serde::{Deserialize, Serialize};
-use once_cell::sync::Lazy;
-use crate::sat::Sat;
+use {
+  super::*, crate::decimal_sat::DecimalSat, crate::milestone::Milestone,
+  bitcoin::blockdata::constants::COIN_VALUE, once_cell::sync::Lazy,
+};
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Display, PartialOrd)]
 pub(crate) struct Epoch(pub(crate) u32);
 
-fn read_sat_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<Sat>, Box<dyn std::error::Error>> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let sats: Vec<u64> = serde_json::from_reader(reader)?;
+/// Subsidies of the blocks before `FIXED_SUBSIDY_HEIGHT`, whose rewards dogecoin
+/// drew at random, as little-endian `u64`s taken from mainnet coinbases.
+const RANDOM_SUBSIDIES: &[u8] = include_bytes!("../subsidies.bin");
 
-    Ok(sats.into_iter().map(Sat).collect())
+/// Every block before this height is an epoch of its own. From here on the
+/// subsidy halves every epoch until it stays at 10,000 DOGE.
+const FIXED_SUBSIDY_HEIGHT: u32 = 145_000;
+
+const FIXED_SUBSIDIES: [(u32, u64); 6] = [
+  (145_000, 250_000 * COIN_VALUE),
+  (200_000, 125_000 * COIN_VALUE),
+  (300_000, 62_500 * COIN_VALUE),
+  (400_000, 31_250 * COIN_VALUE),
+  (500_000, 15_625 * COIN_VALUE),
+  (600_000, 10_000 * COIN_VALUE),
+];
+
+/// The subsidy and first sat of every epoch on mainnet.
+struct Compiled {
+  subsidies: Vec<u64>,
+  starting_sats: Vec<Sat>,
 }
 
-lazy_static! {
-    pub(crate) static ref STARTING_SATS: Vec<Sat> = {
-        let path = env::var("STARTING_SATS_PATH").expect("STARTING_SATS_PATH must be set");
-        read_sat_from_file(&path).expect("Failed to read JSON")
-    };
+impl Compiled {
+  fn new() -> Self {
+    let subsidies = RANDOM_SUBSIDIES
+      .chunks_exact(8)
+      .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+      .chain(FIXED_SUBSIDIES.iter().map(|(_, subsidy)| *subsidy))
+      .collect::<Vec<u64>>();
+
+    assert_eq!(subsidies.len(), usize::try_from(Epoch::LAST.0).unwrap() + 1);
+
+    let mut starting_sats = vec![Sat(0); subsidies.len()];
+
+    for epoch in 1..subsidies.len() {
+      let blocks = Epoch(u32::try_from(epoch).unwrap()).starting_height().n()
+        - Epoch(u32::try_from(epoch - 1).unwrap()).starting_height().n();
+      starting_sats[epoch] = starting_sats[epoch - 1] + subsidies[epoch - 1] * u64::from(blocks);
+    }
+
+    Self {
+      subsidies,
+      starting_sats,
+    }
+  }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Epochs {
-    epochs: HashMap<u32, u64>,
+static COMPILED: Lazy<Compiled> = Lazy::new(Compiled::new);
+
+static MAINNET: Lazy<Schedule> = Lazy::new(|| Schedule::new(Chain::Mainnet));
+
+/// How sats are numbered on a chain. On mainnet that is the compiled
+/// schedule. Elsewhere the subsidies an index recorded from genesis on take
+/// the place of the compiled ones, which number the blocks after them.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Schedule {
+  chain: Chain,
+  /// Recorded subsidies of the first `subsidies.len()` blocks.
+  subsidies: Vec<u64>,
+  /// First sat of each of those blocks, and of the block after them.
+  starting_sats: Vec<Sat>,
 }
 
-static EPOCHS: Lazy<Epochs> = Lazy::new(|| {
-    let path = env::var("SUBSIDIES_PATH").expect("SUBSIDIES_PATH must be set");
-    let data = fs::read_to_string(&path).expect("Unable to read file");
-    serde_json::from_str(&data).expect("Unable to parse JSON")
-});
+impl Schedule {
+  pub(crate) fn new(chain: Chain) -> Self {
+    Self {
+      chain,
+      subsidies: Vec::new(),
+      starting_sats: vec![Sat(0)],
+    }
+  }
+
+  /// The compiled schedule, which numbers sats wherever there is no index.
+  pub(crate) fn mainnet() -> &'static Self {
+    &MAINNET
+  }
+
+  /// Records the subsidy the coinbase of the block at `height` claims, and
+  /// returns whether it now numbers that block. Only the block after the last
+  /// recorded one counts, and only before `FIXED_SUBSIDY_HEIGHT`, so a chain
+  /// with a block that wasn't recorded keeps the compiled subsidies from that
+  /// block on. Mainnet always keeps them.
+  pub(crate) fn record(&mut self, height: Height, subsidy: u64) -> bool {
+    if self.chain == Chain::Mainnet
+      || height.n() >= FIXED_SUBSIDY_HEIGHT
+      || usize::try_from(height.n()).unwrap() != self.subsidies.len()
+    {
+      return false;
+    }
+
+    let next = *self.starting_sats.last().unwrap() + subsidy;
+    self.subsidies.push(subsidy);
+    self.starting_sats.push(next);
+
+    true
+  }
+
+  pub(crate) fn epoch_subsidy(&self, epoch: Epoch) -> u64 {
+    let epoch = usize::try_from(epoch.0).unwrap();
+
+    match self
+      .subsidies
+      .get(epoch)
+      .or_else(|| COMPILED.subsidies.get(epoch))
+    {
+      Some(&value) => value,
+      None => panic!("bad epoch"),
+    }
+  }
+
+  pub(crate) fn epoch_starting_sat(&self, epoch: Epoch) -> Sat {
+    let epoch = usize::try_from(epoch.0).unwrap();
+
+    if let Some(starting_sat) = self.starting_sats.get(epoch) {
+      return *starting_sat;
+    }
+
+    let compiled = &COMPILED.starting_sats;
+    let recorded = self.subsidies.len();
+
+    *self.starting_sats.last().unwrap()
+      + (compiled.get(epoch).unwrap_or_else(|| compiled.last().unwrap()).n() - compiled[recorded].n())
+  }
+
+  pub(crate) fn epoch(&self, sat: Sat) -> Epoch {
+    let recorded_end = *self.starting_sats.last().unwrap();
+
+    let epoch = if sat < recorded_end {
+      self
+        .starting_sats
+        .partition_point(|starting_sat| *starting_sat <= sat)
+        - 1
+    } else {
+      let compiled = &COMPILED.starting_sats;
+      let sat = Sat(
+        compiled[self.subsidies.len()]
+          .n()
+          .saturating_add(sat.n() - recorded_end.n()),
+      );
+      compiled.partition_point(|starting_sat| *starting_sat <= sat) - 1
+    };
+
+    Epoch(u32::try_from(epoch).unwrap())
+  }
+
+  pub(crate) fn subsidy(&self, height: Height) -> u64 {
+    self.epoch_subsidy(height.into())
+  }
+
+  pub(crate) fn starting_sat(&self, height: Height) -> Sat {
+    let epoch = Epoch::from(height);
+    self.epoch_starting_sat(epoch)
+      + u64::from(height.n() - epoch.starting_height().n()) * self.epoch_subsidy(epoch)
+  }
+
+  pub(crate) fn height(&self, sat: Sat) -> Height {
+    let epoch = self.epoch(sat);
+    epoch.starting_height()
+      + u32::try_from(self.epoch_position(sat) / self.epoch_subsidy(epoch)).unwrap()
+  }
+
+  pub(crate) fn epoch_position(&self, sat: Sat) -> u64 {
+    sat.n() - self.epoch_starting_sat(self.epoch(sat)).n()
+  }
+
+  pub(crate) fn third(&self, sat: Sat) -> u64 {
+    self.epoch_position(sat) % self.epoch_subsidy(self.epoch(sat))
+  }
+
+  pub(crate) fn is_common(&self, sat: Sat) -> bool {
+    self.third(sat) != 0
+  }
+
+  pub(crate) fn milestone(&self, sat: Sat) -> Option<Milestone> {
+    if self.is_common(sat) {
+      return None;
+    }
+
+    Milestone::of_block(self.height(sat))
+  }
+
+  pub(crate) fn rarity(&self, sat: Sat) -> Rarity {
+    match self.milestone(sat) {
+      Some(milestone) => milestone.rarity(),
+      None if self.is_common(sat) => Rarity::Common,
+      None => Rarity::Uncommon,
+    }
+  }
+
+  pub(crate) fn decimal(&self, sat: Sat) -> DecimalSat {
+    DecimalSat {
+      height: self.height(sat),
+      offset: self.third(sat),
+    }
+  }
+
+  /// Parses a sat number, or a block height and offset joined by a period.
+  pub(crate) fn parse_sat(&self, s: &str) -> Result<Sat> {
+    let Some((height, offset)) = s.split_once('.') else {
+      return Ok(Sat(s.parse()?));
+    };
+
+    let height = Height(height.parse()?);
+    let offset = offset.parse::<u64>()?;
+
+    if offset >= self.subsidy(height) {
+      bail!("invalid block offset");
+    }
+
+    Ok(self.starting_sat(height) + offset)
+  }
+}
 
 impl Epoch {
-  pub fn get_starting_sats() -> &'static Vec<Sat> {
-    &STARTING_SATS
+  pub(crate) const LAST: Self = Self(145_005);
+
+  pub fn get_starting_sats() -> Vec<Sat> {
+    COMPILED.starting_sats.clone()
   }
 
   pub(crate) fn subsidy(self) -> u64 {
-      match EPOCHS.epochs.get(&self.0) {
-          Some(&value) => value,
-          None => panic!("bad epoch"),
-      }
+    Schedule::mainnet().epoch_subsidy(self)
   }
 
   pub(crate) fn starting_sat(self) -> Sat {
-    *Self::get_starting_sats()
-      .get(usize::try_from(self.0).unwrap())
-      .unwrap_or_else(|| Self::get_starting_sats().last().unwrap())
+    Schedule::mainnet().epoch_starting_sat(self)
   }
 
   pub(crate) fn starting_height(self) -> Height {
-    if self.0 < 145_000 {
-      Height(self.0)
-    } else if self.0 < 145_001 {
-      Height(145_000)
-    } else if self.0 < 145_002 {
-      Height(200_000)
-    } else if self.0 < 145_003 {
-      Height(300_000)
-    } else if self.0 < 145_004 {
-      Height(400_000)
-    } else if self.0 < 145_005 {
-      Height(500_000)
-    } else if self.0 < 145_006 {
-      Height(600_000)
-    } else {
-      panic!("bad epoch")
+    if self.0 < FIXED_SUBSIDY_HEIGHT {
+      return Height(self.0);
+    }
+
+    match FIXED_SUBSIDIES.get(usize::try_from(self.0 - FIXED_SUBSIDY_HEIGHT).unwrap()) {
+      Some((height, _)) => Height(*height),
+      None => panic!("bad epoch"),
     }
   }
 }
@@ -85,89 +255,124 @@ impl PartialEq<u32> for Epoch {
 
 impl From<Sat> for Epoch {
   fn from(sat: Sat) -> Self {
-    let starting_sats = Self::get_starting_sats();
-
-    let len = starting_sats.len();
-    for i in 0..len-1 {
-      if sat < starting_sats[i+1] {
-        return Epoch(i as u32);
-      }
-    }
-
-    // If none of the starting sats is greater than the given sat, return the last Epoch
-    Epoch(145_005)
+    Schedule::mainnet().epoch(sat)
   }
 }
 
 impl From<Height> for Epoch {
   fn from(height: Height) -> Self {
-    if height.0 < 145_000 {
-      Epoch(height.0)
-    } else if height.0 < 200_000 {
-      Epoch(145_000)
-    } else if height.0 < 300_000 {
-      Epoch(145_001)
-    } else if height.0 < 400_000 {
-      Epoch(145_002)
-    } else if height.0 < 500_000 {
-      Epoch(145_003)
-    } else if height.0 < 600_000 {
-      Epoch(145_004)
-    } else {
-      Epoch(145_005)
+    if height.0 < FIXED_SUBSIDY_HEIGHT {
+      return Epoch(height.0);
     }
+
+    let fixed =
+      FIXED_SUBSIDIES.partition_point(|(starting_height, _)| *starting_height <= height.0);
+
+    Epoch(FIXED_SUBSIDY_HEIGHT + u32::try_from(fixed).unwrap() - 1)
   }
 }
 
 #[cfg(test)]
 mod tests {
-  use super::super::*;
+  use super::*;
 
   #[test]
-  fn starting_sat() {
-    assert_eq!(Epoch(0).starting_sat(), 0);
+  fn compiled_subsidies() {
+    let schedule = Compiled::new();
+    assert_eq!(schedule.subsidies[0], 88 * COIN_VALUE);
+    assert_eq!(schedule.subsidies[1], 68_416 * COIN_VALUE);
+    assert_eq!(schedule.subsidies[2], 729_752 * COIN_VALUE);
+    assert_eq!(schedule.subsidies[145_000], 250_000 * COIN_VALUE);
+  }
+
+  #[test]
+  fn compiled_starting_sats() {
+    let schedule = Compiled::new();
+    assert_eq!(schedule.starting_sats[0], 0);
+    assert_eq!(schedule.starting_sats[1], 88 * COIN_VALUE);
+    assert_eq!(schedule.starting_sats[2], (88 + 68_416) * COIN_VALUE);
+
+    for epoch in 1..FIXED_SUBSIDY_HEIGHT as usize {
+      assert_eq!(
+        schedule.starting_sats[epoch - 1] + schedule.subsidies[epoch - 1],
+        schedule.starting_sats[epoch]
+      );
+    }
+
+    assert_eq!(
+      schedule.starting_sats[145_001],
+      schedule.starting_sats[145_000] + 55_000 * 250_000 * COIN_VALUE
+    );
   }
 
   #[test]
   fn subsidy() {
-    assert_eq!(Epoch(0).subsidy(), 1_000_000 * COIN_VALUE);
-    assert_eq!(Epoch(1).subsidy(), 500_000 * COIN_VALUE);
-    // assert_eq!(Epoch(32).subsidy(), 1);
-    // assert_eq!(Epoch(33).subsidy(), 0);
+    assert_eq!(Epoch(145_000).subsidy(), 250_000 * COIN_VALUE);
+    assert_eq!(Epoch(145_001).subsidy(), 125_000 * COIN_VALUE);
+    assert_eq!(Epoch(145_004).subsidy(), 15_625 * COIN_VALUE);
+    assert_eq!(Epoch::LAST.subsidy(), 10_000 * COIN_VALUE);
+  }
+
+  #[test]
+  #[should_panic(expected = "bad epoch")]
+  fn subsidy_after_last_epoch() {
+    Epoch(Epoch::LAST.0 + 1).subsidy();
+  }
+
+  #[test]
+  fn starting_sat() {
+    assert_eq!(Epoch(0).starting_sat(), 0);
+    assert_eq!(
+      Epoch(145_001).starting_sat(),
+      Epoch(145_000).starting_sat() + 55_000 * 250_000 * COIN_VALUE
+    );
+    assert_eq!(
+      Epoch(Epoch::LAST.0 + 1).starting_sat(),
+      Epoch::LAST.starting_sat()
+    );
   }
 
   #[test]
   fn starting_height() {
     assert_eq!(Epoch(0).starting_height(), 0);
-    assert_eq!(Epoch(1).starting_height(), 100_000);
-    assert_eq!(Epoch(2).starting_height(), 145_000);
+    assert_eq!(Epoch(1).starting_height(), 1);
+    assert_eq!(Epoch(144_999).starting_height(), 144_999);
+    assert_eq!(Epoch(145_000).starting_height(), 145_000);
+    assert_eq!(Epoch(145_001).starting_height(), 200_000);
+    assert_eq!(Epoch::LAST.starting_height(), 600_000);
   }
 
   #[test]
   fn from_height() {
     assert_eq!(Epoch::from(Height(0)), 0);
-    assert_eq!(Epoch::from(Height(100_000)), 1);
-    assert_eq!(Epoch::from(Height(150_000)), 2);
-    assert_eq!(Epoch::from(Height(200_000)), 3);
+    assert_eq!(Epoch::from(Height(144_999)), 144_999);
+    assert_eq!(Epoch::from(Height(145_000)), 145_000);
+    assert_eq!(Epoch::from(Height(199_999)), 145_000);
+    assert_eq!(Epoch::from(Height(200_000)), 145_001);
+    assert_eq!(Epoch::from(Height(599_999)), 145_004);
+    assert_eq!(Epoch::from(Height(600_000)), Epoch::LAST);
+    assert_eq!(Epoch::from(Height(u32::MAX)), Epoch::LAST);
   }
 
   #[test]
   fn from_sat() {
-    for (epoch, starting_sat) in Epoch::get_starting_sats().into_iter().enumerate() {
-      if epoch > 0 {
-        assert_eq!(
-          Epoch::from(Sat(starting_sat.n() - 1)),
-          Epoch(epoch as u64 - 1)
-        );
-      }
-      assert_eq!(Epoch::from(starting_sat), Epoch(epoch as u64));
-      assert_eq!(Epoch::from(starting_sat + 1), Epoch(epoch as u64));
+    for epoch in [145_000, 145_001, Epoch::LAST.0] {
+      let starting_sat = Epoch(epoch).starting_sat();
+      assert_eq!(Epoch::from(Sat(starting_sat.n() - 1)), Epoch(epoch - 1));
+      assert_eq!(Epoch::from(starting_sat), Epoch(epoch));
+      assert_eq!(Epoch::from(starting_sat + 1), Epoch(epoch));
     }
     assert_eq!(Epoch::from(Sat(0)), 0);
-    assert_eq!(Epoch::from(Sat(1)), 0);
-    assert_eq!(Epoch::from(Epoch(1).starting_sat()), 1);
-    assert_eq!(Epoch::from(Epoch(1).starting_sat() + 1), 1);
-    // assert_eq!(Epoch::from(Sat(u128::max_value())), 33);
+    assert_eq!(Epoch::from(Sat(u64::MAX)), Epoch::LAST);
+  }
+
+  #[test]
+  fn starting_heights_and_sats_agree() {
+    for epoch in [0, 1, 144_999, 145_000, 145_003, Epoch::LAST.0] {
+      let epoch = Epoch(epoch);
+      assert_eq!(Epoch::from(epoch.starting_height()), epoch);
+      assert_eq!(Epoch::from(epoch.starting_sat()), epoch);
+    }
   }
 
   #[test]
@@ -175,6 +380,53 @@ mod tests {
     assert_eq!(Epoch(0), 0);
     assert_eq!(Epoch(100), 100);
   }
-}
 
+  #[test]
+  fn mainnet_ignores_recorded_subsidies() {
+    let mut schedule = Schedule::new(Chain::Mainnet);
+    assert!(!schedule.record(Height(0), 50 * COIN_VALUE));
+    assert_eq!(&schedule, Schedule::mainnet());
+    assert_eq!(schedule.subsidy(Height(0)), 88 * COIN_VALUE);
+  }
+
+  #[test]
+  fn only_the_next_block_is_recorded() {
+    let mut schedule = Schedule::new(Chain::Regtest);
+    assert!(!schedule.record(Height(1), 50 * COIN_VALUE));
+    assert!(schedule.record(Height(0), 50 * COIN_VALUE));
+    assert!(!schedule.record(Height(0), 40 * COIN_VALUE));
+    assert!(schedule.record(Height(1), 40 * COIN_VALUE));
+    assert_eq!(schedule.subsidy(Height(0)), 50 * COIN_VALUE);
+    assert_eq!(schedule.subsidy(Height(1)), 40 * COIN_VALUE);
+    assert!(!schedule.record(Height(FIXED_SUBSIDY_HEIGHT), 50 * COIN_VALUE));
+  }
 
+  #[test]
+  fn recorded_subsidies_number_sats() {
+    let mut schedule = Schedule::new(Chain::Regtest);
+    schedule.record(Height(0), 50 * COIN_VALUE);
+    schedule.record(Height(1), 40 * COIN_VALUE);
+
+    assert_eq!(schedule.starting_sat(Height(1)), 50 * COIN_VALUE);
+    assert_eq!(schedule.starting_sat(Height(2)), 90 * COIN_VALUE);
+    assert_eq!(schedule.height(Sat(90 * COIN_VALUE - 1)), 1);
+    assert_eq!(schedule.height(Sat(90 * COIN_VALUE)), 2);
+    assert_eq!(schedule.subsidy(Height(2)), Epoch(2).subsidy());
+    assert_eq!(
+      schedule.starting_sat(Height(3)),
+      Sat(90 * COIN_VALUE) + Epoch(2).subsidy()
+    );
+    assert_eq!(schedule.third(Sat(50 * COIN_VALUE + 7)), 7);
+    assert_eq!(schedule.rarity(Sat(50 * COIN_VALUE)), Rarity::Uncommon);
+    assert_eq!(
+      schedule.parse_sat("2.5").unwrap(),
+      Sat(90 * COIN_VALUE + 5)
+    );
+    assert!(schedule.parse_sat("1.4000000000").is_err());
+
+    for height in [0, 1, 2, 3, FIXED_SUBSIDY_HEIGHT, 600_000] {
+      let height = Height(height);
+      assert_eq!(schedule.height(schedule.starting_sat(height)), height);
+    }
+  }
+}
@@ -5,6 +5,12 @@ use super::*;
 pub(crate) struct Height(pub(crate) u32);
 
 impl Height {
+  /// First block retargeting difficulty every block, with DigiShield.
+  pub(crate) const DIGISHIELD: Self = Self(145_000);
+
+  /// First block that may be merge mined, carrying an AuxPoW header.
+  pub(crate) const AUXPOW: Self = Self(371_337);
+
   pub(crate) fn n(self) -> u32 {
     self.0
   }
@@ -80,10 +86,23 @@ mod tests {
 
   #[test]
   fn subsidy() {
-    assert_eq!(Height(0).subsidy(), 1_000_000 * COIN_VALUE);
-    assert_eq!(Height(1).subsidy(), 1_000_000 * COIN_VALUE);
-    assert_eq!(Height(100_000).subsidy(), 500_000 * COIN_VALUE);
+    assert_eq!(Height(145_000).subsidy(), 250_000 * COIN_VALUE);
     assert_eq!(Height(199_999).subsidy(), 250_000 * COIN_VALUE);
     assert_eq!(Height(201_000).subsidy(), 125_000 * COIN_VALUE);
+    assert_eq!(Height(600_000).subsidy(), 10_000 * COIN_VALUE);
+    assert_eq!(Height(u32::MAX).subsidy(), 10_000 * COIN_VALUE);
+  }
+
+  #[test]
+  fn starting_sat() {
+    assert_eq!(Height(0).starting_sat(), 0);
+    assert_eq!(
+      Height(145_001).starting_sat(),
+      Height(145_000).starting_sat() + 250_000 * COIN_VALUE
+    );
+    assert_eq!(
+      Height(200_000).starting_sat(),
+      Height(199_999).starting_sat() + 250_000 * COIN_VALUE
+    );
   }
 }
@@ -1,46 +1,58 @@
 use {
   self::{
+    addresses::AddressTables,
+    blk::BlkFiles,
     dunes::{Dune, DuneId},
     entry::{
-      BlockHashValue, DuneEntryValue, DuneIdValue, Entry, InscriptionEntry, InscriptionEntryValue,
-      InscriptionIdValue, OutPointMapValue, OutPointValue, SatPointValue, SatRange, TxidValue,
+      address_height_outpoint_key, address_outpoint_key, AddressHeightOutPointValue,
+      AddressOutPointValue, BlockHashValue, ContentHashValue, DuneEntryValue, DuneIdValue, Entry, InscriptionEntry, InscriptionEntryValue,
+      InscriptionIdValue, OutPointMapValue, OutPointValue, SatPointValue, TxidValue,
+      decode_sat_ranges, push_sat_range,
     },
+    block_source::{BlockSource, DirectoryBlockSource, RpcBlockSource},
     reorg::*,
     updater::Updater,
   },
   bitcoin::BlockHeader,
   bitcoincore_rpc::{Auth, Client, json::GetBlockHeaderResult},
   chrono::SubsecRound,
-  crate::inscription::ParsedInscription,
   crate::wallet::Wallet,
   indicatif::{ProgressBar, ProgressStyle},
   log::log_enabled,
   redb::{
-    Database, DatabaseError, MultimapTable, MultimapTableDefinition, ReadableMultimapTable,
-    ReadableTable, StorageError, Table, TableDefinition, WriteTransaction,
+    Database, DatabaseError, ReadableTable, StorageError, Table, TableDefinition, TableError,
+    WriteTransaction,
   },
   std::collections::HashMap,
-  std::io::Cursor,
   std::sync::atomic::{self, AtomicBool},
   super::*,
   url::Url,
 };
 
-use crate::drc20::{Balance, max_script_tick_key, min_script_tick_key, script_tick_key, Tick, TokenInfo, TransferableLog, min_script_tick_id_key, max_script_tick_id_key};
+use crate::drc20::{Balance, StateHash, max_script_tick_key, min_script_tick_key, script_tick_key, Tick, TickActivity, TokenInfo, TransferableLog, min_script_tick_id_key, max_script_tick_id_key, min_tick_activity_key, max_tick_activity_key, balance_history_key, min_balance_history_key, min_tick_balance_history_key, max_tick_balance_history_key, deserialize_balance_history_key, min_tick_holder_key, max_tick_holder_key, deserialize_tick_balance_holder_key};
 use crate::drc20::script_key::ScriptKey;
+use crate::auxpow::AuxPow;
 use crate::sat::Sat;
 use crate::sat_point::SatPoint;
 use crate::templates::BlockHashAndConfirmations;
 
-pub(crate) use self::entry::DuneEntry;
+pub(crate) use self::{
+  entry::DuneEntry,
+  updater::{backfill::OptionalIndex, overlay::WritableTable, pending::PendingBalances},
+};
 
 pub(crate) mod entry;
+mod addresses;
+mod blk;
+pub(crate) mod block_source;
+pub(crate) mod migration;
 mod reorg;
 mod fetcher;
 mod rtx;
+pub(crate) mod snapshot;
 mod updater;
 
-const SCHEMA_VERSION: u64 = 6;
+const SCHEMA_VERSION: u64 = 20;
 
 macro_rules! define_table {
   ($name:ident, $key:ty, $value:ty) => {
@@ -48,14 +60,9 @@ macro_rules! define_table {
   };
 }
 
-macro_rules! define_multimap_table {
-  ($name:ident, $key:ty, $value:ty) => {
-    const $name: MultimapTableDefinition<$key, $value> =
-      MultimapTableDefinition::new(stringify!($name));
-  };
-}
-
 define_table! { HEIGHT_TO_BLOCK_HASH, u32, &BlockHashValue }
+define_table! { HEIGHT_TO_SUBSIDY, u32, u64 }
+define_table! { HEIGHT_TO_AUXPOW, u32, &[u8] }
 define_table! { INSCRIPTION_ID_TO_INSCRIPTION_ENTRY, &InscriptionIdValue, InscriptionEntryValue }
 define_table! { INSCRIPTION_ID_TO_DUNE, &InscriptionIdValue, u128 }
 define_table! { INSCRIPTION_ID_TO_SATPOINT, &InscriptionIdValue, &SatPointValue }
@@ -63,10 +70,15 @@ define_table! { INSCRIPTION_NUMBER_TO_INSCRIPTION_ID, u64, &InscriptionIdValue }
 define_table! { OUTPOINT_TO_DUNE_BALANCES, &OutPointValue, &[u8] }
 define_table! { INSCRIPTION_ID_TO_TXIDS, &InscriptionIdValue, &[u8] }
 define_table! { INSCRIPTION_TXID_TO_TX, &[u8], &[u8] }
+define_table! { INSCRIPTION_ID_TO_CONTENT_HASH, &InscriptionIdValue, &ContentHashValue }
+define_table! { CONTENT_HASH_TO_INSCRIPTION_CONTENT, &ContentHashValue, &[u8] }
 define_table! { PARTIAL_TXID_TO_INSCRIPTION_TXIDS, &[u8], &[u8] }
 define_table! { OUTPOINT_TO_SAT_RANGES, &OutPointValue, &[u8] }
 define_table! { OUTPOINT_TO_VALUE, &OutPointValue, u64}
-define_multimap_table! { ADDRESS_TO_OUTPOINT, &[u8], &OutPointValue}
+define_table! { ADDRESS_OUTPOINT_TO_OUTPUT, &AddressOutPointValue, (u64, u32) }
+define_table! { ADDRESS_HEIGHT_OUTPOINT_TO_VALUE, &AddressHeightOutPointValue, u64 }
+define_table! { ADDRESS_TO_BALANCE, &[u8; 34], (u64, u64) }
+define_table! { OUTPOINT_TO_ADDRESS, &OutPointValue, &[u8; 34] }
 define_table! { DUNE_ID_TO_DUNE_ENTRY, DuneIdValue, DuneEntryValue }
 define_table! { DUNE_TO_DUNE_ID, u128, DuneIdValue }
 define_table! { SATPOINT_TO_INSCRIPTION_ID, &SatPointValue, &InscriptionIdValue }
@@ -80,11 +92,22 @@ define_table! { DRC20_BALANCES, &str, &[u8] }
 define_table! { DRC20_TOKEN, &str, &[u8] }
 define_table! { DRC20_INSCRIBE_TRANSFER, &InscriptionIdValue, &[u8] }
 define_table! { DRC20_TRANSFERABLELOG, &str, &[u8] }
-define_multimap_table! { DRC20_TOKEN_HOLDER, &str, &str}
+define_table! { DRC20_TICK_HOLDER, &str, () }
+define_table! { DRC20_TICK_BALANCE_HOLDER, &str, () }
+define_table! { DRC20_TICK_TO_HOLDER_COUNT, &str, u64 }
+define_table! { DRC20_TICK_ACTIVITY, &str, &[u8] }
+define_table! { DRC20_HEIGHT_TO_STATE_HASH, u32, &[u8; 32] }
+define_table! { DRC20_BALANCE_HISTORY, &str, &[u8] }
 
 pub(crate) struct Index {
-  auth: Auth,
+  block_source: Arc<dyn BlockSource>,
+  /// Blocks found in the node's blk files, scanned on first use and
+  /// refreshed by later updates.
+  blk_files: Arc<Mutex<Option<BlkFiles>>>,
+  blocks_dir: Option<PathBuf>,
   client: Client,
+  commit_cache_size: usize,
+  commit_interval: u32,
   database: Database,
   path: PathBuf,
   first_inscription_height: u32,
@@ -97,9 +120,14 @@ pub(crate) struct Index {
   index_sats: bool,
   index_transactions: bool,
   unrecoverably_reorged: AtomicBool,
-  rpc_url: String,
-  nr_parallel_requests: usize,
+  /// Height from which `OUTPOINT_TO_VALUE` holds every unspent output, either
+  /// genesis with `--index-utxos` or the block after a loaded UTXO snapshot.
+  utxos_from: Option<u32>,
   chain: Chain,
+  /// How the mempool changes DRC-20 balances, refreshed by the indexer.
+  drc20_pending: Mutex<PendingBalances>,
+  /// How sats are numbered, replaced by the updater after every commit.
+  schedule: Mutex<Arc<Schedule>>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -122,6 +150,10 @@ pub(crate) enum Statistic {
   SatRanges,
   Schema,
   IndexTransactions,
+  IndexUtxos,
+  UtxosFrom,
+  CachedOutputs,
+  Drc20BalanceHistoryFrom,
 }
 
 impl Statistic {
@@ -140,6 +172,8 @@ impl From<Statistic> for u64 {
 pub(crate) struct Info {
   pub(crate) blocks_indexed: u32,
   pub(crate) branch_pages: u64,
+  /// Outputs that were held in memory when the index was last committed.
+  pub(crate) cached_outputs: u64,
   pub(crate) fragmented_bytes: u64,
   pub(crate) index_file_size: u64,
   pub(crate) index_path: PathBuf,
@@ -185,47 +219,74 @@ impl<T> BitcoinCoreRpcResultExt<T> for Result<T, bitcoincore_rpc::Error> {
 
 impl Index {
   pub(crate) fn open(options: &Options) -> Result<Self> {
-    let rpc_url = options.rpc_url();
-    let nr_parallel_requests = options.nr_parallel_requests();
-    let cookie_file = options.cookie_file()?;
-    // if cookie_file is emtpy / not set try to parse username:password from RPC URL to create the UserPass auth
-    let auth: Auth = if !cookie_file.exists() {
-      let url = Url::parse(&rpc_url)?;
-      let username = url.username().to_string();
-      let password = url.password().map(|x| x.to_string()).unwrap_or_default();
+    let block_source: Arc<dyn BlockSource> = match &options.replay_dir {
+      Some(replay_dir) => Arc::new(DirectoryBlockSource::open(replay_dir)?),
+      None => Arc::new(RpcBlockSource::new(
+        &options.rpc_url(),
+        Self::rpc_auth(options)?,
+        options.nr_parallel_requests(),
+      )?),
+    };
 
-      log::info!(
-        "Connecting to Dogecoin Core RPC server at {rpc_url} using credentials from the url"
-      );
+    Self::open_with_block_source(options, block_source)
+  }
 
-      Auth::UserPass(username, password)
-    } else {
-      log::info!(
-        "Connecting to Dogecoin Core RPC server at {rpc_url} using credentials from `{}`",
-        cookie_file.display()
-      );
+  /// Uses the cookie file if there is one, otherwise the username and
+  /// password from the RPC URL.
+  pub(crate) fn rpc_auth(options: &Options) -> Result<Auth> {
+    let cookie_file = options.cookie_file()?;
 
-      Auth::CookieFile(cookie_file)
-    };
+    if cookie_file.exists() {
+      return Ok(Auth::CookieFile(cookie_file));
+    }
+
+    let url = Url::parse(&options.rpc_url())?;
+    let username = url.username().to_string();
+    let password = url.password().map(|x| x.to_string()).unwrap_or_default();
 
-    let client = Client::new(&rpc_url, auth.clone()).context("failed to connect to RPC URL")?;
+    Ok(Auth::UserPass(username, password))
+  }
 
+  pub(crate) fn index_path(options: &Options) -> Result<PathBuf> {
     let data_dir = options.data_dir()?;
 
     if let Err(err) = fs::create_dir_all(&data_dir) {
       bail!("failed to create data dir `{}`: {err}", data_dir.display());
     }
 
-    let path = if let Some(path) = &options.index {
+    Ok(if let Some(path) = &options.index {
       path.clone()
     } else {
       data_dir.join("index.redb")
-    };
+    })
+  }
+
+  pub(crate) fn open_with_block_source(
+    options: &Options,
+    block_source: Arc<dyn BlockSource>,
+  ) -> Result<Self> {
+    let rpc_url = options.rpc_url();
+    let auth = Self::rpc_auth(options)?;
+
+    match &auth {
+      Auth::CookieFile(cookie_file) => log::info!(
+        "Connecting to Dogecoin Core RPC server at {rpc_url} using credentials from `{}`",
+        cookie_file.display()
+      ),
+      _ => log::info!(
+        "Connecting to Dogecoin Core RPC server at {rpc_url} using credentials from the url"
+      ),
+    }
+
+    let client = Client::new(&rpc_url, auth).context("failed to connect to RPC URL")?;
+
+    let path = Self::index_path(options)?;
 
     let index_drc20;
     let index_dunes;
     let index_sats;
     let index_transactions;
+    let utxos_from;
 
     let database = match unsafe { Database::builder().open(&path) } {
       Ok(database) => {
@@ -238,6 +299,11 @@ impl Index {
             .unwrap_or(0);
 
           match schema_version.cmp(&SCHEMA_VERSION) {
+            cmp::Ordering::Less if migration::plan(schema_version).is_ok() =>
+              bail!(
+              "index at `{}` was built with index schema {schema_version}, run `ord index migrate` to upgrade it to ord schema {SCHEMA_VERSION}",
+              path.display()
+            ),
             cmp::Ordering::Less =>
               bail!(
               "index at `{}` appears to have been built with an older, incompatible version of ord, consider deleting and rebuilding the index: index schema {schema_version}, ord schema {SCHEMA_VERSION}",
@@ -273,6 +339,23 @@ impl Index {
             .unwrap()
             .value()
             != 0;
+
+          let index_utxos = statistics
+            .get(&Statistic::IndexUtxos.key())?
+            .map(|x| x.value())
+            .unwrap_or_default()
+            != 0;
+
+          utxos_from = if index_utxos {
+            Some(
+              statistics
+                .get(&Statistic::UtxosFrom.key())?
+                .map(|x| u32::try_from(x.value()).unwrap())
+                .unwrap_or_default(),
+            )
+          } else {
+            None
+          };
         }
 
         database
@@ -303,15 +386,22 @@ impl Index {
         };
 
         tx.open_table(HEIGHT_TO_BLOCK_HASH)?;
+        tx.open_table(HEIGHT_TO_SUBSIDY)?;
+        tx.open_table(HEIGHT_TO_AUXPOW)?;
         tx.open_table(INSCRIPTION_ID_TO_INSCRIPTION_ENTRY)?;
         tx.open_table(INSCRIPTION_ID_TO_DUNE)?;
         tx.open_table(INSCRIPTION_ID_TO_SATPOINT)?;
         tx.open_table(INSCRIPTION_NUMBER_TO_INSCRIPTION_ID)?;
         tx.open_table(INSCRIPTION_ID_TO_TXIDS)?;
         tx.open_table(INSCRIPTION_TXID_TO_TX)?;
+        tx.open_table(INSCRIPTION_ID_TO_CONTENT_HASH)?;
+        tx.open_table(CONTENT_HASH_TO_INSCRIPTION_CONTENT)?;
         tx.open_table(PARTIAL_TXID_TO_INSCRIPTION_TXIDS)?;
         tx.open_table(OUTPOINT_TO_VALUE)?;
-        tx.open_multimap_table(ADDRESS_TO_OUTPOINT)?;
+        tx.open_table(ADDRESS_OUTPOINT_TO_OUTPUT)?;
+        tx.open_table(ADDRESS_HEIGHT_OUTPOINT_TO_VALUE)?;
+        tx.open_table(ADDRESS_TO_BALANCE)?;
+        tx.open_table(OUTPOINT_TO_ADDRESS)?;
         tx.open_table(SATPOINT_TO_INSCRIPTION_ID)?;
         tx.open_table(SAT_TO_INSCRIPTION_ID)?;
         tx.open_table(SAT_TO_SATPOINT)?;
@@ -325,10 +415,11 @@ impl Index {
             outpoint_to_sat_ranges.insert(&OutPoint::null().store(), [].as_slice())?;
           }
 
-          index_drc20 = options.index_dunes();
+          index_drc20 = options.index_drc20;
           index_dunes = options.index_dunes();
           index_sats = options.index_sats;
           index_transactions = options.index_transactions;
+          utxos_from = options.index_utxos.then_some(0);
 
           statistics.insert(&Statistic::IndexDrc20.key(), &u64::from(index_drc20))?;
 
@@ -341,6 +432,8 @@ impl Index {
             &u64::from(index_transactions),
           )?;
 
+          statistics.insert(&Statistic::IndexUtxos.key(), &u64::from(options.index_utxos))?;
+
           statistics.insert(&Statistic::Schema.key(), &SCHEMA_VERSION)?;
         }
 
@@ -351,13 +444,19 @@ impl Index {
       Err(error) => return Err(error.into()),
     };
 
+    let schedule = Self::load_schedule(&database, options.chain_argument)?;
+
     let genesis_block_coinbase_transaction =
       options.chain().genesis_block().coinbase().unwrap().clone();
 
     Ok(Self {
       genesis_block_coinbase_txid: genesis_block_coinbase_transaction.txid(),
-      auth,
+      block_source,
+      blk_files: Arc::new(Mutex::new(None)),
+      blocks_dir: options.blocks_dir()?,
       client,
+      commit_cache_size: options.commit_cache_size(),
+      commit_interval: options.commit_interval(),
       database,
       path,
       first_inscription_height: options.first_inscription_height(),
@@ -369,12 +468,37 @@ impl Index {
       index_sats,
       index_transactions,
       unrecoverably_reorged: AtomicBool::new(false),
-      rpc_url,
-      nr_parallel_requests,
+      utxos_from,
       chain: options.chain_argument,
+      drc20_pending: Mutex::new(PendingBalances::default()),
+      schedule: Mutex::new(Arc::new(schedule)),
     })
   }
 
+  /// The schedule for `chain` with the subsidies recorded in `database`.
+  fn load_schedule(database: &Database, chain: Chain) -> Result<Schedule> {
+    let mut schedule = Schedule::new(chain);
+
+    for result in database
+      .begin_read()?
+      .open_table(HEIGHT_TO_SUBSIDY)?
+      .iter()?
+    {
+      let (height, subsidy) = result?;
+
+      if !schedule.record(Height(height.value()), subsidy.value()) {
+        break;
+      }
+    }
+
+    Ok(schedule)
+  }
+
+  /// How this index numbers sats.
+  pub(crate) fn schedule(&self) -> Arc<Schedule> {
+    self.schedule.lock().unwrap().clone()
+  }
+
   pub(crate) fn get_unspent_outputs(&self, _wallet: Wallet) -> Result<BTreeMap<OutPoint, Amount>> {
     let mut utxos = BTreeMap::new();
     utxos.extend(
@@ -441,6 +565,10 @@ impl Index {
     self.index_sats
   }
 
+  pub(crate) fn path(&self) -> &Path {
+    &self.path
+  }
+
   pub(crate) fn info(&self) -> Result<Info> {
     let wtx = self.begin_write()?;
 
@@ -456,6 +584,10 @@ impl Index {
         .get(&Statistic::OutputsTraversed.key())?
         .map(|x| x.value())
         .unwrap_or(0);
+      let cached_outputs = statistic_to_count
+        .get(&Statistic::CachedOutputs.key())?
+        .map(|x| x.value())
+        .unwrap_or(0);
       let transactions: Vec<TransactionInfo> = wtx
         .open_table(WRITE_TRANSACTION_STARTING_BLOCK_COUNT_TO_TIMESTAMP)?
         .range(0..)?
@@ -479,6 +611,7 @@ impl Index {
           .transpose()?
           .unwrap_or(0),
         branch_pages: stats.branch_pages(),
+        cached_outputs,
         fragmented_bytes: stats.fragmented_bytes(),
         index_file_size: fs::metadata(&self.path)?.len(),
         leaf_pages: stats.leaf_pages(),
@@ -524,6 +657,103 @@ impl Index {
     }
   }
 
+  /// Seeds an empty index with the outputs unspent as of `height`, read as
+  /// `<TXID>:<VOUT> <VALUE>` lines, so that blocks up to `height` only need
+  /// their headers and later input values are resolved from the index.
+  pub(crate) fn load_utxo_snapshot(&self, height: u32, snapshot: impl io::BufRead) -> Result<u64> {
+    ensure!(
+      self.block_count()? == 0,
+      "UTXO snapshots can only be loaded into an empty index"
+    );
+
+    ensure!(
+      !self.index_sats,
+      "UTXO snapshots can't be used with --index-sats, which needs every block from genesis"
+    );
+
+    ensure!(
+      height < self.first_inscription_height,
+      "UTXO snapshot at height {height} must be below the first inscription height {}",
+      self.first_inscription_height
+    );
+
+    let wtx = self.begin_write()?;
+
+    let mut utxos = 0;
+
+    {
+      let mut outpoint_to_value = wtx.open_table(OUTPOINT_TO_VALUE)?;
+
+      for (i, line) in snapshot.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.is_empty() {
+          continue;
+        }
+
+        let (outpoint, value) = line
+          .split_once(' ')
+          .ok_or_else(|| anyhow!("invalid UTXO snapshot line {}: `{line}`", i + 1))?;
+
+        let outpoint = outpoint
+          .parse::<OutPoint>()
+          .with_context(|| format!("invalid outpoint on UTXO snapshot line {}", i + 1))?;
+
+        let value = value
+          .trim()
+          .parse::<u64>()
+          .with_context(|| format!("invalid value on UTXO snapshot line {}", i + 1))?;
+
+        outpoint_to_value.insert(&outpoint.store(), value)?;
+
+        utxos += 1;
+      }
+
+      let mut statistics = wtx.open_table(STATISTIC_TO_COUNT)?;
+      statistics.insert(&Statistic::IndexUtxos.key(), &1)?;
+      statistics.insert(&Statistic::UtxosFrom.key(), &u64::from(height + 1))?;
+    }
+
+    wtx.commit()?;
+
+    Ok(utxos)
+  }
+
+  /// Stores an inscription's content under its hash, so identical content,
+  /// such as repeated DRC-20 mints, is only stored once.
+  fn insert_inscription_content(
+    id_to_content_hash: &mut dyn WritableTable<
+      &'static InscriptionIdValue,
+      &'static ContentHashValue,
+    >,
+    content_hash_to_content: &mut dyn WritableTable<&'static ContentHashValue, &'static [u8]>,
+    inscription_id: &InscriptionIdValue,
+    inscription: &Inscription,
+  ) -> Result {
+    let content = inscription.to_bytes();
+    let content_hash = bitcoin::hashes::sha256::Hash::hash(&content).into_inner();
+
+    if content_hash_to_content.get(&content_hash)?.is_none() {
+      content_hash_to_content.insert(&content_hash, content.as_slice())?;
+    }
+
+    id_to_content_hash.insert(inscription_id, &content_hash)?;
+
+    Ok(())
+  }
+
+  /// Blocks below this height are only indexed for their headers.
+  fn full_blocks_from(&self) -> u32 {
+    if self.index_sats {
+      0
+    } else {
+      self
+        .first_inscription_height
+        .min(self.utxos_from.unwrap_or(u32::MAX))
+    }
+  }
+
   pub(crate) fn is_unrecoverably_reorged(&self) -> bool {
     self.unrecoverably_reorged.load(atomic::Ordering::Relaxed)
   }
@@ -653,6 +883,53 @@ impl Index {
     Ok(entry)
   }
 
+  /// Returns up to `limit` etched dunes whose name starts with `prefix`,
+  /// shortest names first. Dunes of a given length occupy a contiguous,
+  /// alphabetically ordered range of `DUNE_TO_DUNE_ID`, so each length is a
+  /// single range scan.
+  pub(crate) fn suggest_dunes(&self, prefix: &str, limit: usize) -> Result<Vec<SpacedDune>> {
+    if !self.index_dunes {
+      return Ok(Vec::new());
+    }
+
+    let prefix = prefix
+      .chars()
+      .filter(|c| *c != '•' && *c != '.')
+      .collect::<String>()
+      .to_uppercase();
+
+    let rtx = self.database.begin_read()?;
+    let dune_to_dune_id = rtx.open_table(DUNE_TO_DUNE_ID)?;
+    let dune_id_to_dune_entry = rtx.open_table(DUNE_ID_TO_DUNE_ENTRY)?;
+
+    let mut dunes = Vec::new();
+
+    for length in prefix.chars().count().max(1).. {
+      let padding = length - prefix.chars().count();
+
+      let (Ok(start), Ok(end)) = (
+        format!("{prefix}{}", "A".repeat(padding)).parse::<Dune>(),
+        format!("{prefix}{}", "Z".repeat(padding)).parse::<Dune>(),
+      ) else {
+        break;
+      };
+
+      for result in dune_to_dune_id.range(start.0..=end.0)? {
+        if dunes.len() == limit {
+          return Ok(dunes);
+        }
+
+        let (_, id) = result?;
+
+        if let Some(entry) = dune_id_to_dune_entry.get(id.value())? {
+          dunes.push(DuneEntry::load(entry.value()).spaced_dune());
+        }
+      }
+    }
+
+    Ok(dunes)
+  }
+
   pub(crate) fn dunes(&self) -> Result<Vec<(DuneId, DuneEntry)>> {
     let mut entries = Vec::new();
 
@@ -823,22 +1100,115 @@ impl Index {
   }
 
   pub(crate) fn get_account_outputs(&self, address: String) -> Result<Vec<OutPoint>> {
-    let mut result: Vec<OutPoint> = Vec::new();
+    Ok(
+      self
+        .get_account_outputs_page(&address, None, usize::MAX)?
+        .0
+        .into_iter()
+        .map(|(outpoint, _value)| outpoint)
+        .collect(),
+    )
+  }
 
-    self
-      .database
-      .begin_read()?
-      .open_multimap_table(ADDRESS_TO_OUTPOINT)?
-      .get(address.as_bytes())?
-      .for_each(|res| {
-        if let Ok(item) = res {
-          result.push(OutPoint::load(*item.value()));
-        } else {
-          println!("Error: {:?}", res.err().unwrap());
-        }
-      });
+  /// Returns the key the outputs of `address` are indexed under. Only
+  /// addresses that encode to 34 characters, as every P2PKH and P2SH dogecoin
+  /// address does, are indexed, so any other address is an error rather than
+  /// an address without outputs.
+  pub(crate) fn indexed_address(address: &str) -> Result<&[u8; 34]> {
+    address
+      .as_bytes()
+      .try_into()
+      .map_err(|_| anyhow!("address `{address}` is not indexed, only 34 character addresses are"))
+  }
 
-    Ok(result)
+  /// Returns up to `limit` outputs of `address` and their values, ordered by
+  /// outpoint and starting after `after`, and whether more outputs remain.
+  pub(crate) fn get_account_outputs_page(
+    &self,
+    address: &str,
+    after: Option<OutPoint>,
+    limit: usize,
+  ) -> Result<(Vec<(OutPoint, u64)>, bool)> {
+    let address = Self::indexed_address(address)?;
+
+    let start = address_outpoint_key(address, &after.map(OutPoint::store).unwrap_or([0; 36]));
+    let end = address_outpoint_key(address, &[u8::MAX; 36]);
+
+    let rtx = self.database.begin_read()?;
+    let address_outpoint_to_output = rtx.open_table(ADDRESS_OUTPOINT_TO_OUTPUT)?;
+
+    let mut outputs = Vec::new();
+
+    for result in address_outpoint_to_output.range::<&AddressOutPointValue>(&start..=&end)? {
+      let (key, value) = result?;
+      let key = key.value();
+
+      if after.is_some() && *key == start {
+        continue;
+      }
+
+      if outputs.len() == limit {
+        return Ok((outputs, true));
+      }
+
+      outputs.push((
+        OutPoint::load(key[34..].try_into().unwrap()),
+        value.value().0,
+      ));
+    }
+
+    Ok((outputs, false))
+  }
+
+  /// Returns the number and total value of the unspent outputs of `address`.
+  pub(crate) fn get_address_balance(&self, address: &str) -> Result<(u64, u64)> {
+    let address = Self::indexed_address(address)?;
+
+    Ok(
+      self
+        .database
+        .begin_read()?
+        .open_table(ADDRESS_TO_BALANCE)?
+        .get(address)?
+        .map(|balance| balance.value())
+        .unwrap_or_default(),
+    )
+  }
+
+  /// Returns up to `limit` unspent outputs of `address`, newest first, with
+  /// their values and the height they were created at. Outputs indexed before
+  /// heights were recorded have height 0 and come last.
+  pub(crate) fn get_address_recent_outputs(
+    &self,
+    address: &str,
+    limit: usize,
+  ) -> Result<Vec<(OutPoint, u64, u32)>> {
+    let address = Self::indexed_address(address)?;
+
+    let start = address_height_outpoint_key(address, 0, &[0; 36]);
+    let end = address_height_outpoint_key(address, u32::MAX, &[u8::MAX; 36]);
+
+    let rtx = self.database.begin_read()?;
+    let address_height_outpoint_to_value = rtx.open_table(ADDRESS_HEIGHT_OUTPOINT_TO_VALUE)?;
+
+    let mut outputs = Vec::new();
+
+    for result in address_height_outpoint_to_value
+      .range::<&AddressHeightOutPointValue>(&start..=&end)?
+      .rev()
+      .take(limit)
+    {
+      let (key, value) = result?;
+      let key = key.value();
+
+      outputs.push((
+        OutPoint::load(key[38..].try_into().unwrap()),
+        value.value(),
+        u32::from_be_bytes(key[34..38].try_into().unwrap()),
+      ));
+    }
+
+    Ok(outputs)
   }
 
   pub(crate) fn block_header(&self, hash: BlockHash) -> Result<Option<BlockHeader>> {
@@ -888,6 +1258,28 @@ impl Index {
     self.client.get_block(&hash).into_option()
   }
 
+  /// The AuxPoW header of the indexed `block` at `height`, as recorded while
+  /// indexing, or else decoded from the node's copy of the block, since blocks
+  /// the indexer only fetched headers for have none recorded.
+  pub(crate) fn get_auxpow(&self, height: u32, block: &Block) -> Result<Option<AuxPow>> {
+    if !AuxPow::is_merge_mined(&block.header) {
+      return Ok(None);
+    }
+
+    if let Some(auxpow) = self
+      .database
+      .begin_read()?
+      .open_table(HEIGHT_TO_AUXPOW)?
+      .get(&height)?
+    {
+      return Ok(Some(consensus::deserialize(auxpow.value())?));
+    }
+
+    let hex = self.client.get_block_hex(&block.block_hash())?;
+
+    Ok(AuxPow::decode_block(&hex::decode(hex)?)?.1)
+  }
+
   pub(crate) fn get_drc20_balances(&self, script_key: &ScriptKey) -> Result<Vec<Balance>> {
     if self.block_count().unwrap() >= self.first_inscription_height {
       let rtx = self.database.begin_read()?;
@@ -909,14 +1301,32 @@ impl Index {
     }
   }
 
+  /// Returns the balance of `script_key` in `tick` after block `at_height`,
+  /// or the current balance if `at_height` is `None`.
   pub(crate) fn get_drc20_balance(
     &self,
     script_key: &ScriptKey,
     tick: &Tick,
+    at_height: Option<u32>,
   ) -> Result<Option<Balance>> {
     if self.block_count().unwrap() >= self.first_inscription_height {
       let rtx = self.database.begin_read()?;
 
+      if let Some(height) = at_height {
+        self.check_drc20_balance_history(height)?;
+
+        let drc20_balance_history = rtx.open_table(DRC20_BALANCE_HISTORY)?;
+
+        return drc20_balance_history
+          .range(
+            min_balance_history_key(tick, script_key).as_str()
+              ..=balance_history_key(tick, script_key, height).as_str(),
+          )?
+          .next_back()
+          .map(|result| Ok(bincode::deserialize::<Balance>(result?.1.value())?))
+          .transpose();
+      }
+
       let drc20_token_balance = rtx.open_table(DRC20_BALANCES)?;
 
       return Ok(
@@ -962,25 +1372,322 @@ impl Index {
     }
   }
 
+  /// Returns up to `limit` deployed ticks starting with `prefix`, compared
+  /// case-insensitively. Token keys are the hex of the lowercased tick, so
+  /// the matches form a contiguous range.
+  pub(crate) fn suggest_drc20_ticks(&self, prefix: &str, limit: usize) -> Result<Vec<Tick>> {
+    if self.block_count()? < self.first_inscription_height {
+      return Ok(Vec::new());
+    }
+
+    let prefix = hex::encode(prefix.to_lowercase());
+
+    let rtx = self.database.begin_read()?;
+    let drc20_token_info = rtx.open_table(DRC20_TOKEN)?;
+
+    let mut ticks = Vec::new();
+
+    for result in drc20_token_info.range(prefix.as_str()..)? {
+      let (key, data) = result?;
+
+      if !key.value().starts_with(&prefix) || ticks.len() == limit {
+        break;
+      }
+
+      ticks.push(bincode::deserialize::<TokenInfo>(data.value())?.tick);
+    }
+
+    Ok(ticks)
+  }
+
   pub(crate) fn get_drc20_token_holder(&self, tick: &Tick) -> Result<Vec<ScriptKey>> {
-    if self.block_count().unwrap() >= self.first_inscription_height {
-      let rtx = self.database.begin_read()?;
+    Ok(self.get_drc20_token_holder_page(tick, None, usize::MAX)?.0)
+  }
 
-      let drc20_token_holder = rtx.open_multimap_table(DRC20_TOKEN_HOLDER)?;
+  /// Returns up to `limit` holders of `tick` ordered by script key, starting
+  /// after `after`, along with the last scanned key if more holders remain.
+  pub(crate) fn get_drc20_token_holder_page(
+    &self,
+    tick: &Tick,
+    after: Option<&str>,
+    limit: usize,
+  ) -> Result<(Vec<ScriptKey>, Option<String>)> {
+    if self.block_count()? < self.first_inscription_height {
+      return Ok((Vec::new(), None));
+    }
 
-      return Ok(
-        drc20_token_holder
-          .get(tick.to_lowercase().hex().as_str())?
-          .flat_map(|result| {
-            result.into_iter().filter_map(|(scriptKey)| {
-              ScriptKey::from_str(scriptKey.value(), self.chain.network())
-            })
-          })
-          .collect(),
-      );
-    } else {
-      return Ok(vec![]);
+    let prefix = min_tick_holder_key(tick);
+    let start = format!("{prefix}{}", after.unwrap_or_default());
+
+    let rtx = self.database.begin_read()?;
+    let drc20_tick_holder = rtx.open_table(DRC20_TICK_HOLDER)?;
+
+    let mut holders = Vec::new();
+    let mut scanned = 0;
+    let mut last = None;
+
+    for result in drc20_tick_holder.range(start.as_str()..max_tick_holder_key(tick).as_str())? {
+      let (key, _) = result?;
+      let key = key.value();
+
+      if after.is_some() && key == start {
+        continue;
+      }
+
+      if scanned == limit {
+        return Ok((holders, last));
+      }
+
+      let script_key = &key[prefix.len()..];
+
+      scanned += 1;
+      last = Some(script_key.to_string());
+
+      if let Some(script_key) = ScriptKey::from_str(script_key, self.chain.network()) {
+        holders.push(script_key);
+      }
     }
+
+    Ok((holders, None))
+  }
+
+  /// Returns the `limit` largest holders of `tick` by overall balance, read
+  /// from the end of the tick's `DRC20_TICK_BALANCE_HOLDER` keys.
+  pub(crate) fn get_drc20_top_holders(
+    &self,
+    tick: &Tick,
+    limit: usize,
+  ) -> Result<Vec<(ScriptKey, Balance)>> {
+    if self.block_count()? < self.first_inscription_height {
+      return Ok(Vec::new());
+    }
+
+    let rtx = self.database.begin_read()?;
+    let drc20_tick_balance_holder = rtx.open_table(DRC20_TICK_BALANCE_HOLDER)?;
+    let drc20_token_balance = rtx.open_table(DRC20_BALANCES)?;
+
+    let mut holders = Vec::new();
+
+    for result in drc20_tick_balance_holder
+      .range(min_tick_holder_key(tick).as_str()..max_tick_holder_key(tick).as_str())?
+      .rev()
+    {
+      if holders.len() == limit {
+        break;
+      }
+
+      let (key, _) = result?;
+
+      let Some(script_key) = deserialize_tick_balance_holder_key(key.value())
+        .and_then(|script| ScriptKey::from_str(script, self.chain.network()))
+      else {
+        continue;
+      };
+
+      if let Some(balance) = drc20_token_balance.get(script_tick_key(&script_key, tick).as_str())? {
+        holders.push((script_key, bincode::deserialize::<Balance>(balance.value())?));
+      }
+    }
+
+    Ok(holders)
+  }
+
+  /// Returns every holder of `tick` with a nonzero balance after block
+  /// `height`, in the order of their balance history keys.
+  pub(crate) fn get_drc20_holders_at(
+    &self,
+    tick: &Tick,
+    height: u32,
+  ) -> Result<Vec<(ScriptKey, Balance)>> {
+    Ok(self.get_drc20_holder_page_at(tick, height, None, usize::MAX)?.0)
+  }
+
+  /// Returns up to `limit` holders of `tick` with a nonzero balance after
+  /// block `height`, starting after the script key `after`, along with the
+  /// last scanned script key if more history remains. The scan starts at the
+  /// first key after `after` and stops once the page is full.
+  pub(crate) fn get_drc20_holder_page_at(
+    &self,
+    tick: &Tick,
+    height: u32,
+    after: Option<&str>,
+    limit: usize,
+  ) -> Result<(Vec<(ScriptKey, Balance)>, Option<String>)> {
+    if self.block_count()? < self.first_inscription_height {
+      return Ok((Vec::new(), None));
+    }
+
+    self.check_drc20_balance_history(height)?;
+
+    let rtx = self.database.begin_read()?;
+    let drc20_balance_history = rtx.open_table(DRC20_BALANCE_HISTORY)?;
+
+    // Versions are ordered by script key and then height, so `~`, which sorts
+    // after every height, skips all versions of `after`.
+    let start = match after {
+      Some(after) => format!("{}{after}_~", min_tick_balance_history_key(tick)),
+      None => min_tick_balance_history_key(tick),
+    };
+
+    let mut holders = Vec::new();
+
+    let push = |holders: &mut Vec<(ScriptKey, Balance)>, script_key: &str, balance: Option<Balance>| {
+      if let Some(balance) = balance.filter(|balance| balance.overall_balance > 0) {
+        if let Some(script_key) = ScriptKey::from_str(script_key, self.chain.network()) {
+          holders.push((script_key, balance));
+        }
+      }
+    };
+
+    // The script key being scanned and its last version at or below `height`.
+    let mut current: Option<(String, Option<Balance>)> = None;
+
+    for result in drc20_balance_history
+      .range(start.as_str()..max_tick_balance_history_key(tick).as_str())?
+    {
+      let (key, value) = result?;
+
+      let Some((script_key, version)) = deserialize_balance_history_key(key.value()) else {
+        bail!("invalid DRC-20 balance history key `{}`", key.value());
+      };
+
+      if current.as_ref().map(|(current, _)| current.as_str()) != Some(script_key) {
+        if let Some((previous, balance)) = current.take() {
+          push(&mut holders, &previous, balance);
+
+          if holders.len() == limit {
+            return Ok((holders, Some(previous)));
+          }
+        }
+
+        current = Some((script_key.into(), None));
+      }
+
+      if version <= height {
+        if let Some((_, balance)) = &mut current {
+          *balance = Some(bincode::deserialize(value.value())?);
+        }
+      }
+    }
+
+    if let Some((previous, balance)) = current {
+      push(&mut holders, &previous, balance);
+    }
+
+    Ok((holders, None))
+  }
+
+  fn check_drc20_balance_history(&self, height: u32) -> Result {
+    let block_count = self.block_count()?;
+
+    ensure!(
+      height < block_count,
+      "block {height} has not been indexed, the index has {block_count} blocks"
+    );
+
+    let from = self
+      .database
+      .begin_read()?
+      .open_table(STATISTIC_TO_COUNT)?
+      .get(&Statistic::Drc20BalanceHistoryFrom.key())?
+      .map(|from| from.value())
+      .unwrap_or_default();
+
+    ensure!(
+      u64::from(height) >= from,
+      "DRC-20 balance history starts at block {from}"
+    );
+
+    Ok(())
+  }
+
+  /// Returns the heights of the first and last DRC-20 state hashes, or `None`
+  /// if no block has been indexed with one.
+  pub(crate) fn get_drc20_state_hash_heights(&self) -> Result<Option<(u32, u32)>> {
+    let rtx = self.database.begin_read()?;
+
+    let drc20_height_to_state_hash = match rtx.open_table(DRC20_HEIGHT_TO_STATE_HASH) {
+      Ok(table) => table,
+      Err(TableError::TableDoesNotExist(_)) => return Ok(None),
+      Err(err) => return Err(err.into()),
+    };
+
+    let mut heights = drc20_height_to_state_hash.range(0..)?;
+
+    let Some(first) = heights.next() else {
+      return Ok(None);
+    };
+
+    let first = first?.0.value();
+
+    let last = match heights.next_back() {
+      Some(last) => last?.0.value(),
+      None => first,
+    };
+
+    Ok(Some((first, last)))
+  }
+
+  pub(crate) fn get_drc20_state_hash(&self, height: u32) -> Result<Option<StateHash>> {
+    let rtx = self.database.begin_read()?;
+
+    let drc20_height_to_state_hash = match rtx.open_table(DRC20_HEIGHT_TO_STATE_HASH) {
+      Ok(table) => table,
+      Err(TableError::TableDoesNotExist(_)) => return Ok(None),
+      Err(err) => return Err(err.into()),
+    };
+
+    let state_hash = drc20_height_to_state_hash
+      .get(&height)?
+      .map(|hash| StateHash {
+        height,
+        hash: bitcoin::hashes::sha256::Hash::from_inner(*hash.value()),
+      });
+
+    Ok(state_hash)
+  }
+
+  /// Returns the latest `limit` mints and transfers of `tick`, newest first.
+  pub(crate) fn get_drc20_tick_activity(
+    &self,
+    tick: &Tick,
+    limit: usize,
+  ) -> Result<Vec<TickActivity>> {
+    if self.block_count()? < self.first_inscription_height {
+      return Ok(Vec::new());
+    }
+
+    let rtx = self.database.begin_read()?;
+    let drc20_tick_activity = rtx.open_table(DRC20_TICK_ACTIVITY)?;
+
+    let mut activity = Vec::new();
+
+    for result in drc20_tick_activity
+      .range(min_tick_activity_key(tick).as_str()..max_tick_activity_key(tick).as_str())?
+      .rev()
+      .take(limit)
+    {
+      let (_, data) = result?;
+      activity.push(bincode::deserialize::<TickActivity>(data.value())?);
+    }
+
+    Ok(activity)
+  }
+
+  pub(crate) fn get_drc20_token_holder_count(&self, tick: &Tick) -> Result<usize> {
+    if self.block_count()? < self.first_inscription_height {
+      return Ok(0);
+    }
+
+    Ok(
+      self
+        .database
+        .begin_read()?
+        .open_table(DRC20_TICK_TO_HOLDER_COUNT)?
+        .get(tick.to_lowercase().hex().as_str())?
+        .map(|count| count.value().try_into().unwrap())
+        .unwrap_or_default(),
+    )
   }
 
   pub(crate) fn get_drc20_transferable_by_range(
@@ -1142,54 +1849,31 @@ impl Index {
     &self,
     inscription_id: InscriptionId,
   ) -> Result<Option<Inscription>> {
-    if self
-      .database
-      .begin_read()?
+    let rtx = self.database.begin_read()?;
+
+    if rtx
       .open_table(INSCRIPTION_ID_TO_SATPOINT)?
       .get(&inscription_id.store())?
       .is_none()
     {
       return Ok(None);
-    }
-
-    let reader = self.database.begin_read()?;
-
-    let table = reader.open_table(INSCRIPTION_ID_TO_TXIDS)?;
-    let txids_result = table.get(&inscription_id.store())?;
-
-    match txids_result {
-      Some(txids) => {
-        let mut txs = vec![];
-
-        let txids = txids.value();
-
-        for i in 0..txids.len() / 32 {
-          let txid_buf = &txids[i * 32..i * 32 + 32];
-          let table = reader.open_table(INSCRIPTION_TXID_TO_TX)?;
-          let tx_result = table.get(txid_buf)?;
-
-          match tx_result {
-            Some(tx_result) => {
-              let tx_buf = tx_result.value().to_vec();
-              let mut cursor = Cursor::new(tx_buf);
-              let tx = bitcoin::Transaction::consensus_decode(&mut cursor)?;
-              txs.push(tx);
-            }
-            None => return Ok(None),
-          }
-        }
+    }
 
-        let parsed_inscription = Inscription::from_transactions(txs);
+    let Some(content_hash) = rtx
+      .open_table(INSCRIPTION_ID_TO_CONTENT_HASH)?
+      .get(&inscription_id.store())?
+      .map(|content_hash| *content_hash.value())
+    else {
+      return Ok(None);
+    };
 
-        match parsed_inscription {
-          ParsedInscription::None => return Ok(None),
-          ParsedInscription::Partial => return Ok(None),
-          ParsedInscription::Complete(inscription) => Ok(Some(inscription)),
-        }
-      }
+    let inscription = rtx
+      .open_table(CONTENT_HASH_TO_INSCRIPTION_CONTENT)?
+      .get(&content_hash)?
+      .map(|content| Inscription::from_bytes(content.value()))
+      .transpose();
 
-      None => return Ok(None),
-    }
+    inscription
   }
 
   pub(crate) fn inscription_exists(&self, inscription_id: InscriptionId) -> Result<bool> {
@@ -1297,7 +1981,7 @@ impl Index {
   pub(crate) fn find(&self, sat: Sat) -> Result<Option<SatPoint>> {
     let rtx = self.begin_read()?;
 
-    if rtx.block_count()? <= Sat(sat.0).height().n() {
+    if rtx.block_count()? <= self.schedule().height(sat).n() {
       return Ok(None);
     }
 
@@ -1312,8 +1996,7 @@ impl Index {
       };
 
       let mut offset = 0;
-      for chunk in value.value().chunks_exact(24) {
-        let (start, end) = SatRange::load(chunk.try_into().unwrap());
+      for (start, end) in decode_sat_ranges(value.value())? {
         if start <= sat.0 && sat.0 < end {
           return Ok(Some(SatPoint {
             outpoint: Entry::load(*key.value()),
@@ -1348,12 +2031,7 @@ impl Index {
     let sat_ranges = self.list_inner(array)?;
 
     match sat_ranges {
-      Some(sat_ranges) => Ok(Some(List::Unspent(
-        sat_ranges
-          .chunks_exact(24)
-          .map(|chunk| SatRange::load(chunk.try_into().unwrap()))
-          .collect(),
-      ))),
+      Some(sat_ranges) => Ok(Some(List::Unspent(decode_sat_ranges(&sat_ranges)?))),
       None => {
         if self.is_transaction_in_active_chain(outpoint.txid)? {
           Ok(Some(List::Spent))
@@ -1703,6 +2381,398 @@ mod tests {
     }
   }
 
+  #[test]
+  fn index_drc20_is_set_by_its_own_flag() {
+    assert!(
+      Context::builder()
+        .arg("--index-drc20")
+        .build()
+        .index
+        .index_drc20
+    );
+    assert!(
+      !Context::builder()
+        .arg("--index-dunes")
+        .build()
+        .index
+        .index_drc20
+    );
+  }
+
+  #[test]
+  fn replaying_blocks_from_memory_matches_rpc() {
+    let context = Context::builder().build();
+    context.mine_blocks(1);
+    let txid = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      script_sig: inscription("text/plain;charset=utf-8", "hello").to_script_sig(),
+      ..Default::default()
+    });
+    let inscription_id = InscriptionId::from(txid);
+    context.mine_blocks(1);
+
+    let blocks = (0..=2)
+      .map(|height| context.index.block_source.block(height).unwrap().unwrap())
+      .collect::<Vec<Block>>();
+
+    let replay = Index::open_with_block_source(
+      &Options {
+        index: Some(context.tempdir.path().join("replay.redb")),
+        ..context.options.clone()
+      },
+      Arc::new(block_source::MemoryBlockSource::new(0, blocks, [])),
+    )
+    .unwrap();
+
+    replay.update().unwrap();
+
+    assert_eq!(replay.block_count().unwrap(), 3);
+    assert_eq!(
+      replay.block_hash(Some(2)).unwrap(),
+      context.index.block_hash(Some(2)).unwrap()
+    );
+    assert_eq!(
+      replay.get_inscription_satpoint_by_id(inscription_id).unwrap(),
+      context
+        .index
+        .get_inscription_satpoint_by_id(inscription_id)
+        .unwrap()
+    );
+  }
+
+  /// Serves blocks from memory, attaching an AuxPoW header to those whose
+  /// version says they were merge mined.
+  #[test]
+  fn auxpow_headers_are_recorded() {
+    let context = Context::builder().build();
+    context.mine_blocks(2);
+
+    let mut blocks = (0..=2)
+      .map(|height| context.index.block_source.block(height).unwrap().unwrap())
+      .collect::<Vec<Block>>();
+
+    blocks[2].header.version |= 1 << 8;
+
+    let auxpow = crate::auxpow::tests::auxpow();
+
+    let index = Index::open_with_block_source(
+      &Options {
+        index: Some(context.tempdir.path().join("merge-mined.redb")),
+        first_inscription_height: Some(0),
+        ..context.options.clone()
+      },
+      Arc::new(block_source::tests::MergeMined(
+        block_source::MemoryBlockSource::new(0, blocks.clone(), []),
+        auxpow.clone(),
+      )),
+    )
+    .unwrap();
+
+    index.update().unwrap();
+
+    let rtx = index.database.begin_read().unwrap();
+    let height_to_auxpow = rtx.open_table(HEIGHT_TO_AUXPOW).unwrap();
+    assert_eq!(height_to_auxpow.len().unwrap(), 1);
+    assert_eq!(
+      consensus::deserialize::<AuxPow>(height_to_auxpow.get(&2).unwrap().unwrap().value())
+        .unwrap(),
+      auxpow
+    );
+
+    assert_eq!(index.get_auxpow(1, &blocks[1]).unwrap(), None);
+    assert_eq!(index.get_auxpow(2, &blocks[2]).unwrap(), Some(auxpow));
+  }
+
+  /// Serves blocks like the node would with `txindex=0`.
+  struct WithoutTxindex(block_source::MemoryBlockSource);
+
+  impl BlockSource for WithoutTxindex {
+    fn tip_height(&self) -> Result<u32> {
+      self.0.tip_height()
+    }
+
+    fn block_hash(&self, height: u32) -> Result<Option<BlockHash>> {
+      self.0.block_hash(height)
+    }
+
+    fn block(&self, height: u32) -> Result<Option<Block>> {
+      self.0.block(height)
+    }
+
+    fn block_header(&self, height: u32) -> Result<Option<BlockHeader>> {
+      self.0.block_header(height)
+    }
+
+    fn transactions(&self, _txids: &[Txid]) -> Result<Vec<Transaction>> {
+      bail!("txindex is disabled")
+    }
+  }
+
+  fn inscribed_chain_without_txindex() -> (Context, InscriptionId, Arc<WithoutTxindex>) {
+    let context = Context::builder().build();
+    context.mine_blocks(2);
+    let txid = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      script_sig: inscription("text/plain;charset=utf-8", "hello").to_script_sig(),
+      ..Default::default()
+    });
+    context.mine_blocks(1);
+
+    let blocks = (0..=3)
+      .map(|height| context.index.block_source.block(height).unwrap().unwrap())
+      .collect::<Vec<Block>>();
+
+    let block_source = Arc::new(WithoutTxindex(block_source::MemoryBlockSource::new(
+      0,
+      blocks,
+      [],
+    )));
+
+    (context, txid.into(), block_source)
+  }
+
+  #[test]
+  fn index_utxos_does_not_need_txindex() {
+    let (context, inscription_id, block_source) = inscribed_chain_without_txindex();
+
+    let options = Options {
+      index: Some(context.tempdir.path().join("without-utxos.redb")),
+      first_inscription_height: Some(3),
+      ..context.options.clone()
+    };
+
+    assert!(Index::open_with_block_source(&options, block_source.clone())
+      .unwrap()
+      .update()
+      .is_err());
+
+    let index = Index::open_with_block_source(
+      &Options {
+        index: Some(context.tempdir.path().join("with-utxos.redb")),
+        index_utxos: true,
+        ..options
+      },
+      block_source,
+    )
+    .unwrap();
+
+    index.update().unwrap();
+
+    assert_eq!(
+      index.get_inscription_satpoint_by_id(inscription_id).unwrap(),
+      context
+        .index
+        .get_inscription_satpoint_by_id(inscription_id)
+        .unwrap()
+    );
+  }
+
+  #[test]
+  fn utxo_snapshot_replaces_txindex() {
+    let (context, inscription_id, block_source) = inscribed_chain_without_txindex();
+
+    let mut snapshot = String::new();
+    for height in 0..=1 {
+      for tx in block_source.block(height).unwrap().unwrap().txdata {
+        for (vout, output) in tx.output.iter().enumerate() {
+          snapshot.push_str(&format!("{}:{vout} {}\n", tx.txid(), output.value));
+        }
+      }
+    }
+
+    let options = Options {
+      index: Some(context.tempdir.path().join("snapshot.redb")),
+      first_inscription_height: Some(3),
+      ..context.options.clone()
+    };
+
+    {
+      let index = Index::open_with_block_source(&options, block_source.clone()).unwrap();
+
+      assert_eq!(
+        index.load_utxo_snapshot(3, snapshot.as_bytes()).unwrap_err().to_string(),
+        "UTXO snapshot at height 3 must be below the first inscription height 3"
+      );
+
+      assert_eq!(
+        index.load_utxo_snapshot(1, snapshot.as_bytes()).unwrap(),
+        u64::try_from(snapshot.lines().count()).unwrap()
+      );
+    }
+
+    let index = Index::open_with_block_source(&options, block_source).unwrap();
+
+    index.update().unwrap();
+
+    assert_eq!(
+      index.get_inscription_satpoint_by_id(inscription_id).unwrap(),
+      context
+        .index
+        .get_inscription_satpoint_by_id(inscription_id)
+        .unwrap()
+    );
+
+    assert_eq!(
+      index.load_utxo_snapshot(1, snapshot.as_bytes()).unwrap_err().to_string(),
+      "UTXO snapshots can only be loaded into an empty index"
+    );
+  }
+
+  #[test]
+  fn spent_outputs_leave_address_balances_without_transaction_index() {
+    let context = Context::builder().build();
+    context.mine_blocks(1);
+    context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      ..Default::default()
+    });
+    context.mine_blocks(1);
+
+    assert!(!context.index.index_transactions);
+
+    let script = Script::new_p2pkh(&bitcoin::PubkeyHash::from_inner([1; 20]));
+    let address = context
+      .index
+      .chain
+      .address_from_script(&script)
+      .unwrap()
+      .to_string();
+
+    let mut blocks = (0..=2)
+      .map(|height| context.index.block_source.block(height).unwrap().unwrap())
+      .collect::<Vec<Block>>();
+    blocks[1].txdata[0].output[0].script_pubkey = script;
+    blocks[2].txdata[1].input[0].previous_output.txid = blocks[1].txdata[0].txid();
+
+    let options = Options {
+      index: Some(context.tempdir.path().join("addresses.redb")),
+      ..context.options.clone()
+    };
+
+    let open = |blocks: &[Block]| {
+      let block_source = block_source::MemoryBlockSource::new(0, blocks.to_vec(), []);
+      let index = Index::open_with_block_source(&options, Arc::new(block_source)).unwrap();
+      index.update().unwrap();
+      index
+    };
+
+    let value = blocks[1].txdata[0].output[0].value;
+
+    assert_eq!(
+      open(&blocks[..2]).get_address_balance(&address).unwrap(),
+      (1, value)
+    );
+
+    let index = open(&blocks);
+
+    assert_eq!(index.get_address_balance(&address).unwrap(), (0, 0));
+    assert!(index
+      .get_address_recent_outputs(&address, 10)
+      .unwrap()
+      .is_empty());
+  }
+
+  #[test]
+  fn addresses_that_are_not_indexed_are_errors() {
+    let context = Context::builder().build();
+    context.mine_blocks(1);
+
+    let error = "address `DShort` is not indexed, only 34 character addresses are";
+
+    assert_eq!(
+      context
+        .index
+        .get_address_balance("DShort")
+        .unwrap_err()
+        .to_string(),
+      error
+    );
+    assert_eq!(
+      context
+        .index
+        .get_address_recent_outputs("DShort", 10)
+        .unwrap_err()
+        .to_string(),
+      error
+    );
+    assert_eq!(
+      context
+        .index
+        .get_account_outputs_page("DShort", None, 10)
+        .unwrap_err()
+        .to_string(),
+      error
+    );
+  }
+
+  #[test]
+  fn historical_drc20_holders_are_paged() {
+    let context = Context::builder()
+      .args(["--index-drc20", "--index-transactions"])
+      .build();
+    context.mine_blocks(2);
+
+    let tick = Tick::from_str("doge").unwrap();
+    let network = context.index.chain.network();
+
+    let mut holders = (1..=3)
+      .map(|i| {
+        ScriptKey::from_script(
+          &Script::new_p2pkh(&bitcoin::PubkeyHash::from_inner([i; 20])),
+          network,
+        )
+      })
+      .collect::<Vec<ScriptKey>>();
+    holders.sort_by_key(ToString::to_string);
+
+    let balance = |overall_balance| Balance {
+      tick: tick.clone(),
+      overall_balance,
+      transferable_balance: 0,
+    };
+
+    {
+      let wtx = context.index.begin_write().unwrap();
+      let mut drc20_balance_history = wtx.open_table(DRC20_BALANCE_HISTORY).unwrap();
+
+      for (holder, height, overall_balance) in [(0, 1, 10), (1, 1, 5), (1, 2, 0), (2, 2, 7)] {
+        drc20_balance_history
+          .insert(
+            balance_history_key(&tick, &holders[holder], height).as_str(),
+            bincode::serialize(&balance(overall_balance))
+              .unwrap()
+              .as_slice(),
+          )
+          .unwrap();
+      }
+
+      drop(drc20_balance_history);
+      wtx.commit().unwrap();
+    }
+
+    let page = |height, after: Option<&ScriptKey>| {
+      context
+        .index
+        .get_drc20_holder_page_at(&tick, height, after.map(ToString::to_string).as_deref(), 1)
+        .unwrap()
+    };
+
+    let (first, after) = page(1, None);
+    assert_eq!(first, [(holders[0].clone(), balance(10))]);
+    assert_eq!(after, Some(holders[0].to_string()));
+
+    let (second, after) = page(1, Some(&holders[0]));
+    assert_eq!(second, [(holders[1].clone(), balance(5))]);
+    assert_eq!(after, Some(holders[1].to_string()));
+
+    assert_eq!(page(1, Some(&holders[1])), (Vec::new(), None));
+
+    assert_eq!(
+      page(2, Some(&holders[0])),
+      (vec![(holders[2].clone(), balance(7))], None)
+    );
+  }
+
   #[test]
   fn inscriptions_below_first_inscription_height_are_skipped() {
     let inscription = inscription("text/plain;charset=utf-8", "hello");
@@ -2855,6 +3925,57 @@ mod tests {
     }
   }
 
+  #[test]
+  fn identical_inscription_content_is_stored_once() {
+    let context = Context::builder().build();
+    context.mine_blocks(2);
+
+    let first = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      script_sig: inscription("text/plain", "hello").to_script_sig(),
+      ..Default::default()
+    });
+    let second = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 0, 0)],
+      script_sig: inscription("text/plain", "hello").to_script_sig(),
+      ..Default::default()
+    });
+    context.mine_blocks(1);
+
+    let third = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(3, 0, 0)],
+      script_sig: inscription("text/plain", "goodbye").to_script_sig(),
+      ..Default::default()
+    });
+    context.mine_blocks(1);
+
+    for (txid, body) in [(first, "hello"), (second, "hello"), (third, "goodbye")] {
+      assert_eq!(
+        context.index.get_inscription_by_id(txid.into()).unwrap(),
+        Some(inscription("text/plain", body))
+      );
+    }
+
+    let rtx = context.index.database.begin_read().unwrap();
+
+    assert_eq!(
+      rtx
+        .open_table(INSCRIPTION_ID_TO_CONTENT_HASH)
+        .unwrap()
+        .len()
+        .unwrap(),
+      3
+    );
+    assert_eq!(
+      rtx
+        .open_table(CONTENT_HASH_TO_INSCRIPTION_CONTENT)
+        .unwrap()
+        .len()
+        .unwrap(),
+      2
+    );
+  }
+
   #[test]
   fn get_latest_inscriptions_with_no_prev_and_next() {
     for context in Context::configurations() {
@@ -3096,4 +4217,83 @@ mod tests {
           .assert_inscription_location(first_id, first_location, Some(50 * COIN_VALUE));
     }
   }
+
+  #[test]
+  fn claimed_subsidies_are_recorded_and_number_sats() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      context.rpc_server.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0)],
+        fee: 10,
+        ..Default::default()
+      });
+
+      let coinbase = &context.mine_blocks_with_subsidy(1, 30 * COIN_VALUE)[0].txdata[0];
+      assert_eq!(coinbase.output[0].value, 30 * COIN_VALUE + 10);
+
+      let rtx = context.index.database.begin_read().unwrap();
+      let height_to_subsidy = rtx.open_table(HEIGHT_TO_SUBSIDY).unwrap();
+
+      assert_eq!(
+        height_to_subsidy.get(&1).unwrap().unwrap().value(),
+        50 * COIN_VALUE
+      );
+      assert_eq!(
+        height_to_subsidy.get(&2).unwrap().unwrap().value(),
+        30 * COIN_VALUE
+      );
+
+      let schedule = context.index.schedule();
+      let start = schedule.starting_sat(Height(2));
+      assert_eq!(schedule.subsidy(Height(2)), 30 * COIN_VALUE);
+      assert_eq!(schedule.starting_sat(Height(3)), start + 30 * COIN_VALUE);
+      assert_eq!(schedule.height(Sat(start.n() + 30 * COIN_VALUE - 1)), 2);
+
+      if context.index.has_sat_index() {
+        assert_eq!(
+          context
+            .index
+            .list(OutPoint::new(coinbase.txid(), 0))
+            .unwrap()
+            .unwrap(),
+          List::Unspent(vec![
+            (start.n(), start.n() + 30 * COIN_VALUE),
+            (start.n() - 10, start.n()),
+          ])
+        );
+      }
+    }
+  }
+
+  fn commit_starting_block_counts(index: &Index) -> Vec<u32> {
+    index
+      .info()
+      .unwrap()
+      .transactions
+      .iter()
+      .map(|transaction| transaction.starting_block_count)
+      .collect()
+  }
+
+  #[test]
+  fn commit_interval_limits_blocks_per_commit() {
+    let context = Context::builder().arg("--commit-interval=2").build();
+
+    context.mine_blocks(5);
+
+    assert_eq!(commit_starting_block_counts(&context.index), [0, 1, 3, 5]);
+  }
+
+  #[test]
+  fn full_caches_are_committed_early() {
+    let context = Context::builder()
+      .args(["--index-sats", "--commit-cache-size=1"])
+      .build();
+
+    context.mine_blocks(2);
+
+    assert_eq!(commit_starting_block_counts(&context.index), [0, 1, 2]);
+    assert!(context.index.info().unwrap().cached_outputs > 0);
+  }
 }
@@ -0,0 +1,118 @@
+use {
+  super::{updater::overlay::Overlay, *},
+  redb::ReadTransaction,
+};
+
+/// The tables indexing unspent outputs by address. `ADDRESS_OUTPOINT_TO_OUTPUT`
+/// pages through an address's outputs by outpoint,
+/// `ADDRESS_HEIGHT_OUTPOINT_TO_VALUE` lists them newest first and
+/// `ADDRESS_TO_BALANCE` holds their count and total value, so reading an
+/// address's balance or recent outputs never scans all of its outputs.
+/// `OUTPOINT_TO_ADDRESS` finds the address of a spent output without
+/// looking up the transaction that created it.
+pub(super) struct AddressTables<'tx> {
+  outpoint_to_output: Box<dyn WritableTable<&'static AddressOutPointValue, (u64, u32)> + 'tx>,
+  height_outpoint_to_value: Box<dyn WritableTable<&'static AddressHeightOutPointValue, u64> + 'tx>,
+  to_balance: Box<dyn WritableTable<&'static [u8; 34], (u64, u64)> + 'tx>,
+  outpoint_to_address: Box<dyn WritableTable<&'static OutPointValue, &'static [u8; 34]> + 'tx>,
+}
+
+impl<'tx> AddressTables<'tx> {
+  pub(super) fn open<'db: 'tx>(wtx: &'tx WriteTransaction<'db>) -> Result<Self> {
+    Ok(Self {
+      outpoint_to_output: Box::new(wtx.open_table(ADDRESS_OUTPOINT_TO_OUTPUT)?),
+      height_outpoint_to_value: Box::new(wtx.open_table(ADDRESS_HEIGHT_OUTPOINT_TO_VALUE)?),
+      to_balance: Box::new(wtx.open_table(ADDRESS_TO_BALANCE)?),
+      outpoint_to_address: Box::new(wtx.open_table(OUTPOINT_TO_ADDRESS)?),
+    })
+  }
+
+  /// Opens the tables of `rtx` with their writes kept in memory.
+  pub(super) fn overlay<'db: 'tx>(rtx: &'tx ReadTransaction<'db>) -> Result<Self> {
+    Ok(Self {
+      outpoint_to_output: Box::new(Overlay::new(rtx.open_table(ADDRESS_OUTPOINT_TO_OUTPUT)?)),
+      height_outpoint_to_value: Box::new(Overlay::new(
+        rtx.open_table(ADDRESS_HEIGHT_OUTPOINT_TO_VALUE)?,
+      )),
+      to_balance: Box::new(Overlay::new(rtx.open_table(ADDRESS_TO_BALANCE)?)),
+      outpoint_to_address: Box::new(Overlay::new(rtx.open_table(OUTPOINT_TO_ADDRESS)?)),
+    })
+  }
+
+  /// Records `outpoint`, worth `value` and created at `height`, as an
+  /// unspent output of `address`.
+  pub(super) fn insert(
+    &mut self,
+    address: &[u8; 34],
+    outpoint: OutPoint,
+    value: u64,
+    height: u32,
+  ) -> Result {
+    let outpoint = outpoint.store();
+
+    self
+      .outpoint_to_output
+      .insert(&address_outpoint_key(address, &outpoint), (value, height))?;
+
+    self.height_outpoint_to_value.insert(
+      &address_height_outpoint_key(address, height, &outpoint),
+      value,
+    )?;
+
+    let (outputs, shibes) = self
+      .to_balance
+      .get(address)?
+      .map(|balance| balance.value())
+      .unwrap_or_default();
+
+    self
+      .to_balance
+      .insert(address, (outputs + 1, shibes + value))?;
+
+    self.outpoint_to_address.insert(&outpoint, address)?;
+
+    Ok(())
+  }
+
+  /// Removes `outpoint` from the outputs of the address it was paid to, doing
+  /// nothing if it wasn't paid to an address.
+  pub(super) fn remove(&mut self, outpoint: OutPoint) -> Result {
+    let outpoint = outpoint.store();
+
+    let Some(address) = self
+      .outpoint_to_address
+      .remove(&outpoint)?
+      .map(|address| *address.value())
+    else {
+      return Ok(());
+    };
+
+    let Some((value, height)) = self
+      .outpoint_to_output
+      .remove(&address_outpoint_key(&address, &outpoint))?
+      .map(|output| output.value())
+    else {
+      return Ok(());
+    };
+
+    self
+      .height_outpoint_to_value
+      .remove(&address_height_outpoint_key(&address, height, &outpoint))?;
+
+    let (outputs, shibes) = self
+      .to_balance
+      .get(&address)?
+      .map(|balance| balance.value())
+      .unwrap_or_default();
+
+    if outputs <= 1 {
+      self.to_balance.remove(&address)?;
+    } else {
+      self
+        .to_balance
+        .insert(&address, (outputs - 1, shibes - value))?;
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,342 @@
+use {
+  super::*,
+  crate::auxpow::AuxPow,
+  std::io::{Read, Seek, SeekFrom},
+};
+
+/// Blocks this close to the node's tip are fetched over RPC, since the most
+/// recent blk file may still be partially written and the tip may reorg.
+pub(crate) const BLK_FILES_TIP_DISTANCE: u32 = 100;
+
+const HEADER_SIZE: usize = 80;
+const RECORD_PREFIX_SIZE: usize = 8;
+
+/// Where a block's serialized bytes live inside the node's blk*.dat files.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Location {
+  file: usize,
+  offset: u64,
+  len: usize,
+  pub(crate) hash: BlockHash,
+}
+
+/// Blocks stored by Dogecoin Core in `blocks/blk*.dat`, indexed by the hash of
+/// their parent. Core appends blocks in the order it receives them, so blocks
+/// are out of order and stale forks are interleaved with the main chain.
+///
+/// Only record prefixes and headers are read when scanning, and `refresh`
+/// resumes where the last scan stopped, so a `BlkFiles` can be kept across
+/// updates and only reads the headers of blocks appended since.
+pub(crate) struct BlkFiles {
+  blocks_dir: PathBuf,
+  children: HashMap<BlockHash, Vec<Location>>,
+  files: Vec<PathBuf>,
+  magic: [u8; 4],
+  /// Offset after the last complete block found in each file.
+  scanned: Vec<u64>,
+  xor_key: Option<[u8; 8]>,
+}
+
+impl BlkFiles {
+  pub(crate) fn open(blocks_dir: &Path, magic: [u8; 4]) -> Result<Self> {
+    let xor_key = match fs::read(blocks_dir.join("xor.dat")) {
+      Ok(key) => Some(
+        key
+          .try_into()
+          .map_err(|key: Vec<u8>| anyhow!("xor.dat must be 8 bytes, not {}", key.len()))?,
+      )
+      .filter(|key: &[u8; 8]| key.iter().any(|byte| *byte != 0)),
+      Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+      Err(err) => return Err(err.into()),
+    };
+
+    let mut blk_files = Self {
+      blocks_dir: blocks_dir.into(),
+      children: HashMap::new(),
+      files: Vec::new(),
+      magic,
+      scanned: Vec::new(),
+      xor_key,
+    };
+
+    blk_files.refresh()?;
+
+    Ok(blk_files)
+  }
+
+  /// Scans blocks written since the last scan, both appended to known files
+  /// and in new ones. Core never rewrites or renames blk files, so known
+  /// files keep their place.
+  pub(crate) fn refresh(&mut self) -> Result {
+    let mut files = fs::read_dir(&self.blocks_dir)
+      .with_context(|| format!("failed to read blocks dir `{}`", self.blocks_dir.display()))?
+      .map(|entry| entry.map(|entry| entry.path()))
+      .collect::<io::Result<Vec<PathBuf>>>()?
+      .into_iter()
+      .filter(|path| {
+        path
+          .file_name()
+          .and_then(|name| name.to_str())
+          .map(|name| name.starts_with("blk") && name.ends_with(".dat"))
+          .unwrap_or_default()
+      })
+      .collect::<Vec<PathBuf>>();
+
+    files.sort();
+
+    for path in files.into_iter().skip(self.files.len()) {
+      self.files.push(path);
+      self.scanned.push(0);
+    }
+
+    let mut blocks = 0;
+
+    for file in 0..self.files.len() {
+      let (records, scanned) = self.scan(file)?;
+
+      blocks += records.len();
+
+      for (prev, location) in records {
+        self.children.entry(prev).or_default().push(location);
+      }
+
+      self.scanned[file] = scanned;
+    }
+
+    log::info!(
+      "Found {blocks} new blocks in {} blk files",
+      self.files.len()
+    );
+
+    Ok(())
+  }
+
+  /// Reads the prefix and header of each block in `file` from where the last
+  /// scan stopped, seeking past block bodies. Returns the blocks found and the
+  /// offset after the last complete one.
+  fn scan(&self, file: usize) -> Result<(Vec<(BlockHash, Location)>, u64)> {
+    let mut reader = File::open(&self.files[file])?;
+    let file_len = reader.metadata()?.len();
+
+    let mut records = Vec::new();
+    let mut offset = self.scanned[file];
+    let mut buffer = [0; RECORD_PREFIX_SIZE + HEADER_SIZE];
+
+    while offset + u64::try_from(buffer.len()).unwrap() <= file_len {
+      reader.seek(SeekFrom::Start(offset))?;
+      reader.read_exact(&mut buffer)?;
+
+      if let Some(key) = self.xor_key {
+        xor(&mut buffer, key, offset);
+      }
+
+      // Core preallocates blk files, so the unwritten tail is zeroed.
+      if buffer[..4] != self.magic {
+        break;
+      }
+
+      let len = u32::from_le_bytes(buffer[4..RECORD_PREFIX_SIZE].try_into().unwrap());
+      let start = offset + u64::try_from(RECORD_PREFIX_SIZE).unwrap();
+
+      if (len as usize) < HEADER_SIZE || start + u64::from(len) > file_len {
+        break;
+      }
+
+      let header = &buffer[RECORD_PREFIX_SIZE..];
+
+      records.push((
+        BlockHash::from_slice(&header[4..36])?,
+        Location {
+          file,
+          offset: start,
+          len: len.try_into().unwrap(),
+          hash: BlockHash::hash(header),
+        },
+      ));
+
+      offset = start + u64::from(len);
+    }
+
+    Ok((records, offset))
+  }
+
+  pub(crate) fn children(&self, prev: &BlockHash) -> &[Location] {
+    self
+      .children
+      .get(prev)
+      .map(Vec::as_slice)
+      .unwrap_or_default()
+  }
+
+  pub(crate) fn read(&self, location: &Location) -> Result<(Block, Option<AuxPow>)> {
+    let mut file = File::open(&self.files[location.file])?;
+    file.seek(SeekFrom::Start(location.offset))?;
+
+    let mut bytes = vec![0; location.len];
+    file.read_exact(&mut bytes)?;
+
+    if let Some(key) = self.xor_key {
+      xor(&mut bytes, key, location.offset);
+    }
+
+    let (block, auxpow) = AuxPow::decode_block(&bytes)?;
+
+    if block.block_hash() != location.hash {
+      bail!(
+        "block at {}:{} does not match expected hash {}",
+        self.files[location.file].display(),
+        location.offset,
+        location.hash
+      );
+    }
+
+    Ok((block, auxpow))
+  }
+}
+
+/// Undoes the obfuscation Core applies to block files, where each byte is
+/// XORed with the key byte at the same position modulo the key length.
+fn xor(bytes: &mut [u8], key: [u8; 8], offset: u64) {
+  for (i, byte) in bytes.iter_mut().enumerate() {
+    *byte ^= key[((offset + i as u64) % 8) as usize];
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, bitcoin::consensus::serialize};
+
+  const MAGIC: [u8; 4] = [0xfa, 0xbf, 0xb5, 0xda];
+
+  fn child(parent: &Block, nonce: u32) -> Block {
+    let mut block = parent.clone();
+    block.header.prev_blockhash = parent.block_hash();
+    block.header.nonce = nonce;
+    block
+  }
+
+  fn record(block: &Block) -> Vec<u8> {
+    let bytes = serialize(block);
+    let mut record = MAGIC.to_vec();
+    record.extend_from_slice(&u32::try_from(bytes.len()).unwrap().to_le_bytes());
+    record.extend_from_slice(&bytes);
+    record
+  }
+
+  fn write_blocks(dir: &Path, name: &str, blocks: &[&Block], key: Option<[u8; 8]>) {
+    let mut bytes = blocks.iter().flat_map(|block| record(block)).collect::<Vec<u8>>();
+    bytes.extend_from_slice(&[0; 64]);
+
+    if let Some(key) = key {
+      xor(&mut bytes, key, 0);
+    }
+
+    fs::write(dir.join(name), bytes).unwrap();
+  }
+
+  #[test]
+  fn blocks_are_indexed_by_parent() {
+    let dir = TempDir::new().unwrap();
+
+    let genesis = Chain::Regtest.genesis_block();
+    let one = child(&genesis, 1);
+    let two = child(&one, 2);
+    let stale = child(&one, 3);
+
+    write_blocks(dir.path(), "blk00000.dat", &[&genesis, &two], None);
+    write_blocks(dir.path(), "blk00001.dat", &[&stale, &one], None);
+    fs::write(dir.path().join("rev00000.dat"), [0xff; 16]).unwrap();
+
+    let blk_files = BlkFiles::open(dir.path(), MAGIC).unwrap();
+
+    let roots = blk_files.children(&BlockHash::all_zeros());
+    assert_eq!(roots.len(), 1);
+    assert_eq!(blk_files.read(&roots[0]).unwrap(), (genesis.clone(), None));
+
+    let next = blk_files.children(&genesis.block_hash());
+    assert_eq!(next.len(), 1);
+    assert_eq!(blk_files.read(&next[0]).unwrap(), (one.clone(), None));
+
+    let forks = blk_files
+      .children(&one.block_hash())
+      .iter()
+      .map(|location| location.hash)
+      .collect::<Vec<BlockHash>>();
+    assert_eq!(forks, [two.block_hash(), stale.block_hash()]);
+
+    assert!(blk_files.children(&two.block_hash()).is_empty());
+  }
+
+  #[test]
+  fn refresh_finds_appended_blocks() {
+    let dir = TempDir::new().unwrap();
+
+    let genesis = Chain::Regtest.genesis_block();
+    let one = child(&genesis, 1);
+    let two = child(&one, 2);
+
+    write_blocks(dir.path(), "blk00000.dat", &[&genesis], None);
+
+    let mut blk_files = BlkFiles::open(dir.path(), MAGIC).unwrap();
+    assert!(blk_files.children(&genesis.block_hash()).is_empty());
+
+    write_blocks(dir.path(), "blk00000.dat", &[&genesis, &one], None);
+    write_blocks(dir.path(), "blk00001.dat", &[&two], None);
+
+    blk_files.refresh().unwrap();
+
+    assert_eq!(blk_files.children(&BlockHash::all_zeros()).len(), 1);
+
+    let next = blk_files.children(&genesis.block_hash());
+    assert_eq!(next.len(), 1);
+    assert_eq!(blk_files.read(&next[0]).unwrap(), (one.clone(), None));
+
+    let next = blk_files.children(&one.block_hash());
+    assert_eq!(next.len(), 1);
+    assert_eq!(blk_files.read(&next[0]).unwrap(), (two.clone(), None));
+  }
+
+  #[test]
+  fn obfuscated_blocks_are_decoded() {
+    let dir = TempDir::new().unwrap();
+    let key = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    let genesis = Chain::Regtest.genesis_block();
+    let one = child(&genesis, 1);
+
+    write_blocks(dir.path(), "blk00000.dat", &[&genesis, &one], Some(key));
+    fs::write(dir.path().join("xor.dat"), key).unwrap();
+
+    let blk_files = BlkFiles::open(dir.path(), MAGIC).unwrap();
+
+    let next = blk_files.children(&genesis.block_hash());
+    assert_eq!(next.len(), 1);
+    assert_eq!(blk_files.read(&next[0]).unwrap(), (one.clone(), None));
+  }
+
+  #[test]
+  fn other_network_blocks_are_ignored() {
+    let dir = TempDir::new().unwrap();
+
+    write_blocks(
+      dir.path(),
+      "blk00000.dat",
+      &[&Chain::Regtest.genesis_block()],
+      None,
+    );
+
+    let blk_files = BlkFiles::open(dir.path(), Chain::Mainnet.network_magic()).unwrap();
+
+    assert!(blk_files.children(&BlockHash::all_zeros()).is_empty());
+  }
+
+  #[test]
+  fn xor_is_position_dependent() {
+    let key = [1, 2, 3, 4, 5, 6, 7, 8];
+    let mut bytes = vec![0; 4];
+
+    xor(&mut bytes, key, 6);
+
+    assert_eq!(bytes, [7, 8, 1, 2]);
+  }
+}
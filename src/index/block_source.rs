@@ -0,0 +1,513 @@
+use {
+  super::{fetcher::Fetcher, *},
+  crate::auxpow::AuxPow,
+  bitcoin::BlockHeader,
+  bitcoincore_rpc::Auth,
+  futures::future::try_join_all,
+  std::{collections::hash_map, ops::Range},
+};
+
+/// Chain data the updater indexes. Implemented over Dogecoin Core RPC and over
+/// a directory of recorded blocks, so the updaters can replay a fixed range of
+/// blocks without a node, and in memory for tests.
+pub(crate) trait BlockSource: Send + Sync {
+  /// Height of the first block the source can provide. Indexing a source that
+  /// doesn't start at genesis skips everything below this height.
+  fn start_height(&self) -> u32 {
+    0
+  }
+
+  fn tip_height(&self) -> Result<u32>;
+
+  fn block_hash(&self, height: u32) -> Result<Option<BlockHash>>;
+
+  fn block(&self, height: u32) -> Result<Option<Block>>;
+
+  /// The block at `height` along with its AuxPoW header, for sources that
+  /// keep the serialized block around.
+  fn block_with_auxpow(&self, height: u32) -> Result<Option<(Block, Option<AuxPow>)>> {
+    Ok(self.block(height)?.map(|block| (block, None)))
+  }
+
+  fn block_header(&self, height: u32) -> Result<Option<BlockHeader>>;
+
+  /// Looks up transactions by txid, in the order requested. Fails if any of
+  /// them are unknown.
+  fn transactions(&self, txids: &[Txid]) -> Result<Vec<Transaction>>;
+}
+
+pub(crate) struct RpcBlockSource {
+  client: Client,
+  fetcher: Fetcher,
+  parallel_requests: usize,
+  /// Drives `fetcher`, whose pooled connections only work on the runtime that
+  /// opened them. Only taken on drop.
+  runtime: Option<tokio::runtime::Runtime>,
+}
+
+impl RpcBlockSource {
+  pub(crate) fn new(rpc_url: &str, auth: Auth, parallel_requests: usize) -> Result<Self> {
+    Ok(Self {
+      client: Client::new(rpc_url, auth.clone()).context("failed to connect to RPC URL")?,
+      fetcher: Fetcher::new(rpc_url, auth)?,
+      parallel_requests: parallel_requests.max(1),
+      runtime: Some(
+        tokio::runtime::Builder::new_current_thread()
+          .enable_all()
+          .build()?,
+      ),
+    })
+  }
+}
+
+impl Drop for RpcBlockSource {
+  fn drop(&mut self) {
+    // Dropping a runtime blocks, which panics if the index is dropped from
+    // within the server's runtime.
+    if let Some(runtime) = self.runtime.take() {
+      runtime.shutdown_background();
+    }
+  }
+}
+
+impl BlockSource for RpcBlockSource {
+  fn tip_height(&self) -> Result<u32> {
+    Ok(u32::try_from(self.client.get_block_count()?).unwrap())
+  }
+
+  fn block_hash(&self, height: u32) -> Result<Option<BlockHash>> {
+    self.client.get_block_hash(height.into()).into_option()
+  }
+
+  fn block(&self, height: u32) -> Result<Option<Block>> {
+    self
+      .block_hash(height)?
+      .map(|hash| Ok(self.client.get_block(&hash)?))
+      .transpose()
+  }
+
+  fn block_with_auxpow(&self, height: u32) -> Result<Option<(Block, Option<AuxPow>)>> {
+    self
+      .block_hash(height)?
+      .map(|hash| AuxPow::decode_block(&hex::decode(self.client.get_block_hex(&hash)?)?))
+      .transpose()
+  }
+
+  fn block_header(&self, height: u32) -> Result<Option<BlockHeader>> {
+    self
+      .block_hash(height)?
+      .map(|hash| Ok(self.client.get_block_header(&hash)?))
+      .transpose()
+  }
+
+  fn transactions(&self, txids: &[Txid]) -> Result<Vec<Transaction>> {
+    if txids.is_empty() {
+      return Ok(Vec::new());
+    }
+
+    // Keep in mind that default rpcworkqueue in dogecoind is 16, meaning more
+    // than 16 concurrent requests will be rejected, so the batch is split into
+    // at most `parallel_requests` JSON-RPC calls.
+    let chunk_size = txids.len() / self.parallel_requests + 1;
+
+    self
+      .runtime
+      .as_ref()
+      .unwrap()
+      .block_on(try_join_all(
+        txids
+          .chunks(chunk_size)
+          .map(|chunk| self.fetcher.get_transactions(chunk.to_vec())),
+      ))
+      .map(|chunks| chunks.into_iter().flatten().collect())
+  }
+}
+
+/// Blocks recorded by `ord index record`, stored as `blocks/<HEIGHT>.block`
+/// files serialized the way the node serves them, AuxPoW headers included,
+/// along with the transactions they spend from outside the recorded range as
+/// `transactions/<TXID>.tx`.
+pub(crate) struct DirectoryBlockSource {
+  dir: PathBuf,
+  heights: Range<u32>,
+  /// Height and position of every transaction in the recorded blocks.
+  locations: HashMap<Txid, (u32, usize)>,
+}
+
+impl DirectoryBlockSource {
+  pub(crate) fn open(dir: &Path) -> Result<Self> {
+    let mut heights = Vec::new();
+
+    for entry in fs::read_dir(dir.join("blocks"))
+      .with_context(|| format!("failed to read recorded blocks in `{}`", dir.display()))?
+    {
+      let path = entry?.path();
+
+      if path.extension().and_then(|extension| extension.to_str()) != Some("block") {
+        continue;
+      }
+
+      heights.push(
+        path
+          .file_stem()
+          .and_then(|stem| stem.to_str())
+          .and_then(|stem| stem.parse::<u32>().ok())
+          .ok_or_else(|| anyhow!("invalid recorded block file `{}`", path.display()))?,
+      );
+    }
+
+    heights.sort_unstable();
+
+    let heights = match (heights.first(), heights.last()) {
+      (Some(&first), Some(&last)) => first..last + 1,
+      _ => bail!("no recorded blocks in `{}`", dir.display()),
+    };
+
+    ensure!(
+      heights.len() == usize::try_from(heights.end - heights.start).unwrap(),
+      "recorded blocks in `{}` are not contiguous",
+      dir.display()
+    );
+
+    let mut source = Self {
+      dir: dir.into(),
+      heights: heights.clone(),
+      locations: HashMap::new(),
+    };
+
+    for height in heights {
+      let (block, _) = source.read_block(height)?;
+      for (i, tx) in block.txdata.iter().enumerate() {
+        source.locations.insert(tx.txid(), (height, i));
+      }
+    }
+
+    Ok(source)
+  }
+
+  /// Writes `heights` from `source` to `dir` in the layout `open` expects.
+  pub(crate) fn record(source: &dyn BlockSource, dir: &Path, heights: Range<u32>) -> Result {
+    fs::create_dir_all(dir.join("blocks"))?;
+    fs::create_dir_all(dir.join("transactions"))?;
+
+    let mut created = HashSet::new();
+    let mut spent = Vec::new();
+
+    for height in heights {
+      let (block, auxpow) = source
+        .block_with_auxpow(height)?
+        .ok_or_else(|| anyhow!("block {height} not found"))?;
+
+      for tx in &block.txdata {
+        spent.extend(
+          tx.input
+            .iter()
+            .map(|input| input.previous_output.txid)
+            .filter(|txid| *txid != Txid::all_zeros() && !created.contains(txid)),
+        );
+        created.insert(tx.txid());
+      }
+
+      fs::write(
+        dir.join("blocks").join(format!("{height}.block")),
+        AuxPow::encode_block(&block, auxpow.as_ref()),
+      )?;
+    }
+
+    spent.sort_unstable();
+    spent.dedup();
+
+    for tx in source.transactions(&spent)? {
+      fs::write(
+        dir.join("transactions").join(format!("{}.tx", tx.txid())),
+        consensus::serialize(&tx),
+      )?;
+    }
+
+    Ok(())
+  }
+
+  fn read_block(&self, height: u32) -> Result<(Block, Option<AuxPow>)> {
+    AuxPow::decode_block(&fs::read(
+      self.dir.join("blocks").join(format!("{height}.block")),
+    )?)
+  }
+
+  /// Reads a transaction spent from outside the recorded blocks.
+  fn spent_transaction(&self, txid: Txid) -> Result<Transaction> {
+    match fs::read(self.dir.join("transactions").join(format!("{txid}.tx"))) {
+      Ok(bytes) => Ok(consensus::deserialize(&bytes)?),
+      Err(err) if err.kind() == io::ErrorKind::NotFound => {
+        Err(anyhow!("transaction {txid} not found"))
+      }
+      Err(err) => Err(err.into()),
+    }
+  }
+}
+
+impl BlockSource for DirectoryBlockSource {
+  fn start_height(&self) -> u32 {
+    self.heights.start
+  }
+
+  fn tip_height(&self) -> Result<u32> {
+    Ok(self.heights.end - 1)
+  }
+
+  fn block_hash(&self, height: u32) -> Result<Option<BlockHash>> {
+    Ok(self.block_header(height)?.map(|header| header.block_hash()))
+  }
+
+  fn block(&self, height: u32) -> Result<Option<Block>> {
+    Ok(self.block_with_auxpow(height)?.map(|(block, _)| block))
+  }
+
+  fn block_with_auxpow(&self, height: u32) -> Result<Option<(Block, Option<AuxPow>)>> {
+    if !self.heights.contains(&height) {
+      return Ok(None);
+    }
+
+    self.read_block(height).map(Some)
+  }
+
+  fn block_header(&self, height: u32) -> Result<Option<BlockHeader>> {
+    Ok(self.block(height)?.map(|block| block.header))
+  }
+
+  /// Reads each block holding one of `txids` once.
+  fn transactions(&self, txids: &[Txid]) -> Result<Vec<Transaction>> {
+    let mut blocks = HashMap::new();
+
+    txids
+      .iter()
+      .map(|txid| {
+        let Some(&(height, i)) = self.locations.get(txid) else {
+          return self.spent_transaction(*txid);
+        };
+
+        let block = match blocks.entry(height) {
+          hash_map::Entry::Occupied(entry) => entry.into_mut(),
+          hash_map::Entry::Vacant(entry) => entry.insert(self.read_block(height)?.0),
+        };
+
+        Ok(block.txdata[i].clone())
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+pub(crate) struct MemoryBlockSource {
+  blocks: Vec<Block>,
+  start_height: u32,
+  transactions: HashMap<Txid, Transaction>,
+}
+
+#[cfg(test)]
+impl MemoryBlockSource {
+  /// Serves `blocks` starting at `start_height`. Transactions spent from
+  /// below `start_height` must be provided as `prevouts`.
+  pub(crate) fn new(
+    start_height: u32,
+    blocks: Vec<Block>,
+    prevouts: impl IntoIterator<Item = Transaction>,
+  ) -> Self {
+    let transactions = blocks
+      .iter()
+      .flat_map(|block| block.txdata.iter().cloned())
+      .chain(prevouts)
+      .map(|tx| (tx.txid(), tx))
+      .collect();
+
+    Self {
+      blocks,
+      start_height,
+      transactions,
+    }
+  }
+
+  fn get(&self, height: u32) -> Option<&Block> {
+    self
+      .blocks
+      .get(usize::try_from(height.checked_sub(self.start_height)?).unwrap())
+  }
+}
+
+#[cfg(test)]
+impl BlockSource for MemoryBlockSource {
+  fn start_height(&self) -> u32 {
+    self.start_height
+  }
+
+  fn tip_height(&self) -> Result<u32> {
+    (self.start_height + u32::try_from(self.blocks.len()).unwrap())
+      .checked_sub(1)
+      .ok_or_else(|| anyhow!("no blocks"))
+  }
+
+  fn block_hash(&self, height: u32) -> Result<Option<BlockHash>> {
+    Ok(self.get(height).map(Block::block_hash))
+  }
+
+  fn block(&self, height: u32) -> Result<Option<Block>> {
+    Ok(self.get(height).cloned())
+  }
+
+  fn block_header(&self, height: u32) -> Result<Option<BlockHeader>> {
+    Ok(self.get(height).map(|block| block.header))
+  }
+
+  fn transactions(&self, txids: &[Txid]) -> Result<Vec<Transaction>> {
+    txids
+      .iter()
+      .map(|txid| {
+        self
+          .transactions
+          .get(txid)
+          .cloned()
+          .ok_or_else(|| anyhow!("transaction {txid} not found"))
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use {super::*, crate::index::testing::Context};
+
+  /// Attaches the same AuxPoW header to every merge-mined block.
+  pub(crate) struct MergeMined(pub(crate) MemoryBlockSource, pub(crate) AuxPow);
+
+  impl BlockSource for MergeMined {
+    fn tip_height(&self) -> Result<u32> {
+      self.0.tip_height()
+    }
+
+    fn block_hash(&self, height: u32) -> Result<Option<BlockHash>> {
+      self.0.block_hash(height)
+    }
+
+    fn block(&self, height: u32) -> Result<Option<Block>> {
+      self.0.block(height)
+    }
+
+    fn block_with_auxpow(&self, height: u32) -> Result<Option<(Block, Option<AuxPow>)>> {
+      Ok(self.0.block(height)?.map(|block| {
+        let auxpow = AuxPow::is_merge_mined(&block.header).then(|| self.1.clone());
+        (block, auxpow)
+      }))
+    }
+
+    fn block_header(&self, height: u32) -> Result<Option<BlockHeader>> {
+      self.0.block_header(height)
+    }
+
+    fn transactions(&self, txids: &[Txid]) -> Result<Vec<Transaction>> {
+      self.0.transactions(txids)
+    }
+  }
+
+  fn recorded(context: &Context) -> Vec<Block> {
+    let source = &context.index.block_source;
+    (0..=source.tip_height().unwrap())
+      .map(|height| source.block(height).unwrap().unwrap())
+      .collect()
+  }
+
+  #[test]
+  fn memory_source_serves_blocks_by_height() {
+    let context = Context::builder().build();
+    context.mine_blocks(2);
+
+    let blocks = recorded(&context);
+    let source = MemoryBlockSource::new(1, blocks[1..].to_vec(), blocks[0].txdata.clone());
+
+    assert_eq!(source.start_height(), 1);
+    assert_eq!(source.tip_height().unwrap(), 2);
+    assert_eq!(source.block(0).unwrap(), None);
+    assert_eq!(source.block(2).unwrap(), Some(blocks[2].clone()));
+    assert_eq!(source.block(3).unwrap(), None);
+    assert_eq!(
+      source.block_hash(1).unwrap(),
+      context.index.block_source.block_hash(1).unwrap()
+    );
+
+    let txid = blocks[0].txdata[0].txid();
+    assert_eq!(
+      source.transactions(&[txid]).unwrap(),
+      [blocks[0].txdata[0].clone()]
+    );
+    assert!(source.transactions(&[txid, Txid::all_zeros()]).is_err());
+  }
+
+  #[test]
+  fn recorded_directory_round_trips() {
+    let context = Context::builder().build();
+    context.mine_blocks(1);
+    context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      ..Default::default()
+    });
+    context.mine_blocks(1);
+
+    let tempdir = TempDir::new().unwrap();
+
+    DirectoryBlockSource::record(&*context.index.block_source, tempdir.path(), 2..3).unwrap();
+
+    let source = DirectoryBlockSource::open(tempdir.path()).unwrap();
+
+    let blocks = recorded(&context);
+
+    assert_eq!(source.start_height(), 2);
+    assert_eq!(source.tip_height().unwrap(), 2);
+    assert_eq!(source.block(1).unwrap(), None);
+    assert_eq!(source.block(2).unwrap(), Some(blocks[2].clone()));
+    assert_eq!(source.block_hash(2).unwrap(), Some(blocks[2].block_hash()));
+
+    let spent = blocks[1].txdata[0].clone();
+    assert_eq!(source.transactions(&[spent.txid()]).unwrap(), [spent]);
+
+    let spending = blocks[2].txdata[1].clone();
+    assert_eq!(source.transactions(&[spending.txid()]).unwrap(), [spending]);
+  }
+
+  #[test]
+  fn empty_directory_is_rejected() {
+    let tempdir = TempDir::new().unwrap();
+    fs::create_dir(tempdir.path().join("blocks")).unwrap();
+
+    assert!(DirectoryBlockSource::open(tempdir.path()).is_err());
+  }
+
+  #[test]
+  fn recorded_merge_mined_blocks_keep_their_auxpow() {
+    let context = Context::builder().build();
+    context.mine_blocks(2);
+
+    let mut blocks = recorded(&context);
+    blocks[2].header.version |= 1 << 8;
+
+    let auxpow = crate::auxpow::tests::auxpow();
+
+    let tempdir = TempDir::new().unwrap();
+
+    DirectoryBlockSource::record(
+      &MergeMined(MemoryBlockSource::new(0, blocks.clone(), []), auxpow.clone()),
+      tempdir.path(),
+      1..3,
+    )
+    .unwrap();
+
+    let source = DirectoryBlockSource::open(tempdir.path()).unwrap();
+
+    assert_eq!(
+      source.block_with_auxpow(1).unwrap(),
+      Some((blocks[1].clone(), None))
+    );
+    assert_eq!(
+      source.block_with_auxpow(2).unwrap(),
+      Some((blocks[2].clone(), Some(auxpow)))
+    );
+    assert_eq!(source.block(2).unwrap(), Some(blocks[2].clone()));
+  }
+}
@@ -28,6 +28,9 @@ impl Entry for BlockHash {
 
 pub(crate) type TxidValue = [u8; 32];
 
+/// SHA-256 of an inscription's serialized content, see `Inscription::to_bytes`.
+pub(crate) type ContentHashValue = [u8; 32];
+
 impl Entry for Txid {
   type Value = TxidValue;
 
@@ -378,9 +381,10 @@ impl Entry for InscriptionId {
 pub(crate) struct OutPointMap {
   pub(crate) value: u64,
   pub(crate) address: [u8; 34],
+  pub(crate) height: u32,
 }
 
-pub(crate) type OutPointMapValue = (u64, [u8; 34]);
+pub(crate) type OutPointMapValue = (u64, [u8; 34], u32);
 
 impl Entry for OutPointMap {
   type Value = OutPointMapValue;
@@ -389,14 +393,47 @@ impl Entry for OutPointMap {
     Self {
       value: value.0,
       address: value.1,
+      height: value.2,
     }
   }
 
   fn store(self) -> Self::Value {
-    (self.value, self.address)
+    (self.value, self.address, self.height)
   }
 }
 
+pub(crate) type AddressOutPointValue = [u8; 70];
+
+/// Key of an output of `address` in `ADDRESS_OUTPOINT_TO_OUTPUT`. Keys of the
+/// same address share a prefix and sort by outpoint, so an address's outputs
+/// can be paged through with range scans.
+pub(super) fn address_outpoint_key(
+  address: &[u8; 34],
+  outpoint: &OutPointValue,
+) -> AddressOutPointValue {
+  let mut key = [0; 70];
+  key[..34].copy_from_slice(address);
+  key[34..].copy_from_slice(outpoint);
+  key
+}
+
+pub(crate) type AddressHeightOutPointValue = [u8; 74];
+
+/// Key of an output of `address` created at `height` in
+/// `ADDRESS_HEIGHT_OUTPOINT_TO_VALUE`. The height is stored big-endian, so
+/// scanning an address's keys in reverse lists its newest outputs first.
+pub(super) fn address_height_outpoint_key(
+  address: &[u8; 34],
+  height: u32,
+  outpoint: &OutPointValue,
+) -> AddressHeightOutPointValue {
+  let mut key = [0; 74];
+  key[..34].copy_from_slice(address);
+  key[34..38].copy_from_slice(&height.to_be_bytes());
+  key[38..].copy_from_slice(outpoint);
+  key
+}
+
 pub type OutPointValue = [u8; 36];
 
 impl Entry for OutPoint {
@@ -431,27 +468,194 @@ impl Entry for SatPoint {
 
 pub(super) type SatRange = (u64, u64);
 
-impl Entry for SatRange {
-  type Value = [u8; 11];
+/// Leads the sat ranges of every output in `OUTPOINT_TO_SAT_RANGES`. Ranges
+/// were stored in 11 bytes before, with 51-bit starts and 33-bit lengths,
+/// which are too narrow for dogecoin's supply and block rewards.
+pub(super) const SAT_RANGES_VERSION: u8 = 1;
+
+/// Appends `range` to the encoded sat ranges of an output, which are a
+/// version byte followed by the start and length of each range as LEB128
+/// varints, so that ranges near genesis and ranges of a few sats stay short.
+pub(super) fn push_sat_range(sat_ranges: &mut Vec<u8>, (start, end): SatRange) {
+  if sat_ranges.is_empty() {
+    sat_ranges.push(SAT_RANGES_VERSION);
+  }
+
+  push_varint(sat_ranges, start);
+  push_varint(sat_ranges, end - start);
+}
+
+#[cfg(test)]
+pub(super) fn encode_sat_ranges(ranges: impl IntoIterator<Item = SatRange>) -> Vec<u8> {
+  let mut sat_ranges = Vec::new();
+
+  for range in ranges {
+    push_sat_range(&mut sat_ranges, range);
+  }
+
+  sat_ranges
+}
 
-  fn load([b0, b1, b2, b3, b4, b5, b6, b7, b8, b9, b10]: Self::Value) -> Self {
-    let raw_base = u64::from_le_bytes([b0, b1, b2, b3, b4, b5, b6, 0]);
+pub(super) fn decode_sat_ranges(sat_ranges: &[u8]) -> Result<Vec<SatRange>> {
+  let Some((&version, mut rest)) = sat_ranges.split_first() else {
+    return Ok(Vec::new());
+  };
 
-    // 51 bit base
-    let base = raw_base & ((1 << 51) - 1);
+  ensure!(
+    version == SAT_RANGES_VERSION,
+    "unknown sat range encoding version {version}"
+  );
 
-    let raw_delta = u64::from_le_bytes([b6, b7, b8, b9, b10, 0, 0, 0]);
+  let mut ranges = Vec::new();
 
-    // 33 bit delta
-    let delta = raw_delta >> 3;
+  while !rest.is_empty() {
+    let start = read_varint(&mut rest)?;
+    let len = read_varint(&mut rest)?;
 
-    (base, base + delta)
+    ranges.push((
+      start,
+      start
+        .checked_add(len)
+        .ok_or_else(|| anyhow!("sat range starting at {start} overflows"))?,
+    ));
   }
 
-  fn store(self) -> Self::Value {
-    let base = self.0;
-    let delta = self.1 - self.0;
-    let n = u128::from(base) | u128::from(delta) << 51;
-    n.to_le_bytes()[0..11].try_into().unwrap()
+  Ok(ranges)
+}
+
+fn push_varint(buffer: &mut Vec<u8>, mut n: u64) {
+  while n >= 0x80 {
+    buffer.push(n.to_le_bytes()[0] | 0x80);
+    n >>= 7;
+  }
+
+  buffer.push(n.to_le_bytes()[0]);
+}
+
+fn read_varint(buffer: &mut &[u8]) -> Result<u64> {
+  let mut n = 0;
+
+  for shift in (0..64).step_by(7) {
+    let Some((&byte, rest)) = buffer.split_first() else {
+      bail!("truncated sat range");
+    };
+
+    *buffer = rest;
+
+    let bits = u64::from(byte & 0x7f);
+
+    ensure!(
+      bits << shift >> shift == bits,
+      "sat range varint overflows u64"
+    );
+
+    n |= bits << shift;
+
+    if byte & 0x80 == 0 {
+      return Ok(n);
+    }
+  }
+
+  bail!("sat range varint overflows u64")
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    bitcoin::{
+      blockdata::constants::COIN_VALUE,
+      secp256k1::rand::{rngs::StdRng, RngCore, SeedableRng},
+    },
+  };
+
+  #[track_caller]
+  fn case(ranges: &[SatRange]) {
+    assert_eq!(
+      decode_sat_ranges(&encode_sat_ranges(ranges.iter().copied())).unwrap(),
+      ranges
+    );
+  }
+
+  #[test]
+  fn sat_ranges_round_trip() {
+    case(&[]);
+    case(&[(0, 0)]);
+    case(&[(0, 1)]);
+    case(&[(0, 88 * COIN_VALUE)]);
+    case(&[(1 << 51, (1 << 51) + (1 << 33))]);
+    case(&[
+      (u64::MAX - 1, u64::MAX),
+      (0, u64::MAX),
+      (u64::MAX, u64::MAX),
+    ]);
+  }
+
+  #[test]
+  fn random_sat_ranges_near_u64_max_round_trip() {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for _ in 0..1000 {
+      let ranges = (0..rng.next_u64() % 8)
+        .map(|_| {
+          let end = u64::MAX - rng.next_u64() % (1 << 40);
+          let len = rng.next_u64() >> (rng.next_u64() % 64);
+          (end.saturating_sub(len), end)
+        })
+        .collect::<Vec<SatRange>>();
+
+      case(&ranges);
+    }
+  }
+
+  #[test]
+  fn random_sat_ranges_round_trip() {
+    let mut rng = StdRng::seed_from_u64(1);
+
+    for _ in 0..1000 {
+      let start = rng.next_u64() >> (rng.next_u64() % 64);
+      let end = start.saturating_add(rng.next_u64() >> (rng.next_u64() % 64));
+      case(&[(start, end)]);
+    }
+  }
+
+  #[test]
+  fn pushed_sat_ranges_match_encoded_ones() {
+    let mut sat_ranges = Vec::new();
+    push_sat_range(&mut sat_ranges, (10, 20));
+    push_sat_range(&mut sat_ranges, (u64::MAX - 5, u64::MAX));
+    assert_eq!(
+      sat_ranges,
+      encode_sat_ranges([(10, 20), (u64::MAX - 5, u64::MAX)])
+    );
+  }
+
+  #[test]
+  fn common_sat_ranges_are_compact() {
+    assert_eq!(encode_sat_ranges([(0, 1)]), [SAT_RANGES_VERSION, 0, 1]);
+    assert_eq!(encode_sat_ranges([(0, 50 * COIN_VALUE)]).len(), 7);
+    assert_eq!(encode_sat_ranges([(u64::MAX - 1, u64::MAX)]).len(), 12);
+  }
+
+  #[test]
+  fn malformed_sat_ranges_are_rejected() {
+    #[track_caller]
+    fn case(bytes: &[u8], expected: &str) {
+      assert_eq!(decode_sat_ranges(bytes).unwrap_err().to_string(), expected);
+    }
+
+    let max = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+
+    case(&[2, 0, 1], "unknown sat range encoding version 2");
+    case(&[SAT_RANGES_VERSION, 0], "truncated sat range");
+    case(&[SAT_RANGES_VERSION, 0x80], "truncated sat range");
+    case(
+      &[&[SAT_RANGES_VERSION], max.as_slice(), &[2, 0]].concat(),
+      "sat range varint overflows u64",
+    );
+    case(
+      &[&[SAT_RANGES_VERSION], max.as_slice(), &[1, 1]].concat(),
+      "sat range starting at 18446744073709551615 overflows",
+    );
   }
 }
@@ -0,0 +1,529 @@
+use {
+  super::*,
+  crate::inscription::ParsedInscription,
+  redb::{MultimapTableDefinition, ReadableMultimapTable},
+};
+
+/// Upgrades an index from schema `from` to `to`.
+pub(crate) struct Migration {
+  pub(crate) from: u64,
+  pub(crate) to: u64,
+  pub(crate) description: &'static str,
+  run: fn(&WriteTransaction, Chain, &ProgressBar) -> Result,
+}
+
+impl fmt::Debug for Migration {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    f.debug_struct("Migration")
+      .field("from", &self.from)
+      .field("to", &self.to)
+      .field("description", &self.description)
+      .finish_non_exhaustive()
+  }
+}
+
+/// Every migration, in schema order. An index at any other schema, whether
+/// older than the first one or written by an unreleased ord, has to be
+/// rebuilt.
+const MIGRATIONS: &[Migration] = &[Migration {
+  from: 6,
+  to: 20,
+  description: "add the DRC-20 activity, state hash, balance history and holder tables, store \
+    inscription content by hash, record subsidies and AuxPoW headers, and move address outputs \
+    into tables that can be paged with range scans",
+  run: migrate_from_schema_6,
+}];
+
+/// Returns the migrations that take an index at `schema_version` to
+/// `SCHEMA_VERSION`.
+pub(crate) fn plan(schema_version: u64) -> Result<&'static [Migration]> {
+  ensure!(
+    schema_version <= SCHEMA_VERSION,
+    "index schema {schema_version} is newer than ord schema {SCHEMA_VERSION}, consider updating ord"
+  );
+
+  if schema_version == SCHEMA_VERSION {
+    return Ok(&[]);
+  }
+
+  let start = MIGRATIONS
+    .iter()
+    .position(|migration| migration.from == schema_version)
+    .ok_or_else(|| {
+      anyhow!(
+        "index schema {schema_version} is too old to migrate, consider deleting and rebuilding the index"
+      )
+    })?;
+
+  Ok(&MIGRATIONS[start..])
+}
+
+pub(crate) fn schema_version(database: &Database) -> Result<u64> {
+  Ok(
+    database
+      .begin_read()?
+      .open_table(STATISTIC_TO_COUNT)?
+      .get(&Statistic::Schema.key())?
+      .map(|x| x.value())
+      .unwrap_or(0),
+  )
+}
+
+/// Runs every pending migration in a single write transaction, so an
+/// interrupted migration leaves the index at its original schema.
+pub(crate) fn migrate(database: &Database, chain: Chain) -> Result<&'static [Migration]> {
+  let migrations = plan(schema_version(database)?)?;
+
+  if migrations.is_empty() {
+    return Ok(migrations);
+  }
+
+  let mut wtx = database.begin_write()?;
+
+  if cfg!(test) {
+    wtx.set_durability(redb::Durability::None);
+  }
+
+  for migration in migrations {
+    log::info!(
+      "Migrating index schema {} to {}: {}",
+      migration.from,
+      migration.to,
+      migration.description
+    );
+
+    let progress_bar = if cfg!(test) || integration_test() {
+      ProgressBar::hidden()
+    } else {
+      let progress_bar = ProgressBar::new(0);
+      progress_bar.set_style(
+        ProgressStyle::with_template("[migrating to schema {msg}] {wide_bar} {pos}/{len}")
+          .unwrap(),
+      );
+      progress_bar
+    };
+
+    progress_bar.set_message(migration.to.to_string());
+
+    (migration.run)(&wtx, chain, &progress_bar)?;
+
+    wtx
+      .open_table(STATISTIC_TO_COUNT)?
+      .insert(&Statistic::Schema.key(), &migration.to)?;
+
+    progress_bar.finish_and_clear();
+  }
+
+  wtx.commit()?;
+
+  Ok(migrations)
+}
+
+/// Schema 6 is the last one a released ord wrote. Tables added since then
+/// that only record blocks from the next one on start out empty.
+fn migrate_from_schema_6(
+  wtx: &WriteTransaction,
+  _chain: Chain,
+  progress_bar: &ProgressBar,
+) -> Result {
+  reject_sat_indexes(wtx)?;
+  reject_drc20_indexes(wtx)?;
+
+  wtx.open_table(DRC20_TICK_ACTIVITY)?;
+  wtx.open_table(DRC20_HEIGHT_TO_STATE_HASH)?;
+  wtx.open_table(DRC20_BALANCE_HISTORY)?;
+  wtx.open_table(DRC20_TICK_HOLDER)?;
+  wtx.open_table(DRC20_TICK_TO_HOLDER_COUNT)?;
+  wtx.open_table(DRC20_TICK_BALANCE_HOLDER)?;
+  wtx.delete_multimap_table(DRC20_TOKEN_HOLDER)?;
+  wtx.open_table(HEIGHT_TO_SUBSIDY)?;
+  wtx.open_table(HEIGHT_TO_AUXPOW)?;
+
+  backfill_inscription_content(wtx, progress_bar)?;
+  move_address_outputs(wtx, progress_bar)?;
+
+  Ok(())
+}
+
+/// Sats used to be numbered by a schedule that drifts from the subsidies
+/// dogecoin paid out from epoch 1183 on, and their ranges were stored in an
+/// encoding that can't hold dogecoin's supply, so a sat index can't be
+/// migrated and has to be rebuilt.
+fn reject_sat_indexes(wtx: &WriteTransaction) -> Result {
+  ensure!(
+    wtx.open_table(OUTPOINT_TO_SAT_RANGES)?.is_empty()?,
+    "sats are now numbered by the subsidies coinbases claim, delete the index and rebuild it with --index-sats"
+  );
+
+  Ok(())
+}
+
+/// Transfers spent as fee or sent to an unspendable output are now burned
+/// from genesis, so an index with DRC-20 tokens may have credited such
+/// transfers and has to be rebuilt.
+fn reject_drc20_indexes(wtx: &WriteTransaction) -> Result {
+  ensure!(
+    wtx.open_table(DRC20_TOKEN)?.is_empty()?,
+    "DRC-20 transfers spent as fee or sent to unspendable outputs are now burned, delete the index and rebuild it with --index-drc20"
+  );
+
+  Ok(())
+}
+
+const ADDRESS_TO_OUTPOINT: MultimapTableDefinition<&[u8], &OutPointValue> =
+  MultimapTableDefinition::new("ADDRESS_TO_OUTPOINT");
+
+const DRC20_TOKEN_HOLDER: MultimapTableDefinition<&str, &str> =
+  MultimapTableDefinition::new("DRC20_TOKEN_HOLDER");
+
+/// Multimap tables can only be iterated from the first value of a key, so
+/// paging through an address's outputs meant skipping every earlier one.
+/// Outputs move into `AddressTables`, which can be ranged from any output.
+/// Their heights aren't known, so they're recorded at height 0 and listed
+/// after every output created from the next block on. Spent outputs were only
+/// removed with `--index-transactions`, so outputs that are no longer in
+/// `OUTPOINT_TO_VALUE` are dropped.
+fn move_address_outputs(wtx: &WriteTransaction, progress_bar: &ProgressBar) -> Result {
+  {
+    let address_to_outpoint = wtx.open_multimap_table(ADDRESS_TO_OUTPOINT)?;
+    let outpoint_to_value = wtx.open_table(OUTPOINT_TO_VALUE)?;
+    let mut address_tables = AddressTables::open(wtx)?;
+
+    progress_bar.reset();
+    progress_bar.set_length(address_to_outpoint.len()?);
+
+    for entry in address_to_outpoint.iter()? {
+      let (address, outpoints) = entry?;
+
+      let address: [u8; 34] = address
+        .value()
+        .try_into()
+        .context("invalid address in ADDRESS_TO_OUTPOINT")?;
+
+      for outpoint in outpoints {
+        let outpoint = *outpoint?.value();
+
+        progress_bar.inc(1);
+
+        let Some(value) = outpoint_to_value.get(&outpoint)? else {
+          continue;
+        };
+
+        address_tables.insert(&address, OutPoint::load(outpoint), value.value(), 0)?;
+      }
+    }
+  }
+
+  wtx.delete_multimap_table(ADDRESS_TO_OUTPOINT)?;
+
+  Ok(())
+}
+
+/// Reassembles each inscription from its stored transactions, which is how
+/// content was served before it was stored directly.
+fn backfill_inscription_content(wtx: &WriteTransaction, progress_bar: &ProgressBar) -> Result {
+  let id_to_txids = wtx.open_table(INSCRIPTION_ID_TO_TXIDS)?;
+  let txid_to_tx = wtx.open_table(INSCRIPTION_TXID_TO_TX)?;
+  let mut id_to_content_hash = wtx.open_table(INSCRIPTION_ID_TO_CONTENT_HASH)?;
+  let mut content_hash_to_content = wtx.open_table(CONTENT_HASH_TO_INSCRIPTION_CONTENT)?;
+
+  progress_bar.reset();
+  progress_bar.set_length(id_to_txids.len()?);
+
+  for entry in id_to_txids.iter()? {
+    let (inscription_id, txids) = entry?;
+
+    progress_bar.inc(1);
+
+    let mut txs = Vec::new();
+
+    for txid in txids.value().chunks_exact(32) {
+      let Some(tx) = txid_to_tx.get(txid)? else {
+        break;
+      };
+
+      txs.push(consensus::deserialize::<Transaction>(tx.value())?);
+    }
+
+    if let ParsedInscription::Complete(inscription) = Inscription::from_transactions(txs) {
+      Index::insert_inscription_content(
+        &mut id_to_content_hash,
+        &mut content_hash_to_content,
+        inscription_id.value(),
+        &inscription,
+      )?;
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::index::testing::Context, redb::TableHandle};
+
+  /// Takes an index back to schema 6 by dropping every table added since and
+  /// adding the multimap tables that were replaced.
+  fn downgrade_to_schema_6(index: &Index) {
+    let wtx = index.begin_write().unwrap();
+    wtx.delete_table(DRC20_TICK_ACTIVITY).unwrap();
+    wtx.delete_table(DRC20_HEIGHT_TO_STATE_HASH).unwrap();
+    wtx.delete_table(DRC20_BALANCE_HISTORY).unwrap();
+    wtx.delete_table(DRC20_TICK_HOLDER).unwrap();
+    wtx.delete_table(DRC20_TICK_TO_HOLDER_COUNT).unwrap();
+    wtx.delete_table(DRC20_TICK_BALANCE_HOLDER).unwrap();
+    wtx.delete_table(HEIGHT_TO_SUBSIDY).unwrap();
+    wtx.delete_table(HEIGHT_TO_AUXPOW).unwrap();
+    wtx.delete_table(INSCRIPTION_ID_TO_CONTENT_HASH).unwrap();
+    wtx
+      .delete_table(CONTENT_HASH_TO_INSCRIPTION_CONTENT)
+      .unwrap();
+    wtx.delete_table(ADDRESS_OUTPOINT_TO_OUTPUT).unwrap();
+    wtx.delete_table(ADDRESS_HEIGHT_OUTPOINT_TO_VALUE).unwrap();
+    wtx.delete_table(ADDRESS_TO_BALANCE).unwrap();
+    wtx.delete_table(OUTPOINT_TO_ADDRESS).unwrap();
+    wtx.open_multimap_table(ADDRESS_TO_OUTPOINT).unwrap();
+    wtx.open_multimap_table(DRC20_TOKEN_HOLDER).unwrap();
+    wtx
+      .open_table(STATISTIC_TO_COUNT)
+      .unwrap()
+      .insert(&Statistic::Schema.key(), &6)
+      .unwrap();
+    wtx.commit().unwrap();
+  }
+
+  fn migrate_to_current_schema(context: &Context) {
+    assert_eq!(
+      migrate(&context.index.database, context.options.chain())
+        .unwrap()
+        .len(),
+      1
+    );
+
+    assert_eq!(
+      schema_version(&context.index.database).unwrap(),
+      SCHEMA_VERSION
+    );
+  }
+
+  #[test]
+  fn current_schema_needs_no_migrations() {
+    assert!(plan(SCHEMA_VERSION).unwrap().is_empty());
+  }
+
+  #[test]
+  fn migrations_are_contiguous() {
+    assert_eq!(
+      MIGRATIONS.last().unwrap().to,
+      SCHEMA_VERSION,
+      "SCHEMA_VERSION was bumped without adding a migration"
+    );
+
+    for migration in MIGRATIONS {
+      assert!(migration.from < migration.to);
+    }
+
+    for pair in MIGRATIONS.windows(2) {
+      assert_eq!(pair[0].to, pair[1].from);
+    }
+  }
+
+  #[test]
+  fn unknown_schemas_are_rejected() {
+    for schema_version in [5, 7, SCHEMA_VERSION - 1] {
+      assert_eq!(
+        plan(schema_version).unwrap_err().to_string(),
+        format!(
+          "index schema {schema_version} is too old to migrate, consider deleting and rebuilding the index"
+        )
+      );
+    }
+
+    assert_eq!(
+      plan(SCHEMA_VERSION + 1).unwrap_err().to_string(),
+      format!(
+        "index schema {} is newer than ord schema {SCHEMA_VERSION}, consider updating ord",
+        SCHEMA_VERSION + 1
+      )
+    );
+  }
+
+  #[test]
+  fn new_tables_are_added() {
+    let context = Context::builder().build();
+
+    downgrade_to_schema_6(&context.index);
+
+    migrate_to_current_schema(&context);
+
+    let tables = context
+      .index
+      .database
+      .begin_read()
+      .unwrap()
+      .list_tables()
+      .unwrap()
+      .map(|table| table.name().to_string())
+      .collect::<Vec<String>>();
+
+    for table in [
+      "DRC20_TICK_ACTIVITY",
+      "DRC20_HEIGHT_TO_STATE_HASH",
+      "DRC20_BALANCE_HISTORY",
+      "DRC20_TICK_HOLDER",
+      "DRC20_TICK_TO_HOLDER_COUNT",
+      "DRC20_TICK_BALANCE_HOLDER",
+      "HEIGHT_TO_SUBSIDY",
+      "HEIGHT_TO_AUXPOW",
+    ] {
+      assert!(tables.contains(&table.to_string()), "{table}");
+    }
+  }
+
+  #[test]
+  fn inscription_content_is_backfilled() {
+    let context = Context::builder().build();
+    context.mine_blocks(1);
+
+    let txid = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      script_sig: inscription("text/plain", "hello").to_script_sig(),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    downgrade_to_schema_6(&context.index);
+
+    migrate_to_current_schema(&context);
+
+    assert_eq!(
+      context.index.get_inscription_by_id(txid.into()).unwrap(),
+      Some(inscription("text/plain", "hello"))
+    );
+  }
+
+  #[test]
+  fn drc20_indexes_are_rejected() {
+    let context = Context::builder().arg("--index-drc20").build();
+
+    downgrade_to_schema_6(&context.index);
+
+    let wtx = context.index.begin_write().unwrap();
+    wtx
+      .open_table(DRC20_TOKEN)
+      .unwrap()
+      .insert("646f6765", b"".as_slice())
+      .unwrap();
+    wtx.commit().unwrap();
+
+    assert!(migrate(&context.index.database, context.options.chain())
+      .unwrap_err()
+      .to_string()
+      .contains("rebuild it with --index-drc20"));
+
+    assert_eq!(schema_version(&context.index.database).unwrap(), 6);
+  }
+
+  #[test]
+  fn sat_indexes_are_rejected() {
+    let context = Context::builder().arg("--index-sats").build();
+
+    downgrade_to_schema_6(&context.index);
+
+    assert!(migrate(&context.index.database, context.options.chain())
+      .unwrap_err()
+      .to_string()
+      .contains("rebuild it with --index-sats"));
+
+    assert_eq!(schema_version(&context.index.database).unwrap(), 6);
+  }
+
+  #[test]
+  fn address_outputs_are_moved_to_range_scannable_tables() {
+    let context = Context::builder().build();
+
+    let address = [b'D'; 34];
+
+    downgrade_to_schema_6(&context.index);
+
+    let wtx = context.index.begin_write().unwrap();
+    {
+      let mut address_to_outpoint = wtx.open_multimap_table(ADDRESS_TO_OUTPOINT).unwrap();
+      address_to_outpoint
+        .insert(address.as_slice(), &[1; 36])
+        .unwrap();
+      address_to_outpoint
+        .insert(address.as_slice(), &[2; 36])
+        .unwrap();
+
+      wtx
+        .open_table(OUTPOINT_TO_VALUE)
+        .unwrap()
+        .insert(&[1; 36], 5000)
+        .unwrap();
+    }
+    wtx.commit().unwrap();
+
+    migrate_to_current_schema(&context);
+
+    let address_str = std::str::from_utf8(&address).unwrap();
+
+    assert_eq!(
+      context.index.get_address_balance(address_str).unwrap(),
+      (1, 5000)
+    );
+
+    assert_eq!(
+      context
+        .index
+        .get_address_recent_outputs(address_str, 10)
+        .unwrap(),
+      [(OutPoint::load([1; 36]), 5000, 0)]
+    );
+
+    let rtx = context.index.database.begin_read().unwrap();
+    let outpoint_to_address = rtx.open_table(OUTPOINT_TO_ADDRESS).unwrap();
+
+    assert_eq!(
+      *outpoint_to_address.get(&[1; 36]).unwrap().unwrap().value(),
+      address
+    );
+    assert!(outpoint_to_address.get(&[2; 36]).unwrap().is_none());
+
+    assert!(rtx.list_multimap_tables().unwrap().next().is_none());
+  }
+
+  #[test]
+  fn failed_migration_leaves_index_untouched() {
+    let context = Context::builder().build();
+
+    downgrade_to_schema_6(&context.index);
+
+    let wtx = context.index.begin_write().unwrap();
+    wtx
+      .open_table(INSCRIPTION_ID_TO_TXIDS)
+      .unwrap()
+      .insert(&[0; 36], [0; 32].as_slice())
+      .unwrap();
+    wtx
+      .open_table(INSCRIPTION_TXID_TO_TX)
+      .unwrap()
+      .insert([0; 32].as_slice(), [0xff].as_slice())
+      .unwrap();
+    wtx.commit().unwrap();
+
+    assert!(migrate(&context.index.database, context.options.chain()).is_err());
+
+    assert_eq!(schema_version(&context.index.database).unwrap(), 6);
+
+    assert!(!context
+      .index
+      .database
+      .begin_read()
+      .unwrap()
+      .list_tables()
+      .unwrap()
+      .any(|table| table.name() == "DRC20_TICK_ACTIVITY"));
+  }
+}
@@ -40,9 +40,8 @@ impl Reorg {
         for depth in 1..max_recoverable_reorg_depth {
           let index_block_hash = index.block_hash(height.checked_sub(depth))?;
           let bitcoind_block_hash = index
-            .client
-            .get_block_hash(u64::from(height.saturating_sub(depth)))
-            .into_option()?;
+            .block_source
+            .block_hash(height.saturating_sub(depth))?;
 
           if index_block_hash == bitcoind_block_hash {
             return Err(anyhow!(ReorgError::Recoverable { height, depth }));
@@ -77,12 +76,9 @@ impl Reorg {
 
   pub(crate) fn update_savepoints(index: &Index, height: u32) -> Result {
     if (height < SAVEPOINT_INTERVAL || height % SAVEPOINT_INTERVAL == 0)
-      && u32::try_from(
-      index
-          .client
-          .get_block_count()?
-        )
-        .unwrap()
+      && index
+        .block_source
+        .tip_height()?
         .saturating_sub(height)
       <= CHAIN_TIP_DISTANCE
     {
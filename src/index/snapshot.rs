@@ -0,0 +1,566 @@
+use {
+  super::*,
+  bitcoin::hashes::{sha256, HashEngine},
+  flate2::{read::GzDecoder, write::GzEncoder, Compression},
+  redb::{ReadTransaction, RedbKey, RedbValue, TableError, TableHandle},
+  std::io::{Read, Write},
+};
+
+const MAGIC: &[u8; 8] = b"ordsnap\0";
+
+/// Bumped whenever the framing below changes. Changes to the tables
+/// themselves are covered by `SCHEMA_VERSION`.
+const FORMAT_VERSION: u32 = 1;
+
+const END: u8 = 0;
+const TABLE: u8 = 1;
+const ENTRY: u8 = 1;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Header {
+  pub(crate) format_version: u32,
+  pub(crate) schema_version: u64,
+  pub(crate) chain: Chain,
+  pub(crate) height: u32,
+  pub(crate) block_hash: BlockHash,
+}
+
+trait Visitor {
+  fn table<K: RedbKey + 'static, V: RedbValue + 'static>(
+    &mut self,
+    definition: TableDefinition<K, V>,
+  ) -> Result;
+}
+
+/// Every table in the index, in the order they appear in a snapshot.
+fn visit_tables(visitor: &mut impl Visitor) -> Result {
+  visitor.table(HEIGHT_TO_BLOCK_HASH)?;
+  visitor.table(HEIGHT_TO_SUBSIDY)?;
+  visitor.table(HEIGHT_TO_AUXPOW)?;
+  visitor.table(INSCRIPTION_ID_TO_INSCRIPTION_ENTRY)?;
+  visitor.table(INSCRIPTION_ID_TO_DUNE)?;
+  visitor.table(INSCRIPTION_ID_TO_SATPOINT)?;
+  visitor.table(INSCRIPTION_NUMBER_TO_INSCRIPTION_ID)?;
+  visitor.table(OUTPOINT_TO_DUNE_BALANCES)?;
+  visitor.table(INSCRIPTION_ID_TO_TXIDS)?;
+  visitor.table(INSCRIPTION_TXID_TO_TX)?;
+  visitor.table(INSCRIPTION_ID_TO_CONTENT_HASH)?;
+  visitor.table(CONTENT_HASH_TO_INSCRIPTION_CONTENT)?;
+  visitor.table(PARTIAL_TXID_TO_INSCRIPTION_TXIDS)?;
+  visitor.table(OUTPOINT_TO_SAT_RANGES)?;
+  visitor.table(OUTPOINT_TO_VALUE)?;
+  visitor.table(ADDRESS_OUTPOINT_TO_OUTPUT)?;
+  visitor.table(ADDRESS_HEIGHT_OUTPOINT_TO_VALUE)?;
+  visitor.table(ADDRESS_TO_BALANCE)?;
+  visitor.table(OUTPOINT_TO_ADDRESS)?;
+  visitor.table(DUNE_ID_TO_DUNE_ENTRY)?;
+  visitor.table(DUNE_TO_DUNE_ID)?;
+  visitor.table(SATPOINT_TO_INSCRIPTION_ID)?;
+  visitor.table(SAT_TO_INSCRIPTION_ID)?;
+  visitor.table(SAT_TO_SATPOINT)?;
+  visitor.table(STATISTIC_TO_COUNT)?;
+  visitor.table(TRANSACTION_ID_TO_DUNE)?;
+  visitor.table(TRANSACTION_ID_TO_TRANSACTION)?;
+  visitor.table(WRITE_TRANSACTION_STARTING_BLOCK_COUNT_TO_TIMESTAMP)?;
+  visitor.table(DRC20_BALANCES)?;
+  visitor.table(DRC20_TOKEN)?;
+  visitor.table(DRC20_INSCRIBE_TRANSFER)?;
+  visitor.table(DRC20_TRANSFERABLELOG)?;
+  visitor.table(DRC20_TICK_HOLDER)?;
+  visitor.table(DRC20_TICK_TO_HOLDER_COUNT)?;
+  visitor.table(DRC20_TICK_BALANCE_HOLDER)?;
+  visitor.table(DRC20_TICK_ACTIVITY)?;
+  visitor.table(DRC20_HEIGHT_TO_STATE_HASH)?;
+  visitor.table(DRC20_BALANCE_HISTORY)?;
+  Ok(())
+}
+
+struct Exporter<'a, 'db, W> {
+  rtx: &'a ReadTransaction<'db>,
+  writer: W,
+}
+
+impl<W: Write> Exporter<'_, '_, W> {
+  fn start(&mut self, kind: u8, name: &str) -> Result {
+    self.writer.write_all(&[kind])?;
+    write_bytes(&mut self.writer, name.as_bytes())?;
+    Ok(())
+  }
+
+  fn entry(&mut self, checksum: &mut sha256::HashEngine, key: &[u8], value: &[u8]) -> Result {
+    self.writer.write_all(&[ENTRY])?;
+
+    for bytes in [key, value] {
+      let len = u32::try_from(bytes.len())?.to_le_bytes();
+      checksum.input(&len);
+      checksum.input(bytes);
+      write_bytes(&mut self.writer, bytes)?;
+    }
+
+    Ok(())
+  }
+
+  fn finish(&mut self, checksum: sha256::HashEngine) -> Result {
+    self.writer.write_all(&[END])?;
+    self
+      .writer
+      .write_all(&sha256::Hash::from_engine(checksum).into_inner())?;
+    Ok(())
+  }
+}
+
+impl<W: Write> Visitor for Exporter<'_, '_, W> {
+  fn table<K: RedbKey + 'static, V: RedbValue + 'static>(
+    &mut self,
+    definition: TableDefinition<K, V>,
+  ) -> Result {
+    let table = match self.rtx.open_table(definition) {
+      Ok(table) => table,
+      Err(TableError::TableDoesNotExist(_)) => return Ok(()),
+      Err(err) => return Err(err.into()),
+    };
+
+    self.start(TABLE, definition.name())?;
+
+    let mut checksum = sha256::HashEngine::default();
+
+    for entry in table.iter()? {
+      let (key, value) = entry?;
+      self.entry(
+        &mut checksum,
+        K::as_bytes(&key.value()).as_ref(),
+        V::as_bytes(&value.value()).as_ref(),
+      )?;
+    }
+
+    self.finish(checksum)
+  }
+}
+
+struct Importer<'a, 'db, R> {
+  entries: u64,
+  next: Option<(u8, String)>,
+  reader: R,
+  wtx: &'a WriteTransaction<'db>,
+}
+
+impl<R: Read> Importer<'_, '_, R> {
+  fn read_start(&mut self) -> Result<Option<(u8, String)>> {
+    match read_u8(&mut self.reader)? {
+      END => Ok(None),
+      TABLE => Ok(Some((
+        TABLE,
+        String::from_utf8(read_bytes(&mut self.reader)?).context("invalid table name")?,
+      ))),
+      kind => bail!("invalid table kind {kind} in snapshot"),
+    }
+  }
+
+  /// Consumes the next table in the snapshot if it is `name`. Tables that
+  /// didn't exist in the exported index are absent from the snapshot.
+  fn take(&mut self, kind: u8, name: &str) -> bool {
+    if self.next.as_ref() == Some(&(kind, name.into())) {
+      self.next = None;
+      true
+    } else {
+      false
+    }
+  }
+
+  fn entries(&mut self, name: &str, mut insert: impl FnMut(&[u8], &[u8]) -> Result) -> Result {
+    let mut checksum = sha256::HashEngine::default();
+
+    loop {
+      match read_u8(&mut self.reader)? {
+        END => break,
+        ENTRY => {}
+        tag => bail!("invalid entry tag {tag} in snapshot table `{name}`"),
+      }
+
+      let key = read_bytes(&mut self.reader)?;
+      let value = read_bytes(&mut self.reader)?;
+
+      for bytes in [&key, &value] {
+        checksum.input(&u32::try_from(bytes.len())?.to_le_bytes());
+        checksum.input(bytes);
+      }
+
+      insert(&key, &value)?;
+
+      self.entries += 1;
+    }
+
+    let mut expected = [0; 32];
+    self.reader.read_exact(&mut expected)?;
+
+    ensure!(
+      sha256::Hash::from_engine(checksum).into_inner() == expected,
+      "checksum mismatch in snapshot table `{name}`"
+    );
+
+    self.next = self.read_start()?;
+
+    Ok(())
+  }
+}
+
+impl<R: Read> Visitor for Importer<'_, '_, R> {
+  fn table<K: RedbKey + 'static, V: RedbValue + 'static>(
+    &mut self,
+    definition: TableDefinition<K, V>,
+  ) -> Result {
+    let wtx = self.wtx;
+
+    wtx.delete_table(definition)?;
+
+    if !self.take(TABLE, definition.name()) {
+      return Ok(());
+    }
+
+    let mut table = wtx.open_table(definition)?;
+
+    self.entries(definition.name(), |key, value| {
+      table.insert(K::from_bytes(key), V::from_bytes(value))?;
+      Ok(())
+    })
+  }
+}
+
+fn write_bytes(writer: &mut impl Write, bytes: &[u8]) -> Result {
+  writer.write_all(&u32::try_from(bytes.len())?.to_le_bytes())?;
+  writer.write_all(bytes)?;
+  Ok(())
+}
+
+fn read_u8(reader: &mut impl Read) -> Result<u8> {
+  let mut byte = [0];
+  reader.read_exact(&mut byte)?;
+  Ok(byte[0])
+}
+
+fn read_bytes(reader: &mut impl Read) -> Result<Vec<u8>> {
+  let mut len = [0; 4];
+  reader.read_exact(&mut len)?;
+  let mut bytes = vec![0; u32::from_le_bytes(len).try_into()?];
+  reader.read_exact(&mut bytes)?;
+  Ok(bytes)
+}
+
+impl Index {
+  /// Writes a gzipped snapshot of every table as of the current tip.
+  pub(crate) fn export_snapshot(&self, writer: impl Write) -> Result<Header> {
+    let rtx = self.begin_read()?;
+
+    let height = rtx
+      .height()?
+      .ok_or_else(|| anyhow!("cannot export an empty index"))?
+      .n();
+
+    let header = Header {
+      format_version: FORMAT_VERSION,
+      schema_version: SCHEMA_VERSION,
+      chain: self.chain,
+      height,
+      block_hash: rtx.block_hash(Some(height))?.unwrap(),
+    };
+
+    let mut encoder = GzEncoder::new(writer, Compression::default());
+
+    encoder.write_all(MAGIC)?;
+    bincode::serialize_into(&mut encoder, &header)?;
+
+    let mut exporter = Exporter {
+      rtx: &rtx.0,
+      writer: encoder,
+    };
+
+    visit_tables(&mut exporter)?;
+
+    exporter.writer.write_all(&[END])?;
+    exporter.writer.finish()?.flush()?;
+
+    Ok(header)
+  }
+
+  /// Replaces the contents of an empty index with a snapshot, returning the
+  /// snapshot's header and the number of entries imported. Nothing is
+  /// committed unless every table checksum matches and the snapshot's block is
+  /// still on the main chain, so `update` can carry on from there.
+  pub(crate) fn import_snapshot(&self, reader: impl Read) -> Result<(Header, u64)> {
+    ensure!(
+      self.block_count()? == 0,
+      "snapshots can only be imported into an empty index"
+    );
+
+    let mut reader = GzDecoder::new(reader);
+
+    let mut magic = [0; 8];
+    reader
+      .read_exact(&mut magic)
+      .context("failed to read snapshot")?;
+
+    ensure!(&magic == MAGIC, "file is not an ord index snapshot");
+
+    let header: Header = bincode::deserialize_from(&mut reader)?;
+
+    ensure!(
+      header.format_version == FORMAT_VERSION,
+      "unsupported snapshot format version {}, expected {FORMAT_VERSION}",
+      header.format_version
+    );
+
+    ensure!(
+      header.schema_version == SCHEMA_VERSION,
+      "snapshot was exported from index schema {}, ord schema {SCHEMA_VERSION}",
+      header.schema_version
+    );
+
+    ensure!(
+      header.chain == self.chain,
+      "snapshot is for {}, not {}",
+      header.chain,
+      self.chain
+    );
+
+    let wtx = self.begin_write()?;
+
+    let mut importer = Importer {
+      entries: 0,
+      next: None,
+      reader,
+      wtx: &wtx,
+    };
+
+    importer.next = importer.read_start()?;
+
+    visit_tables(&mut importer)?;
+
+    if let Some((_, name)) = importer.next {
+      bail!("snapshot contains unknown table `{name}`");
+    }
+
+    let entries = importer.entries;
+
+    let main_chain_hash = self.block_source.block_hash(header.height)?;
+
+    ensure!(
+      main_chain_hash == Some(header.block_hash),
+      "snapshot block {} at height {} is no longer on the main chain",
+      header.block_hash,
+      header.height
+    );
+
+    wtx.commit()?;
+
+    Ok((header, entries))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::index::testing::Context, redb::MultimapTableHandle};
+
+  fn inscribed_context() -> Context {
+    let context = Context::builder().arg("--index-sats").build();
+    context.mine_blocks(1);
+
+    context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      script_sig: inscription("text/plain", "hello").to_script_sig(),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    context
+  }
+
+  fn empty_index(context: &Context) -> (TempDir, Index) {
+    let tempdir = TempDir::new().unwrap();
+
+    let options = Options {
+      index: Some(tempdir.path().join("index.redb")),
+      ..context.options.clone()
+    };
+
+    let index = Index::open(&options).unwrap();
+
+    (tempdir, index)
+  }
+
+  fn tables(index: &Index) -> Vec<u8> {
+    let rtx = index.database.begin_read().unwrap();
+
+    let mut exporter = Exporter {
+      rtx: &rtx,
+      writer: Vec::new(),
+    };
+
+    visit_tables(&mut exporter).unwrap();
+
+    exporter.writer
+  }
+
+  fn recompress(snapshot: &[u8], f: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    GzDecoder::new(snapshot).read_to_end(&mut bytes).unwrap();
+
+    f(&mut bytes);
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&bytes).unwrap();
+    encoder.finish().unwrap()
+  }
+
+  #[test]
+  fn snapshot_round_trips() {
+    let context = inscribed_context();
+
+    let mut snapshot = Vec::new();
+    let header = context.index.export_snapshot(&mut snapshot).unwrap();
+
+    assert_eq!(header.height, 2);
+    assert_eq!(header.block_hash, context.index.block_hash(None).unwrap().unwrap());
+
+    let (_tempdir, index) = empty_index(&context);
+
+    let (imported, entries) = index.import_snapshot(snapshot.as_slice()).unwrap();
+
+    assert_eq!(imported, header);
+    assert!(entries > 0);
+    assert_eq!(tables(&index), tables(&context.index));
+
+    index.update().unwrap();
+
+    assert_eq!(index.block_count().unwrap(), 3);
+    assert_eq!(
+      index
+        .get_inscription_by_id(InscriptionId::from(context.rpc_server.tx(2, 1).txid()))
+        .unwrap()
+        .unwrap()
+        .body(),
+      Some("hello".as_bytes())
+    );
+  }
+
+  #[test]
+  fn every_table_is_exported() {
+    struct Names(Vec<String>);
+
+    impl Visitor for Names {
+      fn table<K: RedbKey + 'static, V: RedbValue + 'static>(
+        &mut self,
+        definition: TableDefinition<K, V>,
+      ) -> Result {
+        self.0.push(definition.name().into());
+        Ok(())
+      }
+    }
+
+    let context = Context::builder()
+      .args(["--index-sats", "--index-dunes", "--index-transactions"])
+      .build();
+
+    context.mine_blocks(1);
+
+    let mut names = Names(Vec::new());
+    visit_tables(&mut names).unwrap();
+
+    let rtx = context.index.database.begin_read().unwrap();
+
+    for table in rtx.list_tables().unwrap() {
+      assert!(names.0.contains(&table.name().to_string()), "{}", table.name());
+    }
+
+    for table in rtx.list_multimap_tables().unwrap() {
+      assert!(names.0.contains(&table.name().to_string()), "{}", table.name());
+    }
+  }
+
+  #[test]
+  fn corrupted_snapshot_is_rejected() {
+    let context = inscribed_context();
+
+    let mut snapshot = Vec::new();
+    context.index.export_snapshot(&mut snapshot).unwrap();
+
+    // The last table's checksum sits just before the trailing end marker.
+    let snapshot = recompress(&snapshot, |bytes| {
+      let i = bytes.len() - 2;
+      bytes[i] ^= 1;
+    });
+
+    let (_tempdir, index) = empty_index(&context);
+
+    assert_regex_match!(
+      index
+        .import_snapshot(snapshot.as_slice())
+        .unwrap_err()
+        .to_string(),
+      "checksum mismatch in snapshot table `.*`"
+    );
+
+    assert_eq!(index.block_count().unwrap(), 0);
+  }
+
+  #[test]
+  fn snapshot_of_reorged_block_is_rejected() {
+    let context = inscribed_context();
+
+    let mut snapshot = Vec::new();
+    let header = context.index.export_snapshot(&mut snapshot).unwrap();
+
+    context.rpc_server.invalidate_tip();
+
+    let (_tempdir, index) = empty_index(&context);
+
+    assert_eq!(
+      index
+        .import_snapshot(snapshot.as_slice())
+        .unwrap_err()
+        .to_string(),
+      format!(
+        "snapshot block {} at height 2 is no longer on the main chain",
+        header.block_hash
+      )
+    );
+
+    assert_eq!(index.block_count().unwrap(), 0);
+  }
+
+  #[test]
+  fn snapshot_from_other_chain_is_rejected() {
+    let context = inscribed_context();
+
+    let mut snapshot = Vec::new();
+    context.index.export_snapshot(&mut snapshot).unwrap();
+
+    let other = Context::builder().chain(Chain::Testnet).build();
+    let (_tempdir, index) = empty_index(&other);
+
+    assert_eq!(
+      index
+        .import_snapshot(snapshot.as_slice())
+        .unwrap_err()
+        .to_string(),
+      "snapshot is for regtest, not testnet"
+    );
+  }
+
+  #[test]
+  fn only_empty_indices_accept_snapshots() {
+    let context = inscribed_context();
+
+    let mut snapshot = Vec::new();
+    context.index.export_snapshot(&mut snapshot).unwrap();
+
+    assert_eq!(
+      context
+        .index
+        .import_snapshot(snapshot.as_slice())
+        .unwrap_err()
+        .to_string(),
+      "snapshots can only be imported into an empty index"
+    );
+  }
+}
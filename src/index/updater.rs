@@ -2,30 +2,46 @@ use bitcoincore_rpc::bitcoin::BlockHeader;
 
 use {
   self::{dune_updater::DuneUpdater, inscription_updater::InscriptionUpdater},
-  futures::future::try_join_all,
-  std::sync::mpsc,
-  super::{*, fetcher::Fetcher},
+  std::{panic, sync::mpsc},
+  super::{
+    *,
+    blk::{BlkFiles, BLK_FILES_TIP_DISTANCE},
+    block_source::BlockSource,
+  },
   tokio::sync::mpsc::{error::TryRecvError, Receiver, Sender},
 };
 
+use crate::auxpow::AuxPow;
 use crate::drc20::BlockContext;
 use crate::index::updater::drc20_updater::Drc20Updater;
 use crate::sat::Sat;
 use crate::sat_point::SatPoint;
 
+pub(super) mod backfill;
 mod drc20_updater;
+pub(super) mod overlay;
+pub(super) mod pending;
+pub(super) mod simulate;
 mod dune_updater;
 mod inscription_updater;
 
 pub(crate) struct BlockData {
   pub(crate) header: BlockHeader,
+  pub(crate) auxpow: Option<AuxPow>,
   pub(crate) txdata: Vec<(Transaction, Txid)>,
 }
 
 impl From<Block> for BlockData {
   fn from(block: Block) -> Self {
+    (block, None).into()
+  }
+}
+
+impl From<(Block, Option<AuxPow>)> for BlockData {
+  fn from((block, auxpow): (Block, Option<AuxPow>)) -> Self {
     BlockData {
       header: block.header,
+      auxpow,
       txdata: block
         .txdata
         .into_iter()
@@ -47,24 +63,30 @@ pub(crate) struct Updater<'index> {
   outputs_cached: u64,
   outputs_inserted_since_flush: u64,
   outputs_traversed: u64,
+  schedule: Schedule,
 }
 
 impl<'index> Updater<'_> {
   pub(crate) fn new(index: &'index Index) -> Result<Updater<'index>> {
+    // reload the schedule, since a reorg may have rolled back recorded subsidies
+    let schedule = Index::load_schedule(&index.database, index.chain)?;
+    *index.schedule.lock().unwrap() = Arc::new(schedule.clone());
+
     Ok(Updater {
       range_cache: HashMap::new(),
-      height: index.block_count()?,
+      height: index.block_count()?.max(index.block_source.start_height()),
       index,
       sat_ranges_since_flush: 0,
       outputs_cached: 0,
       outputs_inserted_since_flush: 0,
       outputs_traversed: 0,
+      schedule,
     })
   }
 
   pub(crate) fn update_index(&mut self) -> Result {
     let mut wtx = self.index.begin_write()?;
-    let starting_height = u32::try_from(self.index.client.get_block_count()?).unwrap() + 1;
+    let starting_height = self.index.block_source.tip_height()? + 1;
 
     wtx
       .open_table(WRITE_TRANSACTION_STARTING_BLOCK_COUNT_TO_TIMESTAMP)?
@@ -86,12 +108,12 @@ impl<'index> Updater<'_> {
       let progress_bar = ProgressBar::new(starting_height.into());
       progress_bar.set_position(self.height.into());
       progress_bar.set_style(
-        ProgressStyle::with_template("[indexing blocks] {wide_bar} {pos}/{len}").unwrap(),
+        ProgressStyle::with_template("[indexing blocks] {wide_bar} {pos}/{len} {msg}").unwrap(),
       );
       Some(progress_bar)
     };
 
-    let rx = Self::fetch_blocks_from(self.index, self.height, self.index.index_sats)?;
+    let rx = Self::fetch_blocks_from(self.index, self.height)?;
 
     let (mut outpoint_sender, mut value_receiver) = Self::spawn_fetcher(self.index)?;
 
@@ -107,12 +129,15 @@ impl<'index> Updater<'_> {
         &mut value_cache,
       )?;
 
+      let cached_outputs = value_cache.len() + self.range_cache.len();
+
       if let Some(progress_bar) = &mut progress_bar {
         progress_bar.inc(1);
+        progress_bar.set_message(format!("{cached_outputs} outputs cached"));
 
         if progress_bar.position() > progress_bar.length().unwrap() {
-          if let Ok(count) = self.index.client.get_block_count() {
-            progress_bar.set_length(count + 1);
+          if let Ok(height) = self.index.block_source.tip_height() {
+            progress_bar.set_length(u64::from(height) + 1);
           } else {
             log::warn!("Failed to fetch latest block height");
           }
@@ -121,7 +146,11 @@ impl<'index> Updater<'_> {
 
       uncommitted += 1;
 
-      if uncommitted == 1000 {
+      // Busy blocks fill the caches long before the interval is up, so commit
+      // on whichever limit is reached first.
+      if uncommitted >= self.index.commit_interval
+        || cached_outputs >= self.index.commit_cache_size
+      {
         self.commit(wtx, value_cache)?;
         value_cache = HashMap::new();
         uncommitted = 0;
@@ -165,39 +194,84 @@ impl<'index> Updater<'_> {
     Ok(())
   }
 
-  fn fetch_blocks_from(
-    index: &Index,
-    mut height: u32,
-    index_sats: bool,
-  ) -> Result<mpsc::Receiver<BlockData>> {
+  fn fetch_blocks_from(index: &Index, mut height: u32) -> Result<mpsc::Receiver<BlockData>> {
     let (tx, rx) = mpsc::sync_channel(32);
 
     let height_limit = index.height_limit;
 
-    let client =
-      Client::new(&index.rpc_url, index.auth.clone()).context("failed to connect to RPC URL")?;
+    let block_source = index.block_source.clone();
 
-    let first_inscription_height = index.first_inscription_height;
+    let full_blocks_from = index.full_blocks_from();
 
-    thread::spawn(move || loop {
-      if let Some(height_limit) = height_limit {
-        if height >= height_limit {
-          break;
+    let blocks_dir = index.blocks_dir.clone();
+
+    let blk_files = index.blk_files.clone();
+
+    let chain = index.chain;
+
+    thread::spawn(move || {
+      if let Some(blocks_dir) = blocks_dir {
+        match block_source.tip_height().and_then(|tip_height| {
+          let end = tip_height
+            .saturating_sub(BLK_FILES_TIP_DISTANCE)
+            .min(height_limit.unwrap_or(u32::MAX));
+
+          // Near the tip every block comes from the block source, so the blk
+          // files aren't even scanned.
+          if height >= end {
+            return Ok(true);
+          }
+
+          {
+            let mut blk_files = blk_files.lock().unwrap();
+
+            match &mut *blk_files {
+              Some(blk_files) => blk_files.refresh()?,
+              None => *blk_files = Some(BlkFiles::open(&blocks_dir, chain.network_magic())?),
+            }
+          }
+
+          Self::fetch_blocks_from_blk_files(
+            &*block_source,
+            &blk_files,
+            &mut height,
+            end,
+            full_blocks_from,
+            &tx,
+          )
+        }) {
+          Ok(true) => log::info!("Switching from blk files to block source at block {height}"),
+          Ok(false) => return,
+          Err(err) => log::warn!(
+            "failed to read block {height} from blk files, switching to block source: {err}"
+          ),
         }
       }
 
-      match Self::get_block_with_retries(&client, height, index_sats, first_inscription_height) {
-        Ok(Some(block)) => {
-          if let Err(err) = tx.send(block.into()) {
-            log::info!("Block receiver disconnected: {err}");
+      loop {
+        if let Some(height_limit) = height_limit {
+          if height >= height_limit {
             break;
           }
-          height += 1;
         }
-        Ok(None) => break,
-        Err(err) => {
-          log::error!("failed to fetch block {height}: {err}");
-          break;
+
+        match Self::get_block_with_retries(
+          &*block_source,
+          height,
+          full_blocks_from,
+        ) {
+          Ok(Some(block)) => {
+            if let Err(err) = tx.send(block) {
+              log::info!("Block receiver disconnected: {err}");
+              break;
+            }
+            height += 1;
+          }
+          Ok(None) => break,
+          Err(err) => {
+            log::error!("failed to fetch block {height}: {err}");
+            break;
+          }
         }
       }
     });
@@ -205,31 +279,100 @@ impl<'index> Updater<'_> {
     Ok(rx)
   }
 
+  /// Sends main chain blocks read from the node's blk files until `end`,
+  /// returning `Ok(false)` if the receiver hung up. Every block is checked
+  /// against the hash `block_source` has at its height, so a stale fork in
+  /// the blk files is never indexed. Stops early when the next block isn't on
+  /// disk, e.g. because the node pruned it, leaving the rest to
+  /// `block_source`. `blk_files` is only locked while a block is read, not
+  /// while waiting on `block_source` or the receiver.
+  fn fetch_blocks_from_blk_files(
+    block_source: &dyn BlockSource,
+    blk_files: &Mutex<Option<BlkFiles>>,
+    height: &mut u32,
+    end: u32,
+    full_blocks_from: u32,
+    tx: &mpsc::SyncSender<BlockData>,
+  ) -> Result<bool> {
+    if *height >= end {
+      return Ok(true);
+    }
+
+    let mut prev = match height.checked_sub(1) {
+      Some(prev) => block_source
+        .block_hash(prev)?
+        .ok_or_else(|| anyhow!("block {prev} not found"))?,
+      None => BlockHash::all_zeros(),
+    };
+
+    while *height < end {
+      let Some(hash) = block_source.block_hash(*height)? else {
+        return Ok(true);
+      };
+
+      let (mut block, auxpow) = {
+        let blk_files = blk_files.lock().unwrap();
+        let blk_files = blk_files.as_ref().unwrap();
+
+        let Some(location) = blk_files
+          .children(&prev)
+          .iter()
+          .find(|location| location.hash == hash)
+        else {
+          return Ok(true);
+        };
+
+        blk_files.read(location)?
+      };
+
+      ensure!(
+        block.block_hash() == hash,
+        "block {height} in blk files has hash {} instead of {hash}",
+        block.block_hash(),
+      );
+
+      if *height < full_blocks_from {
+        block.txdata.clear();
+      }
+
+      let block = (block, auxpow);
+
+      if let Err(err) = tx.send(block.into()) {
+        log::info!("Block receiver disconnected: {err}");
+        return Ok(false);
+      }
+
+      prev = hash;
+      *height += 1;
+    }
+
+    Ok(true)
+  }
+
   fn get_block_with_retries(
-    client: &Client,
+    block_source: &dyn BlockSource,
     height: u32,
-    index_sats: bool,
-    first_inscription_height: u32,
-  ) -> Result<Option<Block>> {
+    full_blocks_from: u32,
+  ) -> Result<Option<BlockData>> {
     let mut errors = 0;
     loop {
-      match client
-        .get_block_hash(height.into())
-        .into_option()
-        .and_then(|option| {
-          option
-            .map(|hash| {
-              if index_sats || height >= first_inscription_height {
-                Ok(client.get_block(&hash)?)
-              } else {
-                Ok(Block {
-                  header: client.get_block_header(&hash)?,
-                  txdata: Vec::new(),
-                })
-              }
-            })
-            .transpose()
-        }) {
+      let result = if height >= full_blocks_from {
+        block_source
+          .block_with_auxpow(height)
+          .map(|block| block.map(BlockData::from))
+      } else {
+        block_source.block_header(height).map(|header| {
+          header.map(|header| {
+            Block {
+              header,
+              txdata: Vec::new(),
+            }
+            .into()
+          })
+        })
+      };
+
+      match result {
         Err(err) => {
           if cfg!(test) {
             return Err(err);
@@ -252,7 +395,7 @@ impl<'index> Updater<'_> {
   }
 
   fn spawn_fetcher(index: &Index) -> Result<(Sender<OutPoint>, Receiver<u64>)> {
-    let fetcher = Fetcher::new(&index.rpc_url, index.auth.clone())?;
+    let block_source = index.block_source.clone();
 
     // Not sure if any block has more than 20k inputs, but none so far after first inscription block
     const CHANNEL_BUFFER_SIZE: usize = 20_000;
@@ -263,58 +406,41 @@ impl<'index> Updater<'_> {
     // Batch 2048 missing inputs at a time. Arbitrarily chosen for now, maybe higher or lower can be faster?
     // Did rudimentary benchmarks with 1024 and 4096 and time was roughly the same.
     const BATCH_SIZE: usize = 2048 * 10;
-    // Keep in mind that default rpcworkqueue in dogecoind is 16, meaning more than 16 concurrent requests will be rejected.
-    // Since we are already requesting blocks on a separate thread, and we don't want to break if anything
-    // else runs a request, we need to keep this a bit lower as configured.
-    let parallel_requests = index.nr_parallel_requests;
-
-    std::thread::spawn(move || {
-      let rt = tokio::runtime::Builder::new_multi_thread()
-        .enable_all()
-        .build()
-        .unwrap();
-      rt.block_on(async move {
-        loop {
-          let Some(outpoint) = outpoint_receiver.recv().await else {
-            log::debug!("Outpoint channel closed");
-            return;
-          };
-          // There's no try_iter on tokio::sync::mpsc::Receiver like std::sync::mpsc::Receiver.
-          // So we just loop until BATCH_SIZE doing try_recv until it returns None.
-          let mut outpoints = vec![outpoint];
-          for _ in 0..BATCH_SIZE - 1 {
-            let Ok(outpoint) = outpoint_receiver.try_recv() else {
-              break;
-            };
-            outpoints.push(outpoint);
-          }
-          // Break outpoints into chunks for parallel requests
-          let chunk_size = (outpoints.len() / parallel_requests) + 1;
-          let mut futs = Vec::with_capacity(parallel_requests);
-          for chunk in outpoints.chunks(chunk_size) {
-            let txids = chunk.iter().map(|outpoint| outpoint.txid).collect();
-            let fut = fetcher.get_transactions(txids);
-            futs.push(fut);
-          }
-          let txs = match try_join_all(futs).await {
-            Ok(txs) => txs,
-            Err(e) => {
-              log::error!("Couldn't receive txs {e}");
-              return;
-            }
-          };
-          // Send all tx output values back in order
-          for (i, tx) in txs.iter().flatten().enumerate() {
-            let Ok(_) = value_sender
-              .send(tx.output[usize::try_from(outpoints[i].vout).unwrap()].value)
-              .await
-            else {
-              log::error!("Value channel closed unexpectedly");
-              return;
-            };
-          }
+
+    std::thread::spawn(move || loop {
+      let Some(outpoint) = outpoint_receiver.blocking_recv() else {
+        log::debug!("Outpoint channel closed");
+        return;
+      };
+      // There's no try_iter on tokio::sync::mpsc::Receiver like std::sync::mpsc::Receiver.
+      // So we just loop until BATCH_SIZE doing try_recv until it returns None.
+      let mut outpoints = vec![outpoint];
+      for _ in 0..BATCH_SIZE - 1 {
+        let Ok(outpoint) = outpoint_receiver.try_recv() else {
+          break;
+        };
+        outpoints.push(outpoint);
+      }
+      let txids = outpoints
+        .iter()
+        .map(|outpoint| outpoint.txid)
+        .collect::<Vec<Txid>>();
+      let txs = match block_source.transactions(&txids) {
+        Ok(txs) => txs,
+        Err(e) => {
+          log::error!("Couldn't receive txs {e}");
+          return;
         }
-      })
+      };
+      // Send all tx output values back in order
+      for (outpoint, tx) in outpoints.iter().zip(&txs) {
+        let Ok(_) =
+          value_sender.blocking_send(tx.output[usize::try_from(outpoint.vout).unwrap()].value)
+        else {
+          log::error!("Value channel closed unexpectedly");
+          return;
+        };
+      }
     });
 
     Ok((outpoint_sender, value_receiver))
@@ -349,11 +475,20 @@ impl<'index> Updater<'_> {
     };
 
     let mut outpoint_to_value = wtx.open_table(OUTPOINT_TO_VALUE)?;
-    let mut address_to_outpoint = wtx.open_multimap_table(ADDRESS_TO_OUTPOINT)?;
+    let mut address_tables = AddressTables::open(wtx)?;
 
     let index_inscriptions = self.height >= index.first_inscription_height;
 
-    if index_inscriptions {
+    let utxos_complete = index
+      .utxos_from
+      .map(|utxos_from| self.height >= utxos_from)
+      .unwrap_or_default();
+
+    if utxos_complete && !index_inscriptions {
+      Self::index_utxos(&block, self.height, &mut outpoint_to_value, value_cache)?;
+    }
+
+    if index_inscriptions && !utxos_complete {
       // Send all missing input outpoints to be fetched right away
       let txids = block
         .txdata
@@ -388,6 +523,7 @@ impl<'index> Updater<'_> {
     }
 
     let mut height_to_block_hash = wtx.open_table(HEIGHT_TO_BLOCK_HASH)?;
+    let mut height_to_subsidy = wtx.open_table(HEIGHT_TO_SUBSIDY)?;
 
     let mut inscription_id_to_inscription_entry =
       wtx.open_table(INSCRIPTION_ID_TO_INSCRIPTION_ENTRY)?;
@@ -396,6 +532,9 @@ impl<'index> Updater<'_> {
     let mut inscription_txid_to_tx = wtx.open_table(INSCRIPTION_TXID_TO_TX)?;
     let mut partial_txid_to_inscription_txids =
       wtx.open_table(PARTIAL_TXID_TO_INSCRIPTION_TXIDS)?;
+    let mut inscription_id_to_content_hash = wtx.open_table(INSCRIPTION_ID_TO_CONTENT_HASH)?;
+    let mut content_hash_to_inscription_content =
+      wtx.open_table(CONTENT_HASH_TO_INSCRIPTION_CONTENT)?;
     let mut inscription_number_to_inscription_id =
       wtx.open_table(INSCRIPTION_NUMBER_TO_INSCRIPTION_ID)?;
     let mut sat_to_inscription_id = wtx.open_table(SAT_TO_INSCRIPTION_ID)?;
@@ -404,23 +543,72 @@ impl<'index> Updater<'_> {
     let mut transaction_id_to_transaction = wtx.open_table(TRANSACTION_ID_TO_TRANSACTION)?;
 
     let mut drc20_token_info = wtx.open_table(DRC20_TOKEN)?;
-    let mut drc20_token_holder = wtx.open_multimap_table(DRC20_TOKEN_HOLDER)?;
+    let mut drc20_tick_holder = wtx.open_table(DRC20_TICK_HOLDER)?;
+    let mut drc20_tick_to_holder_count = wtx.open_table(DRC20_TICK_TO_HOLDER_COUNT)?;
+    let mut drc20_tick_balance_holder = wtx.open_table(DRC20_TICK_BALANCE_HOLDER)?;
     let mut drc20_token_balance = wtx.open_table(DRC20_BALANCES)?;
     let mut drc20_inscribe_transfer = wtx.open_table(DRC20_INSCRIBE_TRANSFER)?;
     let mut drc20_transferable_log = wtx.open_table(DRC20_TRANSFERABLELOG)?;
+    let mut drc20_tick_activity = wtx.open_table(DRC20_TICK_ACTIVITY)?;
+    let mut drc20_height_to_state_hash = wtx.open_table(DRC20_HEIGHT_TO_STATE_HASH)?;
+    let mut drc20_balance_history = wtx.open_table(DRC20_BALANCE_HISTORY)?;
 
     let mut lost_sats = statistic_to_count
       .get(&Statistic::LostSats.key())?
       .map(|lost_sats| lost_sats.value())
       .unwrap_or(0);
 
-    {
+    let mut dune_tables = if index.index_dunes && self.height >= self.index.first_dune_height {
+      Some((
+        wtx.open_table(OUTPOINT_TO_DUNE_BALANCES)?,
+        wtx.open_table(DUNE_ID_TO_DUNE_ENTRY)?,
+        wtx.open_table(DUNE_TO_DUNE_ID)?,
+      ))
+    } else {
+      None
+    };
+
+    let dune_updater = dune_tables
+      .as_mut()
+      .map(
+        |(outpoint_to_dune_balances, dune_id_to_dune_entry, dune_to_dune_id)| {
+          DuneUpdater::new(
+            self.height,
+            outpoint_to_dune_balances,
+            dune_id_to_dune_entry,
+            dune_to_dune_id,
+            &statistic_to_count,
+            block.header.time,
+            Dune::minimum_at_height(Chain::Mainnet, Height(self.height)),
+          )
+        },
+      )
+      .transpose()?;
+
+    let txdata = &block.txdata;
+
+    // Dunes only depend on transaction order and their own tables, so they are
+    // indexed on a second thread while inscriptions, sats and DRC-20 are
+    // indexed on this one. Their remaining writes are applied after both
+    // finish, which keeps the result independent of scheduling.
+    let dune_updater = thread::scope(|scope| -> Result<Option<DuneUpdater>> {
+      let dunes = dune_updater.map(|mut dune_updater| {
+        scope.spawn(move || -> Result<DuneUpdater> {
+          for (i, (tx, txid)) in txdata.iter().enumerate() {
+            dune_updater.index_dunes(i, tx, *txid)?;
+          }
+          Ok(dune_updater)
+        })
+      });
+
       let mut inscription_updater = InscriptionUpdater::new(
         self.height,
         &mut inscription_id_to_satpoint,
         &mut inscription_id_to_txids,
         &mut inscription_txid_to_tx,
         &mut partial_txid_to_inscription_txids,
+        &mut inscription_id_to_content_hash,
+        &mut content_hash_to_inscription_content,
         value_receiver,
         self.index.index_transactions,
         Vec::new(),
@@ -429,11 +617,12 @@ impl<'index> Updater<'_> {
         lost_sats,
         &mut inscription_number_to_inscription_id,
         &mut outpoint_to_value,
-        &mut address_to_outpoint,
+        &mut address_tables,
         &mut sat_to_inscription_id,
         &mut satpoint_to_inscription_id,
         block.header.time,
         value_cache,
+        utxos_complete,
         index.chain,
       )?;
 
@@ -443,13 +632,6 @@ impl<'index> Updater<'_> {
 
         let mut coinbase_inputs = VecDeque::new();
 
-        let h = Height(self.height);
-        if h.subsidy() > 0 {
-          let start = h.starting_sat();
-          coinbase_inputs.push_front((start.n(), (start + h.subsidy()).n()));
-          self.sat_ranges_since_flush += 1;
-        }
-
         for (tx_offset, (tx, txid)) in block.txdata.iter().enumerate().skip(1) {
           log::trace!("Indexing transaction {tx_offset}…");
 
@@ -472,9 +654,7 @@ impl<'index> Updater<'_> {
                 .to_vec(),
             };
 
-            for chunk in sat_ranges.chunks_exact(11) {
-              input_sat_ranges.push_back(SatRange::load(chunk.try_into().unwrap()));
-            }
+            input_sat_ranges.extend(decode_sat_ranges(&sat_ranges)?);
           }
 
           self.index_transaction_sats(
@@ -491,9 +671,20 @@ impl<'index> Updater<'_> {
           coinbase_inputs.extend(input_sat_ranges);
         }
 
-        if let Some((tx, txid)) = block.txdata.first() {
+        if let Some((coinbase, txid)) = block.txdata.first() {
+          // what is left of the inputs is the fees
+          let fees = coinbase_inputs.iter().map(|(start, end)| end - start).sum();
+          let subsidy = self.record_subsidy(&mut height_to_subsidy, coinbase, fees)?;
+          inscription_updater.set_subsidy(subsidy);
+
+          if subsidy > 0 {
+            let start = self.schedule.starting_sat(Height(self.height));
+            coinbase_inputs.push_front((start.n(), (start + subsidy).n()));
+            self.sat_ranges_since_flush += 1;
+          }
+
           self.index_transaction_sats(
-            tx,
+            coinbase,
             *txid,
             &mut sat_to_satpoint,
             &mut coinbase_inputs,
@@ -511,7 +702,7 @@ impl<'index> Updater<'_> {
             .unwrap_or_default();
 
           for (start, end) in coinbase_inputs {
-            if !Sat(start).is_common() {
+            if !self.schedule.is_common(Sat(start)) {
               sat_to_satpoint.insert(
                 &start,
                 &SatPoint {
@@ -522,17 +713,24 @@ impl<'index> Updater<'_> {
               )?;
             }
 
-            lost_sat_ranges.extend_from_slice(&(start, end).store());
+            push_sat_range(&mut lost_sat_ranges, (start, end));
 
             lost_sats += u64::try_from(end - start).unwrap();
           }
 
           outpoint_to_sat_ranges.insert(&OutPoint::null().store(), lost_sat_ranges.as_slice())?;
         }
-      } else {
-        for (tx, txid) in block.txdata.iter().skip(1).chain(block.txdata.first()) {
+      } else if index_inscriptions {
+        for (tx, txid) in block.txdata.iter().skip(1) {
           lost_sats += inscription_updater.index_transaction_inscriptions(tx, *txid, None)?;
         }
+
+        if let Some((coinbase, txid)) = block.txdata.first() {
+          let subsidy =
+            self.record_subsidy(&mut height_to_subsidy, coinbase, inscription_updater.fees())?;
+          inscription_updater.set_subsidy(subsidy);
+          lost_sats += inscription_updater.index_transaction_inscriptions(coinbase, *txid, None)?;
+        }
       }
 
       if index.index_drc20 && self.height >= index.first_inscription_height {
@@ -541,15 +739,21 @@ impl<'index> Updater<'_> {
         // Create a protocol manager to index the block of drc20 data.
         Drc20Updater::new(
           &mut drc20_token_info,
-          &mut drc20_token_holder,
+          &mut drc20_tick_holder,
+          &mut drc20_tick_to_holder_count,
+          &mut drc20_tick_balance_holder,
           &mut drc20_token_balance,
           &mut drc20_inscribe_transfer,
           &mut drc20_transferable_log,
+          &mut drc20_tick_activity,
+          &mut drc20_height_to_state_hash,
+          &mut drc20_balance_history,
           &inscription_id_to_inscription_entry,
           &mut transaction_id_to_transaction,
         )?
         .index_block(
           BlockContext {
+            chain: index.chain,
             network: Network::Bitcoin,
             blockheight: self.height as u64,
             blocktime: block.header.time,
@@ -560,27 +764,24 @@ impl<'index> Updater<'_> {
       }
 
       statistic_to_count.insert(&Statistic::LostSats.key(), &lost_sats)?;
-    }
 
-    if index.index_dunes && self.height >= self.index.first_dune_height {
-      let mut outpoint_to_dune_balances = wtx.open_table(OUTPOINT_TO_DUNE_BALANCES)?;
-      let mut dune_id_to_dune_entry = wtx.open_table(DUNE_ID_TO_DUNE_ENTRY)?;
-      let mut dune_to_dune_id = wtx.open_table(DUNE_TO_DUNE_ID)?;
-      let mut inscription_id_to_dune = wtx.open_table(INSCRIPTION_ID_TO_DUNE)?;
-      let mut dune_updater = DuneUpdater::new(
-        self.height,
-        &mut outpoint_to_dune_balances,
-        &mut dune_id_to_dune_entry,
-        &inscription_id_to_inscription_entry,
-        &mut inscription_id_to_dune,
-        &mut dune_to_dune_id,
+      dunes
+        .map(|dunes| dunes.join().unwrap_or_else(|panic| panic::resume_unwind(panic)))
+        .transpose()
+    })?;
+
+    if let Some(dune_updater) = dune_updater {
+      dune_updater.finish(
         &mut statistic_to_count,
-        block.header.time,
-        Dune::minimum_at_height(Chain::Mainnet, Height(self.height)),
+        &inscription_id_to_inscription_entry,
+        &mut wtx.open_table(INSCRIPTION_ID_TO_DUNE)?,
       )?;
-      for (i, (tx, txid)) in block.txdata.iter().enumerate() {
-        dune_updater.index_dunes(i, tx, *txid)?;
-      }
+    }
+
+    if let Some(auxpow) = &block.auxpow {
+      wtx
+        .open_table(HEIGHT_TO_AUXPOW)?
+        .insert(&self.height, consensus::serialize(auxpow).as_slice())?;
     }
 
     height_to_block_hash.insert(&self.height, &block.header.block_hash().store())?;
@@ -596,6 +797,71 @@ impl<'index> Updater<'_> {
     Ok(())
   }
 
+  /// Records the subsidy `coinbase` claims on top of `fees`, and returns the
+  /// one its sats and inscriptions are counted with: the claimed subsidy if the
+  /// schedule numbers the block by it, and otherwise the scheduled one, of
+  /// which sats the coinbase leaves unclaimed are lost.
+  fn record_subsidy(
+    &mut self,
+    height_to_subsidy: &mut Table<u32, u64>,
+    coinbase: &Transaction,
+    fees: u64,
+  ) -> Result<u64> {
+    let claimed = coinbase
+      .output
+      .iter()
+      .map(|output| output.value)
+      .sum::<u64>()
+      .saturating_sub(fees);
+
+    height_to_subsidy.insert(&self.height, &claimed)?;
+
+    let height = Height(self.height);
+
+    Ok(if self.schedule.record(height, claimed) {
+      claimed
+    } else {
+      self.schedule.subsidy(height)
+    })
+  }
+
+  /// Keeps `OUTPOINT_TO_VALUE` complete for blocks below the first inscription
+  /// height, which are otherwise only indexed for their headers.
+  fn index_utxos(
+    block: &BlockData,
+    height: u32,
+    outpoint_to_value: &mut Table<&OutPointValue, u64>,
+    value_cache: &mut HashMap<OutPoint, OutPointMapValue>,
+  ) -> Result {
+    for (tx, txid) in &block.txdata {
+      for input in &tx.input {
+        let outpoint = input.previous_output;
+
+        if outpoint.is_null() {
+          continue;
+        }
+
+        if value_cache.remove(&outpoint).is_none()
+          && outpoint_to_value.remove(&outpoint.store())?.is_none()
+        {
+          bail!("missing value for output {outpoint}");
+        }
+      }
+
+      for (vout, output) in tx.output.iter().enumerate() {
+        value_cache.insert(
+          OutPoint {
+            txid: *txid,
+            vout: vout.try_into().unwrap(),
+          },
+          (output.value, [0; 34], height),
+        );
+      }
+    }
+
+    Ok(())
+  }
+
   fn index_transaction_sats(
     &mut self,
     tx: &Transaction,
@@ -624,7 +890,7 @@ impl<'index> Updater<'_> {
           .pop_front()
           .ok_or_else(|| anyhow!("insufficient inputs for transaction outputs"))?;
 
-        if !Sat(range.0).is_common() {
+        if !self.schedule.is_common(Sat(range.0)) {
           sat_to_satpoint.insert(
             &range.0,
             &SatPoint {
@@ -646,7 +912,7 @@ impl<'index> Updater<'_> {
           range
         };
 
-        sats.extend_from_slice(&assigned.store());
+        push_sat_range(&mut sats, assigned);
 
         remaining -= u64::try_from(assigned.1 - assigned.0).unwrap();
 
@@ -667,11 +933,14 @@ impl<'index> Updater<'_> {
     wtx: WriteTransaction,
     value_cache: HashMap<OutPoint, OutPointMapValue>,
   ) -> Result {
+    let cached_outputs = value_cache.len() + self.range_cache.len();
+
     log::info!(
-      "Committing at block height {}, {} outputs traversed, {} in map, {} cached",
+      "Committing at block height {}, {} outputs traversed, {} in map, {} values, {} cached",
       self.height,
       self.outputs_traversed,
       self.range_cache.len(),
+      value_cache.len(),
       self.outputs_cached
     );
 
@@ -694,12 +963,12 @@ impl<'index> Updater<'_> {
 
     {
       let mut outpoint_to_value = wtx.open_table(OUTPOINT_TO_VALUE)?;
-      let mut address_to_outpoint = wtx.open_multimap_table(ADDRESS_TO_OUTPOINT)?;
+      let mut address_tables = AddressTables::open(&wtx)?;
 
       for (outpoint, map) in value_cache {
         outpoint_to_value.insert(&outpoint.store(), map.0)?;
         if map.1 != [0u8; 34] {
-          address_to_outpoint.insert(map.1.as_slice(), &outpoint.store())?;
+          address_tables.insert(&map.1, outpoint, map.0, map.2)?;
         }
       }
     }
@@ -710,8 +979,14 @@ impl<'index> Updater<'_> {
     self.sat_ranges_since_flush = 0;
     Index::increment_statistic(&wtx, Statistic::Commits, 1)?;
 
+    wtx
+      .open_table(STATISTIC_TO_COUNT)?
+      .insert(&Statistic::CachedOutputs.key(), &u64::try_from(cached_outputs)?)?;
+
     wtx.commit()?;
 
+    *self.index.schedule.lock().unwrap() = Arc::new(self.schedule.clone());
+
     Reorg::update_savepoints(self.index, self.height)?;
 
     Ok(())
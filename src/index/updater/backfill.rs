@@ -0,0 +1,1041 @@
+use {
+  super::*,
+  crate::{
+    drc20::{
+      operation::{deserialize_drc20_operation, Action, InscriptionOp, Operation},
+      Ruleset,
+    },
+    inscription::ParsedInscription,
+  },
+  clap::ValueEnum,
+};
+
+const BLOCKS_PER_COMMIT: u32 = 1000;
+
+/// An index that can be turned on or off after the database was created.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub(crate) enum OptionalIndex {
+  Drc20,
+  Dunes,
+  Sats,
+  Transactions,
+}
+
+impl OptionalIndex {
+  fn statistic(self) -> Statistic {
+    match self {
+      Self::Drc20 => Statistic::IndexDrc20,
+      Self::Dunes => Statistic::IndexDunes,
+      Self::Sats => Statistic::IndexSats,
+      Self::Transactions => Statistic::IndexTransactions,
+    }
+  }
+}
+
+impl Display for OptionalIndex {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(
+      f,
+      "{}",
+      match self {
+        Self::Drc20 => "drc20",
+        Self::Dunes => "dunes",
+        Self::Sats => "sats",
+        Self::Transactions => "transactions",
+      }
+    )
+  }
+}
+
+impl Index {
+  fn is_enabled(&self, index: OptionalIndex) -> bool {
+    match index {
+      OptionalIndex::Drc20 => self.index_drc20,
+      OptionalIndex::Dunes => self.index_dunes,
+      OptionalIndex::Sats => self.index_sats,
+      OptionalIndex::Transactions => self.index_transactions,
+    }
+  }
+
+  /// Turns on `index` for an existing database by replaying the blocks from
+  /// `from` up to the current tip, treating the index as empty before `from`.
+  /// Blocks are committed in batches and the index is only marked as enabled
+  /// once the tip is reached, so an interrupted backfill is simply started
+  /// over. Returns the number of blocks replayed.
+  pub(crate) fn enable_optional_index(&self, index: OptionalIndex, from: Option<u32>) -> Result<u32> {
+    ensure!(!self.is_enabled(index), "--index-{index} is already enabled");
+
+    let first_height = match index {
+      OptionalIndex::Sats => {
+        bail!("--index-sats needs every block from genesis, rebuild the index to enable it")
+      }
+      OptionalIndex::Drc20 => {
+        ensure!(
+          self.index_transactions,
+          "--index-drc20 needs --index-transactions, enable it first"
+        );
+        self.first_inscription_height
+      }
+      OptionalIndex::Dunes => self.first_dune_height,
+      OptionalIndex::Transactions => self.first_inscription_height,
+    };
+
+    let start = from.unwrap_or(first_height);
+
+    ensure!(
+      start >= first_height,
+      "--index-{index} can't be backfilled from {start}, which is below its first height {first_height}"
+    );
+
+    let end = self.block_count()?.max(start);
+
+    let progress_bar = if cfg!(test) || log_enabled!(log::Level::Info) || integration_test() {
+      ProgressBar::hidden()
+    } else {
+      let progress_bar = ProgressBar::new((end - start).into());
+      progress_bar.set_style(
+        ProgressStyle::with_template("[backfilling {msg}] {wide_bar} {pos}/{len}").unwrap(),
+      );
+      progress_bar.set_message(index.to_string());
+      progress_bar
+    };
+
+    let mut replay = Drc20Replay::default();
+    let mut height = start;
+
+    loop {
+      let wtx = self.begin_write()?;
+
+      if height == start {
+        Self::clear_optional_index(&wtx, index)?;
+      }
+
+      let batch = height..end.min(height + BLOCKS_PER_COMMIT);
+
+      for height in batch.clone() {
+        let block = BlockData::from(
+          self
+            .block_source
+            .block(height)?
+            .ok_or_else(|| anyhow!("block {height} not found"))?,
+        );
+
+        match index {
+          OptionalIndex::Drc20 => self.backfill_drc20(&wtx, &mut replay, height, &block)?,
+          OptionalIndex::Dunes => Self::backfill_dunes(&wtx, height, &block)?,
+          OptionalIndex::Transactions => Self::backfill_transactions(&wtx, &block)?,
+          OptionalIndex::Sats => unreachable!(),
+        }
+
+        progress_bar.inc(1);
+      }
+
+      height = batch.end;
+
+      if height == end {
+        wtx
+          .open_table(STATISTIC_TO_COUNT)?
+          .insert(&index.statistic().key(), &1)?;
+        wtx.commit()?;
+        break;
+      }
+
+      wtx.commit()?;
+    }
+
+    progress_bar.finish_and_clear();
+
+    Ok(end - start)
+  }
+
+  /// Turns off `index` and deletes its tables. The space is reused by redb,
+  /// but the file only shrinks once the database is compacted.
+  pub(crate) fn disable_optional_index(&self, index: OptionalIndex) -> Result {
+    ensure!(self.is_enabled(index), "--index-{index} is not enabled");
+
+    ensure!(
+      !(index == OptionalIndex::Transactions && self.index_drc20),
+      "--index-drc20 needs --index-transactions, disable it first"
+    );
+
+    let wtx = self.begin_write()?;
+
+    Self::clear_optional_index(&wtx, index)?;
+
+    wtx
+      .open_table(STATISTIC_TO_COUNT)?
+      .insert(&index.statistic().key(), &0)?;
+
+    wtx.commit()?;
+
+    Ok(())
+  }
+
+  pub(crate) fn compact(&mut self) -> Result<bool> {
+    Ok(self.database.compact()?)
+  }
+
+  /// Replaces the tables that belong to `index` with empty ones.
+  fn clear_optional_index(wtx: &WriteTransaction, index: OptionalIndex) -> Result {
+    fn clear<K: redb::RedbKey + 'static, V: redb::RedbValue + 'static>(
+      wtx: &WriteTransaction,
+      definition: TableDefinition<K, V>,
+    ) -> Result {
+      wtx.delete_table(definition)?;
+      wtx.open_table(definition)?;
+      Ok(())
+    }
+
+    match index {
+      OptionalIndex::Drc20 => {
+        clear(wtx, DRC20_BALANCES)?;
+        clear(wtx, DRC20_TOKEN)?;
+        clear(wtx, DRC20_INSCRIBE_TRANSFER)?;
+        clear(wtx, DRC20_TRANSFERABLELOG)?;
+        clear(wtx, DRC20_TICK_ACTIVITY)?;
+        clear(wtx, DRC20_HEIGHT_TO_STATE_HASH)?;
+        clear(wtx, DRC20_BALANCE_HISTORY)?;
+        clear(wtx, DRC20_TICK_HOLDER)?;
+        clear(wtx, DRC20_TICK_TO_HOLDER_COUNT)?;
+        clear(wtx, DRC20_TICK_BALANCE_HOLDER)?;
+
+        wtx
+          .open_table(STATISTIC_TO_COUNT)?
+          .remove(&Statistic::Drc20BalanceHistoryFrom.key())?;
+      }
+      OptionalIndex::Dunes => {
+        clear(wtx, OUTPOINT_TO_DUNE_BALANCES)?;
+        clear(wtx, DUNE_ID_TO_DUNE_ENTRY)?;
+        clear(wtx, DUNE_TO_DUNE_ID)?;
+        clear(wtx, INSCRIPTION_ID_TO_DUNE)?;
+        clear(wtx, TRANSACTION_ID_TO_DUNE)?;
+
+        let mut statistics = wtx.open_table(STATISTIC_TO_COUNT)?;
+        statistics.remove(&Statistic::Dunes.key())?;
+        statistics.remove(&Statistic::ReservedDunes.key())?;
+      }
+      OptionalIndex::Sats => {
+        clear(wtx, OUTPOINT_TO_SAT_RANGES)?;
+        clear(wtx, SAT_TO_SATPOINT)?;
+        clear(wtx, SAT_TO_INSCRIPTION_ID)?;
+
+        wtx
+          .open_table(STATISTIC_TO_COUNT)?
+          .remove(&Statistic::SatRanges.key())?;
+      }
+      OptionalIndex::Transactions => clear(wtx, TRANSACTION_ID_TO_TRANSACTION)?,
+    }
+
+    Ok(())
+  }
+
+  fn backfill_drc20(
+    &self,
+    wtx: &WriteTransaction,
+    replay: &mut Drc20Replay,
+    height: u32,
+    block: &BlockData,
+  ) -> Result {
+    let inscription_id_to_txids = wtx.open_table(INSCRIPTION_ID_TO_TXIDS)?;
+    let inscription_txid_to_tx = wtx.open_table(INSCRIPTION_TXID_TO_TX)?;
+    let inscription_id_to_inscription_entry = wtx.open_table(INSCRIPTION_ID_TO_INSCRIPTION_ENTRY)?;
+    let mut transaction_id_to_transaction = wtx.open_table(TRANSACTION_ID_TO_TRANSACTION)?;
+
+    let operations = replay.operations(
+      self,
+      height,
+      block,
+      &inscription_id_to_txids,
+      &inscription_txid_to_tx,
+      &inscription_id_to_inscription_entry,
+      &transaction_id_to_transaction,
+    )?;
+
+    let mut drc20_token_info = wtx.open_table(DRC20_TOKEN)?;
+    let mut drc20_tick_holder = wtx.open_table(DRC20_TICK_HOLDER)?;
+    let mut drc20_tick_to_holder_count = wtx.open_table(DRC20_TICK_TO_HOLDER_COUNT)?;
+    let mut drc20_tick_balance_holder = wtx.open_table(DRC20_TICK_BALANCE_HOLDER)?;
+    let mut drc20_token_balance = wtx.open_table(DRC20_BALANCES)?;
+    let mut drc20_inscribe_transfer = wtx.open_table(DRC20_INSCRIBE_TRANSFER)?;
+    let mut drc20_transferable_log = wtx.open_table(DRC20_TRANSFERABLELOG)?;
+    let mut drc20_tick_activity = wtx.open_table(DRC20_TICK_ACTIVITY)?;
+    let mut drc20_height_to_state_hash = wtx.open_table(DRC20_HEIGHT_TO_STATE_HASH)?;
+    let mut drc20_balance_history = wtx.open_table(DRC20_BALANCE_HISTORY)?;
+
+    Drc20Updater::new(
+      &mut drc20_token_info,
+      &mut drc20_tick_holder,
+      &mut drc20_tick_to_holder_count,
+      &mut drc20_tick_balance_holder,
+      &mut drc20_token_balance,
+      &mut drc20_inscribe_transfer,
+      &mut drc20_transferable_log,
+      &mut drc20_tick_activity,
+      &mut drc20_height_to_state_hash,
+      &mut drc20_balance_history,
+      &inscription_id_to_inscription_entry,
+      &mut transaction_id_to_transaction,
+    )?
+    .index_block(
+      BlockContext {
+        chain: self.chain,
+        network: Network::Bitcoin,
+        blockheight: height.into(),
+        blocktime: block.header.time,
+      },
+      block,
+      operations,
+    )?;
+
+    Ok(())
+  }
+
+  fn backfill_dunes(wtx: &WriteTransaction, height: u32, block: &BlockData) -> Result {
+    let mut outpoint_to_dune_balances = wtx.open_table(OUTPOINT_TO_DUNE_BALANCES)?;
+    let mut dune_id_to_dune_entry = wtx.open_table(DUNE_ID_TO_DUNE_ENTRY)?;
+    let inscription_id_to_inscription_entry = wtx.open_table(INSCRIPTION_ID_TO_INSCRIPTION_ENTRY)?;
+    let mut inscription_id_to_dune = wtx.open_table(INSCRIPTION_ID_TO_DUNE)?;
+    let mut dune_to_dune_id = wtx.open_table(DUNE_TO_DUNE_ID)?;
+    let mut statistic_to_count = wtx.open_table(STATISTIC_TO_COUNT)?;
+
+    let mut dune_updater = DuneUpdater::new(
+      height,
+      &mut outpoint_to_dune_balances,
+      &mut dune_id_to_dune_entry,
+      &mut dune_to_dune_id,
+      &statistic_to_count,
+      block.header.time,
+      Dune::minimum_at_height(Chain::Mainnet, Height(height)),
+    )?;
+
+    for (i, (tx, txid)) in block.txdata.iter().enumerate() {
+      dune_updater.index_dunes(i, tx, *txid)?;
+    }
+
+    dune_updater.finish(
+      &mut statistic_to_count,
+      &inscription_id_to_inscription_entry,
+      &mut inscription_id_to_dune,
+    )
+  }
+
+  fn backfill_transactions(wtx: &WriteTransaction, block: &BlockData) -> Result {
+    let mut transaction_id_to_transaction = wtx.open_table(TRANSACTION_ID_TO_TRANSACTION)?;
+    let mut buffer = Vec::new();
+
+    for (tx, txid) in &block.txdata {
+      tx.consensus_encode(&mut buffer)?;
+      transaction_id_to_transaction.insert(&txid.store(), buffer.as_slice())?;
+      buffer.clear();
+    }
+
+    Ok(())
+  }
+}
+
+/// Rebuilds the inscription operations `Drc20Updater` consumes without
+/// re-running inscription indexing, using `INSCRIPTION_ID_TO_TXIDS` to tell
+/// which transactions completed an inscription and `INSCRIPTION_TXID_TO_TX` to
+/// read back the earlier parts of inscriptions revealed over several
+/// transactions. Only operations that can change DRC-20 state are produced:
+/// reveals, and the first move of each inscribe-transfer inscription, which is
+/// tracked from its reveal on.
+#[derive(Default)]
+struct Drc20Replay {
+  unmoved_transfers: HashMap<OutPoint, Vec<(u64, InscriptionId)>>,
+}
+
+impl Drc20Replay {
+  fn operations(
+    &mut self,
+    index: &Index,
+    height: u32,
+    block: &BlockData,
+    inscription_id_to_txids: &Table<'_, '_, &'static InscriptionIdValue, &'static [u8]>,
+    inscription_txid_to_tx: &Table<'_, '_, &'static [u8], &'static [u8]>,
+    inscription_id_to_inscription_entry: &Table<'_, '_, &'static InscriptionIdValue, InscriptionEntryValue>,
+    transaction_id_to_transaction: &Table<'_, '_, &'static TxidValue, &'static [u8]>,
+  ) -> Result<HashMap<Txid, Vec<InscriptionOp>>> {
+    let block_transactions = block
+      .txdata
+      .iter()
+      .map(|(tx, txid)| (*txid, tx))
+      .collect::<HashMap<Txid, &Transaction>>();
+
+    let value = |outpoint: OutPoint| -> Result<u64> {
+      let tx = match block_transactions.get(&outpoint.txid) {
+        Some(tx) => (*tx).clone(),
+        None => match transaction_id_to_transaction.get(&outpoint.txid.store())? {
+          Some(tx) => consensus::deserialize(tx.value())?,
+          None => index
+            .block_source
+            .transactions(&[outpoint.txid])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("transaction {} not found", outpoint.txid))?,
+        },
+      };
+
+      Ok(
+        tx.output
+          .get(usize::try_from(outpoint.vout).unwrap())
+          .ok_or_else(|| anyhow!("output {outpoint} not found"))?
+          .value,
+      )
+    };
+
+    let fee = |tx: &Transaction| -> Result<u64> {
+      let mut input_value = 0;
+      for input in &tx.input {
+        input_value += value(input.previous_output)?;
+      }
+      Ok(input_value - tx.output.iter().map(|output| output.value).sum::<u64>())
+    };
+
+    let inscription_number = |inscription_id: InscriptionId| -> Result<Option<u64>> {
+      Ok(
+        inscription_id_to_inscription_entry
+          .get(&inscription_id.store())?
+          .map(|entry| InscriptionEntry::load(entry.value()).inscription_number),
+      )
+    };
+
+    // Follows the first inputs of `tx` back through the inscription
+    // transactions it continues, until reaching the first transaction of an
+    // inscription. `tx` revealed that inscription if it was its last part.
+    let revealed = |txid: Txid, tx: &Transaction| -> Result<Option<(InscriptionId, Vec<Transaction>)>> {
+      if inscription_txid_to_tx.get(txid.as_inner().as_slice())?.is_none() {
+        return Ok(None);
+      }
+
+      let mut txs = vec![tx.clone()];
+
+      loop {
+        let inscription_id = InscriptionId {
+          txid: txs[0].txid(),
+          index: 0,
+        };
+
+        if let Some(txids) = inscription_id_to_txids.get(&inscription_id.store())? {
+          let txids = txids.value();
+          return Ok(
+            (txids.len() == txs.len() * 32 && txids.ends_with(txid.as_inner()))
+              .then_some((inscription_id, txs)),
+          );
+        }
+
+        let Some(input) = txs[0].input.first() else {
+          return Ok(None);
+        };
+
+        let previous_txid = input.previous_output.txid;
+
+        let Some(previous) = inscription_txid_to_tx.get(previous_txid.as_inner().as_slice())? else {
+          return Ok(None);
+        };
+
+        txs.insert(0, consensus::deserialize(previous.value())?);
+      }
+    };
+
+    let mut operations: HashMap<Txid, Vec<InscriptionOp>> = HashMap::new();
+    let mut spent_as_fee = Vec::new();
+
+    for (i, (tx, txid)) in block.txdata.iter().enumerate().skip(1) {
+      let mut flotsam = Vec::new();
+
+      if let Some((inscription_id, txs)) = revealed(*txid, tx)? {
+        if let ParsedInscription::Complete(inscription) = Inscription::from_transactions(txs) {
+          flotsam.push((
+            0,
+            InscriptionOp {
+              txid: *txid,
+              action: Action::New { inscription },
+              inscription_number: inscription_number(inscription_id)?,
+              inscription_id,
+              old_satpoint: SatPoint {
+                outpoint: tx.input[0].previous_output,
+                offset: 0,
+              },
+              new_satpoint: None,
+            },
+          ));
+        }
+      }
+
+      if let Some(last) = tx
+        .input
+        .iter()
+        .rposition(|input| self.unmoved_transfers.contains_key(&input.previous_output))
+      {
+        let mut input_value = 0;
+
+        for input in &tx.input[..=last] {
+          let outpoint = input.previous_output;
+
+          for (offset, inscription_id) in self.unmoved_transfers.remove(&outpoint).unwrap_or_default() {
+            flotsam.push((
+              input_value + offset,
+              InscriptionOp {
+                txid: *txid,
+                action: Action::Transfer,
+                inscription_number: inscription_number(inscription_id)?,
+                inscription_id,
+                old_satpoint: SatPoint { outpoint, offset },
+                new_satpoint: None,
+              },
+            ));
+          }
+
+          input_value += value(outpoint)?;
+        }
+      }
+
+      if flotsam.is_empty() {
+        continue;
+      }
+
+      flotsam.sort_by_key(|(offset, _)| *offset);
+      let mut flotsam = flotsam.into_iter().peekable();
+
+      let mut output_value = 0;
+      for (vout, output) in tx.output.iter().enumerate() {
+        let end = output_value + output.value;
+
+        while let Some((offset, _)) = flotsam.peek() {
+          if *offset >= end {
+            break;
+          }
+
+          let (offset, mut op) = flotsam.next().unwrap();
+
+          let new_satpoint = SatPoint {
+            outpoint: OutPoint {
+              txid: *txid,
+              vout: vout.try_into().unwrap(),
+            },
+            offset: offset - output_value,
+          };
+
+          op.new_satpoint = Some(new_satpoint);
+
+          if let Action::New { inscription } = &op.action {
+            if let Ok(Operation::InscribeTransfer(_)) =
+              deserialize_drc20_operation(
+                inscription,
+                &op.action,
+                Ruleset::at(index.chain, height.into()),
+              )
+            {
+              self
+                .unmoved_transfers
+                .entry(new_satpoint.outpoint)
+                .or_default()
+                .push((new_satpoint.offset, op.inscription_id));
+            }
+          }
+
+          operations.entry(*txid).or_default().push(op);
+        }
+
+        output_value = end;
+      }
+
+      // Reveals whose inscription ends up in the fee never count, so only
+      // transfers are carried over to the coinbase.
+      let flotsam = flotsam
+        .filter(|(_, op)| op.action == Action::Transfer)
+        .collect::<Vec<(u64, InscriptionOp)>>();
+
+      if !flotsam.is_empty() {
+        let mut reward = index.schedule().subsidy(Height(height));
+        for (tx, _) in &block.txdata[1..i] {
+          reward += fee(tx)?;
+        }
+
+        spent_as_fee.extend(
+          flotsam
+            .into_iter()
+            .map(|(offset, op)| (reward + offset - output_value, op)),
+        );
+      }
+    }
+
+    if spent_as_fee.is_empty() {
+      return Ok(operations);
+    }
+
+    spent_as_fee.sort_by_key(|(offset, _)| *offset);
+
+    let (coinbase, coinbase_txid) = &block.txdata[0];
+    let mut spent_as_fee = spent_as_fee.into_iter().peekable();
+
+    let mut output_value = 0;
+    for (vout, output) in coinbase.output.iter().enumerate() {
+      let end = output_value + output.value;
+
+      while let Some((offset, _)) = spent_as_fee.peek() {
+        if *offset >= end {
+          break;
+        }
+
+        let (offset, mut op) = spent_as_fee.next().unwrap();
+
+        op.new_satpoint = Some(SatPoint {
+          outpoint: OutPoint {
+            txid: *coinbase_txid,
+            vout: vout.try_into().unwrap(),
+          },
+          offset: offset - output_value,
+        });
+
+        operations.entry(op.txid).or_default().push(op);
+      }
+
+      output_value = end;
+    }
+
+    // Lost sats are offset by every sat lost before this block, which the
+    // replay doesn't track. Only the null outpoint matters to DRC-20.
+    for (offset, mut op) in spent_as_fee {
+      op.new_satpoint = Some(SatPoint {
+        outpoint: OutPoint::null(),
+        offset: offset - output_value,
+      });
+
+      operations.entry(op.txid).or_default().push(op);
+    }
+
+    Ok(operations)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    crate::{
+      drc20::{BurnEvent, Event},
+      index::testing::Context,
+    },
+  };
+
+  /// `doge` is deployed without `dec`, so amounts carry the default 18 decimals.
+  const UNIT: u128 = 10u128.pow(18);
+
+  /// Signature scripts revealing a DRC-20 `op` over `parts` transactions.
+  fn reveal(op: &str, parts: usize) -> Vec<Script> {
+    let body = format!(r#"{{"p":"drc-20",{op}}}"#);
+    let chunks = body
+      .as_bytes()
+      .chunks((body.len() + parts - 1) / parts)
+      .collect::<Vec<&[u8]>>();
+
+    let mut remaining = chunks.len();
+
+    chunks
+      .iter()
+      .enumerate()
+      .map(|(i, chunk)| {
+        let mut builder = script::Builder::new();
+
+        if i == 0 {
+          builder = builder
+            .push_slice(b"ord")
+            .push_int(remaining as i64)
+            .push_slice(b"text/plain;charset=utf-8");
+        }
+
+        remaining -= 1;
+
+        builder
+          .push_int(remaining as i64)
+          .push_slice(chunk)
+          .into_script()
+      })
+      .collect()
+  }
+
+  fn drc20(op: &str) -> Script {
+    reveal(op, 1).remove(0)
+  }
+
+  fn drc20_activity(context: &Context) {
+    inscribe_drc20_transfer(context);
+
+    context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(4, 1, 0)],
+      ..Default::default()
+    });
+    context.mine_blocks(1);
+  }
+
+  /// Deploys and mints 100 `doge`, then inscribes a transfer of 40 in block 4.
+  fn inscribe_drc20_transfer(context: &Context) {
+    context.mine_blocks(1);
+
+    for (block, op) in [
+      (1, r#""op":"deploy","tick":"doge","max":"1000","lim":"100""#),
+      (2, r#""op":"mint","tick":"doge","amt":"100""#),
+      (3, r#""op":"transfer","tick":"doge","amt":"40""#),
+    ] {
+      context.rpc_server.broadcast_tx(TransactionTemplate {
+        inputs: &[(block, 0, 0)],
+        script_sig: drc20(op),
+        ..Default::default()
+      });
+      context.mine_blocks(1);
+    }
+  }
+
+  fn drc20_tables(index: &Index) -> Vec<Vec<(Vec<u8>, Vec<u8>)>> {
+    let rtx = index.database.begin_read().unwrap();
+
+    let mut tables = Vec::new();
+
+    for definition in [
+      DRC20_BALANCES,
+      DRC20_TOKEN,
+      DRC20_TRANSFERABLELOG,
+      DRC20_TICK_ACTIVITY,
+      DRC20_BALANCE_HISTORY,
+    ] {
+      tables.push(
+        rtx
+          .open_table(definition)
+          .unwrap()
+          .iter()
+          .unwrap()
+          .map(|entry| {
+            let (key, value) = entry.unwrap();
+            (key.value().as_bytes().to_vec(), value.value().to_vec())
+          })
+          .collect(),
+      );
+    }
+
+    tables.push(
+      rtx
+        .open_table(DRC20_INSCRIBE_TRANSFER)
+        .unwrap()
+        .iter()
+        .unwrap()
+        .map(|entry| {
+          let (key, value) = entry.unwrap();
+          (key.value().to_vec(), value.value().to_vec())
+        })
+        .collect(),
+    );
+
+    tables.push(
+      rtx
+        .open_table(DRC20_TICK_HOLDER)
+        .unwrap()
+        .iter()
+        .unwrap()
+        .map(|entry| (entry.unwrap().0.value().as_bytes().to_vec(), Vec::new()))
+        .collect(),
+    );
+
+    tables
+  }
+
+  fn statistic(index: &Index, statistic: Statistic) -> u64 {
+    index
+      .database
+      .begin_read()
+      .unwrap()
+      .open_table(STATISTIC_TO_COUNT)
+      .unwrap()
+      .get(&statistic.key())
+      .unwrap()
+      .map(|x| x.value())
+      .unwrap_or_default()
+  }
+
+  #[test]
+  fn drc20_backfill_matches_live_indexing() {
+    let live = Context::builder()
+      .args(["--index-drc20", "--index-transactions"])
+      .build();
+    drc20_activity(&live);
+
+    let backfilled = Context::builder().arg("--index-transactions").build();
+    drc20_activity(&backfilled);
+
+    assert_eq!(statistic(&backfilled.index, Statistic::IndexDrc20), 0);
+    assert!(drc20_tables(&backfilled.index).iter().all(Vec::is_empty));
+
+    assert_eq!(
+      backfilled
+        .index
+        .enable_optional_index(OptionalIndex::Drc20, None)
+        .unwrap(),
+      6
+    );
+
+    let tables = drc20_tables(&live.index);
+    assert!(!tables[1].is_empty(), "nothing was deployed");
+    assert_eq!(drc20_tables(&backfilled.index), tables);
+
+    let (_, tip) = live.index.get_drc20_state_hash_heights().unwrap().unwrap();
+    assert_eq!(
+      backfilled.index.get_drc20_state_hash(tip).unwrap(),
+      live.index.get_drc20_state_hash(tip).unwrap()
+    );
+
+    assert_eq!(statistic(&backfilled.index, Statistic::IndexDrc20), 1);
+  }
+
+  #[test]
+  fn multi_transaction_reveals_are_backfilled() {
+    let activity = |context: &Context| {
+      context.mine_blocks(1);
+
+      let deploy = reveal(r#""op":"deploy","tick":"doge","max":"1000","lim":"100""#, 2);
+
+      context.rpc_server.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0)],
+        script_sig: deploy[0].clone(),
+        ..Default::default()
+      });
+      context.mine_blocks(1);
+
+      context.rpc_server.broadcast_tx(TransactionTemplate {
+        inputs: &[(2, 1, 0)],
+        script_sig: deploy[1].clone(),
+        ..Default::default()
+      });
+      context.mine_blocks(1);
+
+      let mint = reveal(r#""op":"mint","tick":"doge","amt":"100""#, 3);
+
+      for (i, script_sig) in mint.into_iter().enumerate() {
+        context.rpc_server.broadcast_tx(TransactionTemplate {
+          inputs: &[(3 + i, if i == 0 { 0 } else { 1 }, 0)],
+          script_sig,
+          ..Default::default()
+        });
+        context.mine_blocks(1);
+      }
+    };
+
+    let live = Context::builder()
+      .args(["--index-drc20", "--index-transactions"])
+      .build();
+    activity(&live);
+
+    let backfilled = Context::builder().arg("--index-transactions").build();
+    activity(&backfilled);
+    backfilled
+      .index
+      .enable_optional_index(OptionalIndex::Drc20, None)
+      .unwrap();
+
+    let token = live
+      .index
+      .get_drc20_token_info(&Tick::from_str("doge").unwrap())
+      .unwrap()
+      .unwrap();
+    assert_eq!(token.minted, 100 * UNIT);
+
+    assert_eq!(drc20_tables(&backfilled.index), drc20_tables(&live.index));
+  }
+
+  #[test]
+  fn drc20_balances_are_kept_per_height() {
+    let context = Context::builder()
+      .args(["--index-drc20", "--index-transactions"])
+      .build();
+    drc20_activity(&context);
+
+    let tick = Tick::from_str("doge").unwrap();
+
+    assert!(context
+      .index
+      .get_drc20_holders_at(&tick, 2)
+      .unwrap()
+      .is_empty());
+
+    let holders = context.index.get_drc20_holders_at(&tick, 3).unwrap();
+    assert_eq!(holders.len(), 1);
+
+    let (holder, minted) = &holders[0];
+    assert_eq!(minted.overall_balance, 100 * UNIT);
+    assert_eq!(minted.transferable_balance, 0);
+
+    assert_eq!(
+      context
+        .index
+        .get_drc20_balance(holder, &tick, Some(4))
+        .unwrap()
+        .unwrap()
+        .transferable_balance,
+      40 * UNIT
+    );
+    assert_eq!(
+      context
+        .index
+        .get_drc20_balance(holder, &tick, Some(3))
+        .unwrap()
+        .as_ref(),
+      Some(minted)
+    );
+    assert_eq!(
+      context.index.get_drc20_balance(holder, &tick, Some(2)).unwrap(),
+      None
+    );
+
+    assert_eq!(
+      context
+        .index
+        .get_drc20_holders_at(&tick, 6)
+        .unwrap_err()
+        .to_string(),
+      "block 6 has not been indexed, the index has 6 blocks"
+    );
+  }
+
+  #[test]
+  fn transfers_spent_as_fee_are_burned() {
+    let burn = |context: &Context| {
+      inscribe_drc20_transfer(context);
+
+      context.rpc_server.broadcast_tx(TransactionTemplate {
+        inputs: &[(4, 1, 0)],
+        output_values: &[0],
+        ..Default::default()
+      });
+      context.mine_blocks(1);
+    };
+
+    let live = Context::builder()
+      .args(["--index-drc20", "--index-transactions"])
+      .build();
+    burn(&live);
+
+    let tick = Tick::from_str("doge").unwrap();
+
+    let token = live.index.get_drc20_token_info(&tick).unwrap().unwrap();
+    assert_eq!(token.minted, 100 * UNIT);
+    assert_eq!(token.burned, 40 * UNIT);
+    assert_eq!(token.circulating(), 60 * UNIT);
+
+    let holders = live.index.get_drc20_holders_at(&tick, 5).unwrap();
+    assert_eq!(holders.len(), 1);
+    assert_eq!(holders[0].1.overall_balance, 60 * UNIT);
+    assert_eq!(holders[0].1.transferable_balance, 0);
+
+    assert!(matches!(
+      live.index.get_drc20_tick_activity(&tick, 1).unwrap()[0].event,
+      Event::Burn(BurnEvent { amount, .. }) if amount == 40 * UNIT
+    ));
+
+    let backfilled = Context::builder().arg("--index-transactions").build();
+    burn(&backfilled);
+    backfilled
+      .index
+      .enable_optional_index(OptionalIndex::Drc20, None)
+      .unwrap();
+
+    assert_eq!(drc20_tables(&backfilled.index), drc20_tables(&live.index));
+  }
+
+  #[test]
+  fn transactions_are_backfilled() {
+    let context = Context::builder().build();
+    let blocks = context.mine_blocks(2);
+
+    assert_eq!(
+      context
+        .index
+        .enable_optional_index(OptionalIndex::Transactions, Some(2))
+        .unwrap(),
+      1
+    );
+
+    let rtx = context.index.database.begin_read().unwrap();
+    let transactions = rtx.open_table(TRANSACTION_ID_TO_TRANSACTION).unwrap();
+
+    assert!(transactions
+      .get(&blocks[1].txdata[0].txid().store())
+      .unwrap()
+      .is_some());
+    assert!(transactions
+      .get(&blocks[0].txdata[0].txid().store())
+      .unwrap()
+      .is_none());
+
+    assert_eq!(statistic(&context.index, Statistic::IndexTransactions), 1);
+  }
+
+  #[test]
+  fn disabling_an_index_deletes_its_data() {
+    let context = Context::builder().arg("--index-transactions").build();
+    context.mine_blocks(1);
+
+    context
+      .index
+      .disable_optional_index(OptionalIndex::Transactions)
+      .unwrap();
+
+    assert_eq!(
+      context
+        .index
+        .database
+        .begin_read()
+        .unwrap()
+        .open_table(TRANSACTION_ID_TO_TRANSACTION)
+        .unwrap()
+        .len()
+        .unwrap(),
+      0
+    );
+
+    assert_eq!(statistic(&context.index, Statistic::IndexTransactions), 0);
+  }
+
+  #[test]
+  fn transactions_cannot_be_disabled_under_drc20() {
+    let context = Context::builder()
+      .args(["--index-drc20", "--index-transactions"])
+      .build();
+
+    assert_eq!(
+      context
+        .index
+        .disable_optional_index(OptionalIndex::Transactions)
+        .unwrap_err()
+        .to_string(),
+      "--index-drc20 needs --index-transactions, disable it first"
+    );
+  }
+
+  #[test]
+  fn sats_cannot_be_backfilled() {
+    let context = Context::builder().build();
+
+    assert_eq!(
+      context
+        .index
+        .enable_optional_index(OptionalIndex::Sats, None)
+        .unwrap_err()
+        .to_string(),
+      "--index-sats needs every block from genesis, rebuild the index to enable it"
+    );
+  }
+
+  #[test]
+  fn enabled_indexes_cannot_be_enabled_again() {
+    let context = Context::builder().arg("--index-transactions").build();
+
+    assert_eq!(
+      context
+        .index
+        .enable_optional_index(OptionalIndex::Transactions, None)
+        .unwrap_err()
+        .to_string(),
+      "--index-transactions is already enabled"
+    );
+  }
+}
@@ -9,13 +9,16 @@ use {
 
 use crate::drc20::errors::Error::LedgerError;
 use crate::drc20::operation::{InscriptionOp, Operation};
-use crate::drc20::params::{BIGDECIMAL_TEN, MAX_DECIMAL_WIDTH};
+use crate::drc20::params::BIGDECIMAL_TEN;
+use crate::drc20::rules::Ruleset;
 use crate::drc20::script_key::ScriptKey;
+use crate::drc20::state_hash::StateHasher;
+use bitcoin::hashes::sha256;
 use crate::drc20::{
   max_script_tick_id_key, max_script_tick_key, min_script_tick_id_key, min_script_tick_key,
-  script_tick_id_key, script_tick_key, Balance, BlockContext, DRC20Error, Deploy, DeployEvent,
-  Event, InscribeTransferEvent, Message, Mint, MintEvent, Num, Tick, TokenInfo, Transfer,
-  TransferEvent, TransferInfo, TransferableLog,
+  balance_history_key, script_tick_id_key, script_tick_key, tick_activity_key, tick_balance_holder_key, tick_holder_key, Balance, BlockContext, DRC20Error,
+  BurnEvent, Deploy, DeployEvent, Event, InscribeTransferEvent, Message, Mint, MintEvent, Num, Tick,
+  TickActivity, TokenInfo, Transfer, TransferEvent, TransferInfo, TransferableLog,
 };
 use crate::subcommand::Output;
 
@@ -28,37 +31,58 @@ pub struct ExecutionMessage {
   pub(self) new_satpoint: SatPoint,
   pub(self) from: ScriptKey,
   pub(self) to: Option<ScriptKey>,
+  // the inscription was spent as fee or sent to an unspendable output.
+  pub(self) burned: bool,
   pub(self) op: Operation,
 }
 
-pub(super) struct Drc20Updater<'a, 'db, 'tx> {
-    drc20_token_info: &'a mut Table<'db, 'tx, &'static str, &'static [u8]>,
-    drc20_token_holder: &'a mut MultimapTable<'db, 'tx, &'static str, &'static str>,
-    drc20_token_balance: &'a mut Table<'db, 'tx, &'static str, &'static [u8]>,
-    drc20_inscribe_transfer: &'a mut Table<'db, 'tx, &'static [u8; 36], &'static [u8]>,
-    drc20_transferable_log: &'a mut Table<'db, 'tx, &'static str, &'static [u8]>,
-    inscription_id_to_inscription_entry: &'a Table<'db, 'tx, &'static InscriptionIdValue, InscriptionEntryValue>,
-    transaction_id_to_transaction: &'a mut Table<'db, 'tx, &'static TxidValue, &'static [u8]>,
+pub(super) struct Drc20Updater<'a> {
+    drc20_token_info: &'a mut dyn WritableTable<&'static str, &'static [u8]>,
+    drc20_tick_holder: &'a mut dyn WritableTable<&'static str, ()>,
+    drc20_tick_to_holder_count: &'a mut dyn WritableTable<&'static str, u64>,
+    drc20_tick_balance_holder: &'a mut dyn WritableTable<&'static str, ()>,
+    drc20_token_balance: &'a mut dyn WritableTable<&'static str, &'static [u8]>,
+    drc20_inscribe_transfer: &'a mut dyn WritableTable<&'static [u8; 36], &'static [u8]>,
+    drc20_transferable_log: &'a mut dyn WritableTable<&'static str, &'static [u8]>,
+    drc20_tick_activity: &'a mut dyn WritableTable<&'static str, &'static [u8]>,
+    drc20_height_to_state_hash: &'a mut dyn WritableTable<u32, &'static [u8; 32]>,
+    drc20_balance_history: &'a mut dyn WritableTable<&'static str, &'static [u8]>,
+    inscription_id_to_inscription_entry: &'a dyn WritableTable<&'static InscriptionIdValue, InscriptionEntryValue>,
+    transaction_id_to_transaction: &'a mut dyn WritableTable<&'static TxidValue, &'static [u8]>,
+    state_hasher: Option<StateHasher>,
+    height: u32,
 }
 
-impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
+impl<'a> Drc20Updater<'a> {
     pub(super) fn new(
-        drc20_token_info: &'a mut Table<'db, 'tx, &'static str, &'static [u8]>,
-        drc20_token_holder: &'a mut MultimapTable<'db, 'tx, &'static str, &'static str>,
-        drc20_token_balance: &'a mut Table<'db, 'tx, &'static str, &'static [u8]>,
-        drc20_inscribe_transfer: &'a mut Table<'db, 'tx, &'static [u8; 36], &'static [u8]>,
-        drc20_transferable_log: &'a mut Table<'db, 'tx, &'static str, &'static [u8]>,
-        inscription_id_to_inscription_entry: &'a Table<'db, 'tx, &'static InscriptionIdValue, InscriptionEntryValue>,
-        transaction_id_to_transaction: &'a mut Table<'db, 'tx, &'static TxidValue, &'static [u8]>,
+        drc20_token_info: &'a mut dyn WritableTable<&'static str, &'static [u8]>,
+        drc20_tick_holder: &'a mut dyn WritableTable<&'static str, ()>,
+        drc20_tick_to_holder_count: &'a mut dyn WritableTable<&'static str, u64>,
+        drc20_tick_balance_holder: &'a mut dyn WritableTable<&'static str, ()>,
+        drc20_token_balance: &'a mut dyn WritableTable<&'static str, &'static [u8]>,
+        drc20_inscribe_transfer: &'a mut dyn WritableTable<&'static [u8; 36], &'static [u8]>,
+        drc20_transferable_log: &'a mut dyn WritableTable<&'static str, &'static [u8]>,
+        drc20_tick_activity: &'a mut dyn WritableTable<&'static str, &'static [u8]>,
+        drc20_height_to_state_hash: &'a mut dyn WritableTable<u32, &'static [u8; 32]>,
+        drc20_balance_history: &'a mut dyn WritableTable<&'static str, &'static [u8]>,
+        inscription_id_to_inscription_entry: &'a dyn WritableTable<&'static InscriptionIdValue, InscriptionEntryValue>,
+        transaction_id_to_transaction: &'a mut dyn WritableTable<&'static TxidValue, &'static [u8]>,
     ) -> Result<Self> {
         Ok(Self {
             drc20_token_info,
-            drc20_token_holder,
+            drc20_tick_holder,
+            drc20_tick_to_holder_count,
+            drc20_tick_balance_holder,
             drc20_token_balance,
             drc20_inscribe_transfer,
             drc20_transferable_log,
+            drc20_tick_activity,
+            drc20_height_to_state_hash,
+            drc20_balance_history,
             inscription_id_to_inscription_entry,
             transaction_id_to_transaction,
+            state_hasher: None,
+            height: 0,
         })
     }
 
@@ -70,7 +94,19 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
     ) -> Result {
         let start = Instant::now();
         let mut messages_size = 0;
-        for (tx, txid) in block.txdata.iter() {
+
+        let height = u32::try_from(context.blockheight)?;
+        let previous = match height.checked_sub(1) {
+            Some(previous) => self
+                .drc20_height_to_state_hash
+                .get(&previous)?
+                .map(|hash| sha256::Hash::from_inner(*hash.value())),
+            None => None,
+        };
+        self.state_hasher = Some(StateHasher::new(height, previous));
+        self.height = height;
+
+        for (tx_index, (tx, txid)) in block.txdata.iter().enumerate() {
             // skip coinbase transaction.
             if tx
                 .input
@@ -84,9 +120,14 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
             // index inscription operations.
             if let Some(tx_operations) = operations.get(txid) {
                 // Resolve and execute messages.
-                let messages = self.resolve_message(tx, tx_operations)?;
-                for msg in messages.iter() {
-                    self.execute_message(context, msg)?;
+                let messages = self.resolve_message(tx, tx_operations, context.rules())?;
+                for (message, msg) in messages.iter().enumerate() {
+                    self.execute_message(
+                        context,
+                        u32::try_from(tx_index)?,
+                        u32::try_from(message)?,
+                        msg,
+                    )?;
                 }
                 messages_size += messages.len();
             }
@@ -98,6 +139,11 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
       messages_size,
       (Instant::now() - start).as_millis(),
     );
+
+        let state_hash = self.state_hasher.take().unwrap().finish();
+        self.drc20_height_to_state_hash
+            .insert(&height, &state_hash.into_inner())?;
+
         Ok(())
     }
 
@@ -105,15 +151,10 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
         &mut self,
         tx: &Transaction,
         operations: &[InscriptionOp],
+        rules: &Ruleset,
     ) -> Result<Vec<Message>> {
         let mut messages = Vec::new();
         let mut operation_iter = operations.iter().peekable();
-        let new_inscriptions: Vec<Inscription> = match Inscription::from_transactions(vec![tx.clone()]) {
-            ParsedInscription::None => { vec![] }
-            ParsedInscription::Partial => { vec![] }
-            ParsedInscription::Complete(inscription) => vec![inscription]
-        };
-
         for input in &tx.input {
             // "operations" is a list of all the operations in the current block, and they are ordered.
             // We just need to find the operation corresponding to the current transaction here.
@@ -125,7 +166,7 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
 
                 // Parse DRC20 message through inscription operation.
                 if let Some(msg) =
-                    Message::resolve(&mut self.drc20_inscribe_transfer, &new_inscriptions, operation)?
+                    Message::resolve(self.drc20_inscribe_transfer, operation, rules)?
                 {
                     messages.push(msg);
                     continue;
@@ -135,9 +176,17 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
         Ok(messages)
     }
 
-    pub fn execute_message(&mut self, context: BlockContext, msg: &Message) -> Result {
+    /// Executes `msg`, the `message`th DRC-20 message of transaction
+    /// `tx_index` in the block.
+    pub fn execute_message(
+        &mut self,
+        context: BlockContext,
+        tx_index: u32,
+        message: u32,
+        msg: &Message,
+    ) -> Result {
         let exec_msg = self.create_execution_message(msg, context.network)?;
-        let _ = match &exec_msg.op {
+        let result = match &exec_msg.op {
             Operation::Deploy(deploy) => {
                 Self::process_deploy(self, context.clone(), &exec_msg, deploy.clone())
             }
@@ -147,14 +196,89 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
             }
             Operation::Transfer(_) => Self::process_transfer(self, context.clone(), &exec_msg.clone()),
         };
+
+        let rule_version = context.rules().version;
+
+        if let Some(state_hasher) = &mut self.state_hasher {
+            state_hasher.receipt(exec_msg.inscription_id, rule_version, &result);
+        }
+
+        if let Ok(event @ (Event::Mint(_) | Event::Transfer(_) | Event::Burn(_))) = result {
+            Self::insert_tick_activity(
+                self,
+                context.blockheight,
+                tx_index,
+                message,
+                exec_msg.inscription_id,
+                rule_version,
+                event,
+            )?;
+        }
+
         Ok(())
     }
 
+    /// Runs `op` as if `from` had inscribed it to `to` (or sent the transfer
+    /// inscription `inscription_id` to `to`) in `context`, returning the event
+    /// it would emit or the error it would be rejected with.
+    pub(crate) fn simulate(
+        &mut self,
+        context: BlockContext,
+        inscription_id: InscriptionId,
+        op: Operation,
+        from: ScriptKey,
+        to: ScriptKey,
+    ) -> Result<Result<Event, DRC20Error>> {
+        self.height = u32::try_from(context.blockheight)?;
+
+        let satpoint = SatPoint {
+            outpoint: OutPoint {
+                txid: inscription_id.txid,
+                vout: 0,
+            },
+            offset: 0,
+        };
+
+        let msg = ExecutionMessage {
+            txid: inscription_id.txid,
+            inscription_id,
+            // not part of any event, and unknown until the inscription is revealed
+            inscription_number: 0,
+            old_satpoint: satpoint,
+            new_satpoint: satpoint,
+            from,
+            to: Some(to),
+            burned: false,
+            op: op.clone(),
+        };
+
+        let result = match op {
+            Operation::Deploy(deploy) => Self::process_deploy(self, context, &msg, deploy),
+            Operation::Mint(mint) => Self::process_mint(self, context, &msg, mint),
+            Operation::InscribeTransfer(transfer) => {
+                Self::process_inscribe_transfer(self, context, &msg, transfer)
+            }
+            Operation::Transfer(_) => Self::process_transfer(self, context, &msg),
+        };
+
+        match result {
+            Ok(event) => Ok(Ok(event)),
+            Err(errors::Error::DRC20Error(error)) => Ok(Err(error)),
+            Err(errors::Error::LedgerError(error)) => Err(error.into()),
+        }
+    }
+
     pub fn create_execution_message(
         &mut self,
         msg: &Message,
         network: Network,
     ) -> Result<ExecutionMessage> {
+        let to_script = if msg.sat_in_outputs {
+            Some(Self::get_script_on_satpoint(self, msg.new_satpoint.unwrap())?)
+        } else {
+            None
+        };
+
         Ok(ExecutionMessage {
             txid: msg.txid,
             inscription_id: msg.inscription_id,
@@ -164,14 +288,12 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
                 .new_satpoint
                 .ok_or(anyhow!("new satpoint cannot be None"))?,
             from: Self::get_script_key_on_satpoint(self, msg.old_satpoint, network)?,
-            to: if msg.sat_in_outputs {
-                Some(Self::get_script_key_on_satpoint(self,
-                    msg.new_satpoint.unwrap(),
-                    network,
-                )?)
-            } else {
-                None
-            },
+            to: to_script
+                .as_ref()
+                .map(|script| ScriptKey::from_script(script, network)),
+            burned: to_script
+                .as_ref()
+                .map_or(true, |script| script.is_provably_unspendable()),
             op: msg.op.clone(),
         })
     }
@@ -185,7 +307,7 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
     // ignore inscribe inscription to coinbase.
     let to_script_key = msg.to.clone().ok_or(DRC20Error::InscribeToCoinbase)?;
 
-    let tick = deploy.tick.parse::<Tick>()?;
+    let tick = Self::parse_tick(&deploy.tick, context.rules())?;
 
     if let Some(stored_tick_info) = Self::get_token_info(self, &tick).map_err(|e| LedgerError(e))? {
       return Err(errors::Error::DRC20Error(DRC20Error::DuplicateTick(
@@ -193,9 +315,10 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
       )));
     }
 
-    let dec = Num::from_str(&deploy.decimals.map_or(MAX_DECIMAL_WIDTH.to_string(), |v| v))?
+    let max_decimal_width = context.rules().max_decimal_width;
+    let dec = Num::from_str(&deploy.decimals.map_or(max_decimal_width.to_string(), |v| v))?
       .checked_to_u8()?;
-    if dec > MAX_DECIMAL_WIDTH {
+    if dec > max_decimal_width {
       return Err(errors::Error::DRC20Error(DRC20Error::DecimalsTooLarge(dec)));
     }
     let base = BIGDECIMAL_TEN.checked_powu(u64::from(dec))?;
@@ -228,6 +351,7 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
       limit_per_mint: limit,
       decimal: dec,
       minted: 0u128,
+      burned: 0u128,
       deploy_by: to_script_key.clone(),
       deployed_number: context.blockheight,
       latest_mint_number: context.blockheight,
@@ -255,7 +379,7 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
     // ignore inscribe inscription to coinbase.
     let to_script_key = msg.to.clone().ok_or(DRC20Error::InscribeToCoinbase)?;
 
-    let tick = mint.tick.parse::<Tick>()?;
+    let tick = Self::parse_tick(&mint.tick, context.rules())?;
 
     let token_info = Self::get_token_info(self, &tick)
       .map_err(|e| LedgerError(e))?
@@ -333,14 +457,14 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
 
   fn process_inscribe_transfer(
     &mut self,
-    _context: BlockContext,
+    context: BlockContext,
     msg: &ExecutionMessage,
     transfer: Transfer,
   ) -> Result<Event, errors::Error<DRC20Error>> {
     // ignore inscribe inscription to coinbase.
     let to_script_key = msg.to.clone().ok_or(DRC20Error::InscribeToCoinbase)?;
 
-    let tick = transfer.tick.parse::<Tick>()?;
+    let tick = Self::parse_tick(&transfer.tick, context.rules())?;
 
     let token_info = Self::get_token_info(self, &tick)
       .map_err(|e| LedgerError(e))?
@@ -411,12 +535,22 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
     }))
   }
 
+  fn parse_tick(tick: &str, rules: &Ruleset) -> Result<Tick, errors::Error<DRC20Error>> {
+    if tick.len() != rules.tick_byte_count {
+      return Err(errors::Error::DRC20Error(DRC20Error::InvalidTickLen(
+        tick.to_string(),
+      )));
+    }
+
+    Ok(tick.parse::<Tick>()?)
+  }
+
   fn process_transfer(
     &mut self,
-    _context: BlockContext,
+    context: BlockContext,
     msg: &ExecutionMessage,
   ) -> Result<Event, errors::Error<DRC20Error>> {
-    let mut transferable = Self::get_transferable_by_id(self, &msg.from, &msg.inscription_id)
+    let transferable = Self::get_transferable_by_id(self, &msg.from, &msg.inscription_id)
       .map_err(|e| LedgerError(e))?
       .ok_or(DRC20Error::TransferableNotFound(msg.inscription_id))?;
     let amt = Into::<Num>::into(transferable.amount);
@@ -449,30 +583,56 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
 
     Self::update_token_balance(self, &msg.from, from_balance).map_err(|e| LedgerError(e))?;
 
-    // redirect receiver to sender if transfer to coinbase.
-    let mut out_msg = None;
-
-    let to_script_key = if msg.to.clone().is_none() {
-      out_msg =
-        Some("redirect receiver to sender, reason: transfer inscription to coinbase".to_string());
-      msg.from.clone()
+    // transfers spent as fee or sent to an unspendable output can never move again, so rulesets
+    // with burns burn them. Without, transfers spent as fee go back to the sender.
+    let to_script_key = if context.rules().burns {
+      (!msg.burned).then(|| msg.to.clone()).flatten()
     } else {
-      msg.to.clone().unwrap()
+      Some(msg.to.clone().unwrap_or_else(|| msg.from.clone()))
     };
 
-    // update to key balance.
-    let mut to_balance = Self::get_balance(self, &to_script_key, &tick)
-      .map_err(|e| LedgerError(e))?
-      .map_or(Balance::new(&tick), |v| v);
-
-    let to_overall = Into::<Num>::into(to_balance.overall_balance);
-    to_balance.overall_balance = to_overall.checked_add(&amt)?.checked_to_u128()?;
-
-    Self::update_token_balance(self, &to_script_key, to_balance).map_err(|e| LedgerError(e))?;
-
-    Self::insert_token_holder(self, &to_script_key, tick.clone()).map_err(|e| LedgerError(e))?;
+    let event = match &to_script_key {
+      Some(to_script_key) => {
+        // update to key balance.
+        let mut to_balance = Self::get_balance(self, to_script_key, &tick)
+          .map_err(|e| LedgerError(e))?
+          .map_or(Balance::new(&tick), |v| v);
+
+        let to_overall = Into::<Num>::into(to_balance.overall_balance);
+        to_balance.overall_balance = to_overall.checked_add(&amt)?.checked_to_u128()?;
+
+        Self::update_token_balance(self, to_script_key, to_balance)
+          .map_err(|e| LedgerError(e))?;
+
+        Self::insert_token_holder(self, to_script_key, tick.clone())
+          .map_err(|e| LedgerError(e))?;
+
+        Event::Transfer(TransferEvent {
+          txid: None,
+          from: msg.from.clone(),
+          to: to_script_key.clone(),
+          vout: msg.new_satpoint.outpoint.vout,
+          tick: token_info.tick,
+          amount: amt.checked_to_u128()?,
+        })
+      }
+      None => {
+        let burned = Into::<Num>::into(token_info.burned)
+          .checked_add(&amt)?
+          .checked_to_u128()?;
+
+        Self::update_burned_token_info(self, &tick, burned).map_err(|e| LedgerError(e))?;
+
+        Event::Burn(BurnEvent {
+          txid: None,
+          from: msg.from.clone(),
+          tick: token_info.tick,
+          amount: amt.checked_to_u128()?,
+        })
+      }
+    };
 
-    if from_overall == 0 && msg.from != to_script_key {
+    if from_overall == 0 && Some(&msg.from) != to_script_key.as_ref() {
       Self::remove_token_holder(self, &msg.from, tick.clone()).map_err(|e| LedgerError(e))?;
     }
 
@@ -482,14 +642,7 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
     Self::remove_inscribe_transfer_inscription(self, msg.inscription_id)
       .map_err(|e| LedgerError(e))?;
 
-    Ok(Event::Transfer(TransferEvent {
-      txid: None,
-      from: msg.clone().from,
-      to: to_script_key,
-      vout: msg.new_satpoint.outpoint.vout,
-      tick: token_info.tick,
-      amount: amt.checked_to_u128()?,
-    }))
+    Ok(event)
   }
 
     fn insert_transferable(
@@ -524,9 +677,8 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
         Ok(
             self.drc20_transferable_log
                 .range(min_script_tick_key(script).as_str()..max_script_tick_key(script).as_str())?
-                .flat_map(|result| {
-                    result.map(|(_, v)| rmp_serde::from_slice::<TransferableLog>(v.value()).unwrap())
-                })
+                .into_iter()
+                .map(|(_, v)| rmp_serde::from_slice::<TransferableLog>(v.value()).unwrap())
                 .collect(),
         )
     }
@@ -542,9 +694,8 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
                     min_script_tick_id_key(script, tick).as_str()
                         ..max_script_tick_id_key(script, tick).as_str(),
                 )?
-                .flat_map(|result| {
-                    result.map(|(_, v)| rmp_serde::from_slice::<TransferableLog>(v.value()).unwrap())
-                })
+                .into_iter()
+                .map(|(_, v)| rmp_serde::from_slice::<TransferableLog>(v.value()).unwrap())
                 .collect(),
         )
     }
@@ -588,9 +739,44 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
         script_key: &ScriptKey,
         new_balance: Balance,
     ) -> Result<(), redb::Error> {
+        if let Some(state_hasher) = &mut self.state_hasher {
+            state_hasher.balance(script_key, &new_balance);
+        }
+
+        let old_balance = self
+            .get_balance(script_key, &new_balance.tick)?
+            .map(|balance| balance.overall_balance)
+            .unwrap_or_default();
+
+        if old_balance != new_balance.overall_balance {
+            let script = script_key.to_string();
+
+            if old_balance > 0 {
+                self.drc20_tick_balance_holder.remove(
+                    tick_balance_holder_key(&new_balance.tick, old_balance, &script).as_str(),
+                )?;
+            }
+
+            if new_balance.overall_balance > 0 {
+                self.drc20_tick_balance_holder.insert(
+                    tick_balance_holder_key(&new_balance.tick, new_balance.overall_balance, &script)
+                        .as_str(),
+                    (),
+                )?;
+            }
+        }
+
+        let balance = bincode::serialize(&new_balance).unwrap();
+
         self.drc20_token_balance.insert(
             script_tick_key(script_key, &new_balance.tick).as_str(),
-            bincode::serialize(&new_balance).unwrap().as_slice(),
+            balance.as_slice(),
+        )?;
+
+        // Later changes in the same block overwrite this version.
+        self.drc20_balance_history.insert(
+            balance_history_key(&new_balance.tick, script_key, self.height).as_str(),
+            balance.as_slice(),
         )?;
         Ok(())
     }
@@ -637,6 +823,24 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
         Ok(())
     }
 
+    fn update_burned_token_info(
+        &mut self,
+        tick: &Tick,
+        burned_amt: u128,
+    ) -> Result<(), redb::Error> {
+        let mut info = Self::get_token_info(self, tick)?.ok_or_else(|| {
+            redb::Error::Corrupted(format!("burned tick {} has no token info", tick.as_str()))
+        })?;
+
+        info.burned = burned_amt;
+
+        self.drc20_token_info.insert(
+            tick.to_lowercase().hex().as_str(),
+            bincode::serialize(&info).unwrap().as_slice(),
+        )?;
+        Ok(())
+    }
+
     pub(super) fn get_token_info(
         &self,
         tick: &Tick
@@ -653,11 +857,17 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
         satpoint: SatPoint,
         network: Network,
     ) -> Result<ScriptKey> {
+        Ok(ScriptKey::from_script(
+            &Self::get_script_on_satpoint(self, satpoint)?,
+            network,
+        ))
+    }
+
+    fn get_script_on_satpoint(&self, satpoint: SatPoint) -> Result<Script> {
         if let Some(transaction) = self.transaction_id_to_transaction
             .get(&satpoint.outpoint.txid.store())? {
             let tx: Transaction = consensus::encode::deserialize(transaction.value())?;
-            let pub_key = tx.output[satpoint.outpoint.vout as usize].script_pubkey.clone();
-            Ok(ScriptKey::from_script(&pub_key, network))
+            Ok(tx.output[satpoint.outpoint.vout as usize].script_pubkey.clone())
         } else {
             Err(anyhow!(
                 "failed to get tx out! error: outpoint {} not found",
@@ -691,18 +901,69 @@ impl<'a, 'db, 'tx> Drc20Updater<'a, 'db, 'tx> {
     }
 
     fn remove_token_holder(&mut self, script_key: &ScriptKey, tick: Tick) -> std::result::Result<(), redb::Error> {
-        self.drc20_token_holder.remove(
-            tick.to_lowercase().hex().as_str(),
-            script_key.to_string().as_str(),
+        if self.drc20_tick_holder
+            .remove(tick_holder_key(&tick, script_key).as_str())?
+            .is_some()
+        {
+            self.update_holder_count(&tick, -1)?;
+        }
+        Ok(())
+    }
+
+    fn insert_tick_activity(
+        &mut self,
+        height: u64,
+        tx_index: u32,
+        message: u32,
+        inscription_id: InscriptionId,
+        rule_version: u32,
+        event: Event,
+    ) -> Result<(), redb::Error> {
+        let tick = match &event {
+            Event::Mint(mint) => mint.tick.clone(),
+            Event::Transfer(transfer) => transfer.tick.clone(),
+            Event::Burn(burn) => burn.tick.clone(),
+            _ => return Ok(()),
+        };
+        let activity = TickActivity {
+            height,
+            inscription_id,
+            event,
+            rule_version,
+        };
+        self.drc20_tick_activity.insert(
+            tick_activity_key(&tick, height, tx_index, message).as_str(),
+            bincode::serialize(&activity).unwrap().as_slice(),
         )?;
         Ok(())
     }
 
     fn insert_token_holder(&mut self, script_key: &ScriptKey, tick: Tick) -> Result<(), redb::Error> {
-        self.drc20_token_holder.insert(
-            tick.to_lowercase().hex().as_str(),
-            script_key.to_string().as_str(),
-        )?;
+        if self.drc20_tick_holder
+            .insert(tick_holder_key(&tick, script_key).as_str(), ())?
+            .is_none()
+        {
+            self.update_holder_count(&tick, 1)?;
+        }
+        Ok(())
+    }
+
+    /// Keeps `DRC20_TICK_TO_HOLDER_COUNT` in step with `DRC20_TICK_HOLDER`, so
+    /// holder counts are a single lookup.
+    fn update_holder_count(&mut self, tick: &Tick, change: i64) -> Result<(), redb::Error> {
+        let key = tick.to_lowercase().hex();
+        let count = self
+            .drc20_tick_to_holder_count
+            .get(key.as_str())?
+            .map(|count| count.value())
+            .unwrap_or_default();
+        let count = count.checked_add_signed(change).ok_or_else(|| {
+            redb::Error::Corrupted(format!(
+                "holder count {count} of tick {} can't change by {change}",
+                tick.as_str()
+            ))
+        })?;
+        self.drc20_tick_to_holder_count.insert(key.as_str(), count)?;
         Ok(())
     }
 }
@@ -22,15 +22,13 @@ struct Allocation {
 
 pub(super) struct DuneUpdater<'a, 'db, 'tx> {
   height: u32,
+  etchings: Vec<(InscriptionId, Dune)>,
   id_to_entry: &'a mut Table<'db, 'tx, DuneIdValue, DuneEntryValue>,
-  inscription_id_to_inscription_entry:
-  &'a Table<'db, 'tx, &'static InscriptionIdValue, InscriptionEntryValue>,
-  inscription_id_to_dune: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, u128>,
   minimum: Dune,
   outpoint_to_balances: &'a mut Table<'db, 'tx, &'static OutPointValue, &'static [u8]>,
   dune_to_id: &'a mut Table<'db, 'tx, u128, DuneIdValue>,
   dunes: u64,
-  statistic_to_count: &'a mut Table<'db, 'tx, u64, u64>,
+  reserved_dunes: u64,
   timestamp: u32,
 }
 
@@ -39,15 +37,8 @@ impl<'a, 'db, 'tx> DuneUpdater<'a, 'db, 'tx> {
     height: u32,
     outpoint_to_balances: &'a mut Table<'db, 'tx, &'static OutPointValue, &'static [u8]>,
     id_to_entry: &'a mut Table<'db, 'tx, DuneIdValue, DuneEntryValue>,
-    inscription_id_to_inscription_entry: &'a Table<
-      'db,
-      'tx,
-      &'static InscriptionIdValue,
-      InscriptionEntryValue,
-    >,
-    inscription_id_to_dune: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, u128>,
     dune_to_id: &'a mut Table<'db, 'tx, u128, DuneIdValue>,
-    statistic_to_count: &'a mut Table<'db, 'tx, u64, u64>,
+    statistic_to_count: &Table<'db, 'tx, u64, u64>,
     timestamp: u32,
     minimum: Dune,
   ) -> Result<Self> {
@@ -55,20 +46,57 @@ impl<'a, 'db, 'tx> DuneUpdater<'a, 'db, 'tx> {
         .get(&Statistic::Dunes.into())?
         .map(|x| x.value())
         .unwrap_or(0);
+    let reserved_dunes = statistic_to_count
+        .get(&Statistic::ReservedDunes.into())?
+        .map(|x| x.value())
+        .unwrap_or(0);
     Ok(Self {
+      etchings: Vec::new(),
       height,
       id_to_entry,
       minimum,
       outpoint_to_balances,
-      inscription_id_to_inscription_entry,
-      inscription_id_to_dune,
       dune_to_id,
       dunes,
-      statistic_to_count,
+      reserved_dunes,
       timestamp,
     })
   }
 
+  /// Writes what the block changed outside the dune tables. Dunes are indexed
+  /// without access to inscriptions or statistics, so they can run alongside
+  /// the inscription updater, and this runs once both are done.
+  pub(super) fn finish(
+    self,
+    statistic_to_count: &mut Table<'db, 'tx, u64, u64>,
+    inscription_id_to_inscription_entry: &Table<
+      'db,
+      'tx,
+      &'static InscriptionIdValue,
+      InscriptionEntryValue,
+    >,
+    inscription_id_to_dune: &mut Table<'db, 'tx, &'static InscriptionIdValue, u128>,
+  ) -> Result {
+    if self.dunes > 0 {
+      statistic_to_count.insert(&Statistic::Dunes.into(), self.dunes)?;
+    }
+
+    if self.reserved_dunes > 0 {
+      statistic_to_count.insert(&Statistic::ReservedDunes.into(), self.reserved_dunes)?;
+    }
+
+    for (inscription_id, dune) in self.etchings {
+      if inscription_id_to_inscription_entry
+          .get(&inscription_id.store())?
+          .is_some()
+      {
+        inscription_id_to_dune.insert(&inscription_id.store(), dune.0)?;
+      }
+    }
+
+    Ok(())
+  }
+
   pub(super) fn index_dunes(&mut self, index: usize, tx: &Transaction, txid: Txid) -> Result<()> {
     let dunestone = Dunestone::from_transaction(tx);
 
@@ -126,16 +154,8 @@ impl<'a, 'db, 'tx> DuneUpdater<'a, 'db, 'tx> {
             let dune = if let Some(dune) = etching.dune {
               dune
             } else {
-              let reserved_dunes = self
-                  .statistic_to_count
-                  .get(&Statistic::ReservedDunes.into())?
-                  .map(|entry| entry.value())
-                  .unwrap_or_default();
-
-              self
-                  .statistic_to_count
-                  .insert(&Statistic::ReservedDunes.into(), reserved_dunes + 1)?;
-
+              let reserved_dunes = self.reserved_dunes;
+              self.reserved_dunes += 1;
               Dune::reserved(reserved_dunes.into())
             };
 
@@ -311,10 +331,6 @@ impl<'a, 'db, 'tx> DuneUpdater<'a, 'db, 'tx> {
         let number = self.dunes;
         self.dunes += 1;
 
-        self
-            .statistic_to_count
-            .insert(&Statistic::Dunes.into(), self.dunes)?;
-
         self.id_to_entry.insert(
           id.store(),
           DuneEntry {
@@ -336,17 +352,7 @@ impl<'a, 'db, 'tx> DuneUpdater<'a, 'db, 'tx> {
               .store(),
         )?;
 
-        let inscription_id = InscriptionId { txid, index: 0 };
-
-        if self
-            .inscription_id_to_inscription_entry
-            .get(&inscription_id.store())?
-            .is_some()
-        {
-          self
-              .inscription_id_to_dune
-              .insert(&inscription_id.store(), dune.0)?;
-        }
+        self.etchings.push((InscriptionId { txid, index: 0 }, dune));
       }
     }
 
@@ -19,60 +19,68 @@ enum Origin {
   Old(SatPoint),
 }
 
-pub(super) struct InscriptionUpdater<'a, 'db, 'tx> {
+pub(super) struct InscriptionUpdater<'a, 'tx> {
   flotsam: Vec<Flotsam>,
   pub(super) operations: HashMap<Txid, Vec<InscriptionOp>>,
   height: u32,
-  id_to_satpoint: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, &'static SatPointValue>,
-  id_to_txids: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, &'static [u8]>,
-  txid_to_tx: &'a mut Table<'db, 'tx, &'static [u8], &'static [u8]>,
-  partial_txid_to_txids: &'a mut Table<'db, 'tx, &'static [u8], &'static [u8]>,
+  id_to_satpoint: &'a mut dyn WritableTable<&'static InscriptionIdValue, &'static SatPointValue>,
+  id_to_txids: &'a mut dyn WritableTable<&'static InscriptionIdValue, &'static [u8]>,
+  txid_to_tx: &'a mut dyn WritableTable<&'static [u8], &'static [u8]>,
+  partial_txid_to_txids: &'a mut dyn WritableTable<&'static [u8], &'static [u8]>,
+  id_to_content_hash:
+    &'a mut dyn WritableTable<&'static InscriptionIdValue, &'static ContentHashValue>,
+  content_hash_to_content: &'a mut dyn WritableTable<&'static ContentHashValue, &'static [u8]>,
   value_receiver: &'a mut Receiver<u64>,
   index_transactions: bool,
   transaction_buffer: Vec<u8>,
-  transaction_id_to_transaction: &'a mut Table<'db, 'tx, &'static TxidValue, &'static [u8]>,
-  id_to_entry: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, InscriptionEntryValue>,
+  transaction_id_to_transaction: &'a mut dyn WritableTable<&'static TxidValue, &'static [u8]>,
+  id_to_entry: &'a mut dyn WritableTable<&'static InscriptionIdValue, InscriptionEntryValue>,
   lost_sats: u64,
   next_number: u64,
-  number_to_id: &'a mut Table<'db, 'tx, u64, &'static InscriptionIdValue>,
-  outpoint_to_value: &'a mut Table<'db, 'tx, &'static OutPointValue, u64>,
-  address_to_outpoint: &'a mut MultimapTable<'db, 'tx, &'static [u8], &'static OutPointValue>,
+  number_to_id: &'a mut dyn WritableTable<u64, &'static InscriptionIdValue>,
+  outpoint_to_value: &'a mut dyn WritableTable<&'static OutPointValue, u64>,
+  address_tables: &'a mut AddressTables<'tx>,
   reward: u64,
-  sat_to_inscription_id: &'a mut Table<'db, 'tx, u64, &'static InscriptionIdValue>,
-  satpoint_to_id: &'a mut Table<'db, 'tx, &'static SatPointValue, &'static InscriptionIdValue>,
+  subsidy: u64,
+  sat_to_inscription_id: &'a mut dyn WritableTable<u64, &'static InscriptionIdValue>,
+  satpoint_to_id: &'a mut dyn WritableTable<&'static SatPointValue, &'static InscriptionIdValue>,
   timestamp: u32,
   value_cache: &'a mut HashMap<OutPoint, OutPointMapValue>,
+  utxos_complete: bool,
   chain: Chain,
 }
 
-impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
+impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
   pub(super) fn new(
     height: u32,
-    id_to_satpoint: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, &'static SatPointValue>,
-    id_to_txids: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, &'static [u8]>,
-    txid_to_tx: &'a mut Table<'db, 'tx, &'static [u8], &'static [u8]>,
-    partial_txid_to_txids: &'a mut Table<'db, 'tx, &'static [u8], &'static [u8]>,
+    id_to_satpoint: &'a mut dyn WritableTable<&'static InscriptionIdValue, &'static SatPointValue>,
+    id_to_txids: &'a mut dyn WritableTable<&'static InscriptionIdValue, &'static [u8]>,
+    txid_to_tx: &'a mut dyn WritableTable<&'static [u8], &'static [u8]>,
+    partial_txid_to_txids: &'a mut dyn WritableTable<&'static [u8], &'static [u8]>,
+    id_to_content_hash: &'a mut dyn WritableTable<
+      &'static InscriptionIdValue,
+      &'static ContentHashValue,
+    >,
+    content_hash_to_content: &'a mut dyn WritableTable<&'static ContentHashValue, &'static [u8]>,
     value_receiver: &'a mut Receiver<u64>,
     index_transactions: bool,
     transaction_buffer: Vec<u8>,
-    transaction_id_to_transaction: &'a mut Table<'db, 'tx, &'static TxidValue, &'static [u8]>,
-    id_to_entry: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, InscriptionEntryValue>,
+    transaction_id_to_transaction: &'a mut dyn WritableTable<&'static TxidValue, &'static [u8]>,
+    id_to_entry: &'a mut dyn WritableTable<&'static InscriptionIdValue, InscriptionEntryValue>,
     lost_sats: u64,
-    number_to_id: &'a mut Table<'db, 'tx, u64, &'static InscriptionIdValue>,
-    outpoint_to_value: &'a mut Table<'db, 'tx, &'static OutPointValue, u64>,
-    address_to_outpoint: &'a mut MultimapTable<'db, 'tx, &'static [u8], &'static OutPointValue>,
-    sat_to_inscription_id: &'a mut Table<'db, 'tx, u64, &'static InscriptionIdValue>,
-    satpoint_to_id: &'a mut Table<'db, 'tx, &'static SatPointValue, &'static InscriptionIdValue>,
+    number_to_id: &'a mut dyn WritableTable<u64, &'static InscriptionIdValue>,
+    outpoint_to_value: &'a mut dyn WritableTable<&'static OutPointValue, u64>,
+    address_tables: &'a mut AddressTables<'tx>,
+    sat_to_inscription_id: &'a mut dyn WritableTable<u64, &'static InscriptionIdValue>,
+    satpoint_to_id: &'a mut dyn WritableTable<&'static SatPointValue, &'static InscriptionIdValue>,
     timestamp: u32,
     value_cache: &'a mut HashMap<OutPoint, OutPointMapValue>,
+    utxos_complete: bool,
     chain: Chain,
   ) -> Result<Self> {
     let next_number = number_to_id
-      .iter()?
-      .rev()
-      .map(|result| result.map(|(number, _id)| number.value() + 1))
-      .next()
-      .transpose()?
+      .last()?
+      .map(|(number, _id)| number.value() + 1)
       .unwrap_or(0);
 
     Ok(Self {
@@ -83,6 +91,8 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
       id_to_txids,
       txid_to_tx,
       partial_txid_to_txids,
+      id_to_content_hash,
+      content_hash_to_content,
       value_receiver,
       index_transactions,
       transaction_buffer,
@@ -92,16 +102,35 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
       next_number,
       number_to_id,
       outpoint_to_value,
-      address_to_outpoint,
+      address_tables,
       reward: Height(height).subsidy(),
+      subsidy: Height(height).subsidy(),
       sat_to_inscription_id,
       satpoint_to_id,
       timestamp,
       value_cache,
+      utxos_complete,
       chain,
     })
   }
 
+  /// Fees paid by the transactions indexed so far.
+  pub(super) fn fees(&self) -> u64 {
+    self.reward - self.subsidy
+  }
+
+  /// Replaces the scheduled subsidy, which fees and flotsam are counted on top
+  /// of, with the one the coinbase is counted with. Must be called before the
+  /// coinbase is indexed.
+  pub(super) fn set_subsidy(&mut self, subsidy: u64) {
+    for flotsam in &mut self.flotsam {
+      flotsam.offset = flotsam.offset - self.subsidy + subsidy;
+    }
+
+    self.reward = self.reward - self.subsidy + subsidy;
+    self.subsidy = subsidy;
+  }
+
   pub(super) fn index_transaction_inscriptions(
     &mut self,
     tx: &Transaction,
@@ -123,25 +152,31 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
     let mut input_value = 0;
     for tx_in in &tx.input {
       if tx_in.previous_output.is_null() {
-        input_value += Height(self.height).subsidy();
+        input_value += self.subsidy;
       } else {
-        let result: Result<(), _> = (|| {
-          for result in Index::inscriptions_on_output(self.satpoint_to_id, tx_in.previous_output)? {
-            let (old_satpoint, inscription_id) = result?;
-            inscriptions.push(Flotsam {
-              txid,
-              offset: input_value + old_satpoint.offset,
-              old_satpoint,
-              inscription_id,
-              origin: Origin::Old(old_satpoint),
-            });
-          }
-          Ok(())
-        })();
+        let start = SatPoint {
+          outpoint: tx_in.previous_output,
+          offset: 0,
+        }
+        .store();
 
-        if let Err(e) = result {
-          // Propagate the error e
-          return Err(e);
+        let end = SatPoint {
+          outpoint: tx_in.previous_output,
+          offset: u64::MAX,
+        }
+        .store();
+
+        for (old_satpoint, inscription_id) in
+          self.satpoint_to_id.range::<&SatPointValue>(&start..=&end)?
+        {
+          let old_satpoint = SatPoint::load(*old_satpoint.value());
+          inscriptions.push(Flotsam {
+            txid,
+            offset: input_value + old_satpoint.offset,
+            old_satpoint,
+            inscription_id: InscriptionId::load(*inscription_id.value()),
+            origin: Origin::Old(old_satpoint),
+          });
         }
 
         input_value += if let Some(map) = self.value_cache.remove(&tx_in.previous_output) {
@@ -150,22 +185,10 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
           .outpoint_to_value
           .remove(&tx_in.previous_output.store())?
         {
-          if let Some(transaction) = self
-            .transaction_id_to_transaction
-            .get(&tx_in.previous_output.txid.store())?
-          {
-            let tx: Transaction = consensus::encode::deserialize(transaction.value())?;
-            let output = tx.output[tx_in.previous_output.vout as usize].clone();
-            if let Some(address_from_script) =
-              self.chain.address_from_script(&output.script_pubkey).ok()
-            {
-              self.address_to_outpoint.remove(
-                address_from_script.to_string().as_bytes(),
-                &tx_in.previous_output.store(),
-              )?;
-            }
-          }
+          self.address_tables.remove(tx_in.previous_output)?;
           map.value()
+        } else if self.utxos_complete {
+          bail!("missing value for output {}", tx_in.previous_output);
         } else {
           self.value_receiver.blocking_recv().ok_or_else(|| {
             anyhow!(
@@ -253,6 +276,13 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
             .id_to_txids
             .insert(&inscription_id, txids_vec.as_slice())?;
 
+          Index::insert_inscription_content(
+            self.id_to_content_hash,
+            self.content_hash_to_content,
+            &inscription_id,
+            &_inscription,
+          )?;
+
           let og_inscription_id = InscriptionId {
             txid: Txid::from_slice(&txids_vec[0..32]).unwrap(),
             index: 0,
@@ -337,7 +367,7 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
           vout: vout.try_into().unwrap(),
           txid,
         },
-        (tx_out.clone().value, address),
+        (tx_out.clone().value, address, self.height),
       );
     }
 
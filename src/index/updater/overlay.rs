@@ -0,0 +1,481 @@
+use {
+  super::*,
+  redb::{ReadOnlyTable, RedbKey, RedbValue},
+  std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+  },
+};
+
+/// The table operations the inscription and DRC-20 updaters use, so they can
+/// write to the index or to an `Overlay` of it. Keys and values are passed as
+/// bytes to keep the trait object safe; the typed methods are on
+/// `dyn WritableTable`.
+pub(crate) trait WritableTable<K: RedbKey + 'static, V: RedbValue + 'static> {
+  fn get_bytes(&self, key: &[u8]) -> Result<Option<Owned<V>>, StorageError>;
+
+  fn insert_bytes(&mut self, key: &[u8], value: &[u8]) -> Result<Option<Owned<V>>, StorageError>;
+
+  fn remove_bytes(&mut self, key: &[u8]) -> Result<Option<Owned<V>>, StorageError>;
+
+  fn range_bytes(
+    &self,
+    start: Bound<&[u8]>,
+    end: Bound<&[u8]>,
+  ) -> Result<Vec<OwnedEntry<K, V>>, StorageError>;
+
+  fn last_bytes(&self) -> Result<Option<OwnedEntry<K, V>>, StorageError>;
+}
+
+impl<K: RedbKey + 'static, V: RedbValue + 'static> dyn WritableTable<K, V> + '_ {
+  pub(crate) fn get<'k>(
+    &self,
+    key: impl Borrow<K::SelfType<'k>>,
+  ) -> Result<Option<Owned<V>>, StorageError> {
+    self.get_bytes(K::as_bytes(key.borrow()).as_ref())
+  }
+
+  pub(crate) fn insert<'k, 'v>(
+    &mut self,
+    key: impl Borrow<K::SelfType<'k>>,
+    value: impl Borrow<V::SelfType<'v>>,
+  ) -> Result<Option<Owned<V>>, StorageError> {
+    self.insert_bytes(
+      K::as_bytes(key.borrow()).as_ref(),
+      V::as_bytes(value.borrow()).as_ref(),
+    )
+  }
+
+  pub(crate) fn remove<'k>(
+    &mut self,
+    key: impl Borrow<K::SelfType<'k>>,
+  ) -> Result<Option<Owned<V>>, StorageError> {
+    self.remove_bytes(K::as_bytes(key.borrow()).as_ref())
+  }
+
+  /// Every entry in `range`, in key order.
+  pub(crate) fn range<'k, KR: Borrow<K::SelfType<'k>>>(
+    &self,
+    range: impl RangeBounds<KR>,
+  ) -> Result<Vec<OwnedEntry<K, V>>, StorageError> {
+    let start = bound(range.start_bound()).map(K::as_bytes);
+    let end = bound(range.end_bound()).map(K::as_bytes);
+
+    self.range_bytes(bytes(&start), bytes(&end))
+  }
+
+  pub(crate) fn last(&self) -> Result<Option<OwnedEntry<K, V>>, StorageError> {
+    self.last_bytes()
+  }
+}
+
+/// A bound with `Bound::map`, which is newer than the minimum Rust version.
+enum KeyBound<T> {
+  Included(T),
+  Excluded(T),
+  Unbounded,
+}
+
+impl<T> KeyBound<T> {
+  fn map<U>(self, f: impl FnOnce(T) -> U) -> KeyBound<U> {
+    match self {
+      Self::Included(key) => KeyBound::Included(f(key)),
+      Self::Excluded(key) => KeyBound::Excluded(f(key)),
+      Self::Unbounded => KeyBound::Unbounded,
+    }
+  }
+}
+
+fn bound<T, KR: Borrow<T>>(bound: Bound<&KR>) -> KeyBound<&T> {
+  match bound {
+    Bound::Included(key) => KeyBound::Included(key.borrow()),
+    Bound::Excluded(key) => KeyBound::Excluded(key.borrow()),
+    Bound::Unbounded => KeyBound::Unbounded,
+  }
+}
+
+fn bytes<B: AsRef<[u8]>>(bound: &KeyBound<B>) -> Bound<&[u8]> {
+  match bound {
+    KeyBound::Included(key) => Bound::Included(key.as_ref()),
+    KeyBound::Excluded(key) => Bound::Excluded(key.as_ref()),
+    KeyBound::Unbounded => Bound::Unbounded,
+  }
+}
+
+/// A key or value read from a `WritableTable`. Reads like a redb
+/// `AccessGuard`, but owns its bytes, since an `Overlay` may hold the value
+/// itself.
+pub(crate) struct Owned<T: RedbValue + 'static> {
+  bytes: Vec<u8>,
+  value: PhantomData<T>,
+}
+
+pub(crate) type OwnedEntry<K, V> = (Owned<K>, Owned<V>);
+
+/// A key of an `Overlay` with its value before and after it was written.
+pub(crate) type Change<K, V> = (Owned<K>, Option<Owned<V>>, Option<Owned<V>>);
+
+impl<T: RedbValue + 'static> Owned<T> {
+  fn new(bytes: Vec<u8>) -> Self {
+    Self {
+      bytes,
+      value: PhantomData,
+    }
+  }
+
+  fn from_value(value: &T::SelfType<'_>) -> Self {
+    Self::new(T::as_bytes(value).as_ref().to_vec())
+  }
+
+  pub(crate) fn value(&self) -> T::SelfType<'_> {
+    T::from_bytes(&self.bytes)
+  }
+}
+
+impl<K: RedbKey + 'static, V: RedbValue + 'static> WritableTable<K, V> for Table<'_, '_, K, V> {
+  fn get_bytes(&self, key: &[u8]) -> Result<Option<Owned<V>>, StorageError> {
+    Ok(ReadableTable::get(self, K::from_bytes(key))?.map(|value| Owned::from_value(&value.value())))
+  }
+
+  fn insert_bytes(&mut self, key: &[u8], value: &[u8]) -> Result<Option<Owned<V>>, StorageError> {
+    Ok(
+      Table::insert(self, K::from_bytes(key), V::from_bytes(value))?
+        .map(|value| Owned::from_value(&value.value())),
+    )
+  }
+
+  fn remove_bytes(&mut self, key: &[u8]) -> Result<Option<Owned<V>>, StorageError> {
+    Ok(Table::remove(self, K::from_bytes(key))?.map(|value| Owned::from_value(&value.value())))
+  }
+
+  fn range_bytes(
+    &self,
+    start: Bound<&[u8]>,
+    end: Bound<&[u8]>,
+  ) -> Result<Vec<OwnedEntry<K, V>>, StorageError> {
+    range(self, start, end)
+  }
+
+  fn last_bytes(&self) -> Result<Option<OwnedEntry<K, V>>, StorageError> {
+    Ok(ReadableTable::last(self)?.map(|(key, value)| {
+      (
+        Owned::from_value(&key.value()),
+        Owned::from_value(&value.value()),
+      )
+    }))
+  }
+}
+
+fn range<K: RedbKey + 'static, V: RedbValue + 'static>(
+  table: &impl ReadableTable<K, V>,
+  start: Bound<&[u8]>,
+  end: Bound<&[u8]>,
+) -> Result<Vec<OwnedEntry<K, V>>, StorageError> {
+  fn decode<K: RedbKey + 'static>(bound: Bound<&[u8]>) -> Bound<K::SelfType<'_>> {
+    match bound {
+      Bound::Included(key) => Bound::Included(K::from_bytes(key)),
+      Bound::Excluded(key) => Bound::Excluded(K::from_bytes(key)),
+      Bound::Unbounded => Bound::Unbounded,
+    }
+  }
+
+  table
+    .range::<K::SelfType<'_>>((decode::<K>(start), decode::<K>(end)))?
+    .map(|result| {
+      result.map(|(key, value)| {
+        (
+          Owned::from_value(&key.value()),
+          Owned::from_value(&value.value()),
+        )
+      })
+    })
+    .collect()
+}
+
+/// Keys of an `Overlay`, ordered like the table orders them.
+struct Key<K> {
+  bytes: Vec<u8>,
+  key: PhantomData<K>,
+}
+
+impl<K: RedbKey> Key<K> {
+  fn new(bytes: &[u8]) -> Self {
+    Self {
+      bytes: bytes.to_vec(),
+      key: PhantomData,
+    }
+  }
+}
+
+impl<K: RedbKey> Ord for Key<K> {
+  fn cmp(&self, other: &Self) -> Ordering {
+    K::compare(&self.bytes, &other.bytes)
+  }
+}
+
+impl<K: RedbKey> PartialOrd for Key<K> {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl<K: RedbKey> PartialEq for Key<K> {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl<K: RedbKey> Eq for Key<K> {}
+
+/// A table of a read transaction whose writes are kept in memory, so blocks
+/// that must not be committed, like the mempool, can be indexed without
+/// taking the database's only write transaction.
+pub(crate) struct Overlay<'txn, K: RedbKey + 'static, V: RedbValue + 'static> {
+  table: ReadOnlyTable<'txn, K, V>,
+  /// Written values, or `None` for removed entries.
+  writes: BTreeMap<Key<K>, Option<Vec<u8>>>,
+}
+
+impl<'txn, K: RedbKey + 'static, V: RedbValue + 'static> Overlay<'txn, K, V> {
+  pub(crate) fn new(table: ReadOnlyTable<'txn, K, V>) -> Self {
+    Self {
+      table,
+      writes: BTreeMap::new(),
+    }
+  }
+
+  /// Every written entry, in key order, with its value before and after, or
+  /// `None` if it didn't exist or was removed.
+  pub(crate) fn changes(&self) -> Result<Vec<Change<K, V>>, StorageError> {
+    let mut changes = Vec::new();
+
+    for (key, value) in &self.writes {
+      let before = ReadableTable::get(&self.table, K::from_bytes(&key.bytes))?
+        .map(|value| Owned::from_value(&value.value()));
+
+      changes.push((
+        Owned::new(key.bytes.clone()),
+        before,
+        value.clone().map(Owned::new),
+      ));
+    }
+
+    Ok(changes)
+  }
+}
+
+impl<K: RedbKey + 'static, V: RedbValue + 'static> WritableTable<K, V> for Overlay<'_, K, V> {
+  fn get_bytes(&self, key: &[u8]) -> Result<Option<Owned<V>>, StorageError> {
+    match self.writes.get(&Key::new(key)) {
+      Some(value) => Ok(value.clone().map(Owned::new)),
+      None => Ok(
+        ReadableTable::get(&self.table, K::from_bytes(key))?
+          .map(|value| Owned::from_value(&value.value())),
+      ),
+    }
+  }
+
+  fn insert_bytes(&mut self, key: &[u8], value: &[u8]) -> Result<Option<Owned<V>>, StorageError> {
+    let previous = self.get_bytes(key)?;
+    self.writes.insert(Key::new(key), Some(value.to_vec()));
+    Ok(previous)
+  }
+
+  fn remove_bytes(&mut self, key: &[u8]) -> Result<Option<Owned<V>>, StorageError> {
+    let previous = self.get_bytes(key)?;
+    self.writes.insert(Key::new(key), None);
+    Ok(previous)
+  }
+
+  fn range_bytes(
+    &self,
+    start: Bound<&[u8]>,
+    end: Bound<&[u8]>,
+  ) -> Result<Vec<OwnedEntry<K, V>>, StorageError> {
+    let mut entries = range(&self.table, start, end)?
+      .into_iter()
+      .map(|(key, value)| (Key::new(&key.bytes), value.bytes))
+      .collect::<BTreeMap<Key<K>, Vec<u8>>>();
+
+    let empty = match (start, end) {
+      (Bound::Included(start), Bound::Included(end)) => K::compare(start, end).is_gt(),
+      (
+        Bound::Included(start) | Bound::Excluded(start),
+        Bound::Included(end) | Bound::Excluded(end),
+      ) => K::compare(start, end).is_ge(),
+      _ => false,
+    };
+
+    if !empty {
+      let key = |bound: Bound<&[u8]>| match bound {
+        Bound::Included(key) => Bound::Included(Key::new(key)),
+        Bound::Excluded(key) => Bound::Excluded(Key::new(key)),
+        Bound::Unbounded => Bound::Unbounded,
+      };
+
+      for (key, value) in self.writes.range((key(start), key(end))) {
+        match value {
+          Some(value) => entries.insert(Key::new(&key.bytes), value.clone()),
+          None => entries.remove(key),
+        };
+      }
+    }
+
+    Ok(
+      entries
+        .into_iter()
+        .map(|(key, value)| (Owned::new(key.bytes), Owned::new(value)))
+        .collect(),
+    )
+  }
+
+  fn last_bytes(&self) -> Result<Option<OwnedEntry<K, V>>, StorageError> {
+    let written = self
+      .writes
+      .iter()
+      .rev()
+      .find_map(|(key, value)| value.as_ref().map(|value| (key, value)));
+
+    let mut stored = None;
+
+    for result in self.table.iter()?.rev() {
+      let (key, value) = result?;
+      let key = Key::new(K::as_bytes(&key.value()).as_ref());
+
+      if !self.writes.contains_key(&key) {
+        stored = Some((key, V::as_bytes(&value.value()).as_ref().to_vec()));
+        break;
+      }
+    }
+
+    let last = match (written, stored) {
+      (Some((key, value)), Some(stored)) if *key > stored.0 => (key.bytes.clone(), value.clone()),
+      (_, Some((key, value))) => (key.bytes, value),
+      (Some((key, value)), None) => (key.bytes.clone(), value.clone()),
+      (None, None) => return Ok(None),
+    };
+
+    Ok(Some((Owned::new(last.0), Owned::new(last.1))))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const TEST_TABLE: TableDefinition<&str, u64> = TableDefinition::new("TEST_TABLE");
+
+  fn database() -> (TempDir, Database) {
+    let tempdir = TempDir::new().unwrap();
+    let database = Database::create(tempdir.path().join("index.redb")).unwrap();
+
+    let wtx = database.begin_write().unwrap();
+    {
+      let mut table = wtx.open_table(TEST_TABLE).unwrap();
+      table.insert("a", 1).unwrap();
+      table.insert("c", 3).unwrap();
+      table.insert("e", 5).unwrap();
+    }
+    wtx.commit().unwrap();
+
+    (tempdir, database)
+  }
+
+  fn entries(entries: Vec<(Owned<&'static str>, Owned<u64>)>) -> Vec<(String, u64)> {
+    entries
+      .into_iter()
+      .map(|(key, value)| (key.value().to_string(), value.value()))
+      .collect()
+  }
+
+  #[test]
+  fn writes_stay_in_memory() {
+    let (_tempdir, database) = database();
+
+    let rtx = database.begin_read().unwrap();
+    let mut overlay = Overlay::new(rtx.open_table(TEST_TABLE).unwrap());
+    let table: &mut dyn WritableTable<&str, u64> = &mut overlay;
+
+    assert_eq!(
+      table.insert("b", 2).unwrap().map(|value| value.value()),
+      None
+    );
+    assert_eq!(
+      table.insert("c", 4).unwrap().map(|value| value.value()),
+      Some(3)
+    );
+    assert_eq!(
+      table.remove("e").unwrap().map(|value| value.value()),
+      Some(5)
+    );
+
+    assert_eq!(table.get("c").unwrap().map(|value| value.value()), Some(4));
+    assert_eq!(table.get("e").unwrap().map(|value| value.value()), None);
+    assert_eq!(
+      entries(table.range::<&str>(..).unwrap()),
+      [("a".into(), 1), ("b".into(), 2), ("c".into(), 4)]
+    );
+    assert_eq!(entries(table.range("b".."c").unwrap()), [("b".into(), 2)]);
+    assert_eq!(
+      table
+        .last()
+        .unwrap()
+        .map(|(key, value)| (key.value().to_string(), value.value())),
+      Some(("c".into(), 4))
+    );
+
+    assert_eq!(
+      overlay
+        .changes()
+        .unwrap()
+        .into_iter()
+        .map(|(key, before, after)| (
+          key.value().to_string(),
+          before.map(|value| value.value()),
+          after.map(|value| value.value())
+        ))
+        .collect::<Vec<_>>(),
+      [
+        ("b".into(), None, Some(2)),
+        ("c".into(), Some(3), Some(4)),
+        ("e".into(), Some(5), None)
+      ]
+    );
+
+    drop(overlay);
+    drop(rtx);
+
+    let rtx = database.begin_read().unwrap();
+    let table = rtx.open_table(TEST_TABLE).unwrap();
+    assert_eq!(table.get("b").unwrap().map(|value| value.value()), None);
+    assert_eq!(table.get("c").unwrap().unwrap().value(), 3);
+    assert_eq!(table.get("e").unwrap().unwrap().value(), 5);
+  }
+
+  #[test]
+  fn tables_are_written_through() {
+    let (_tempdir, database) = database();
+
+    let wtx = database.begin_write().unwrap();
+    {
+      let mut table = wtx.open_table(TEST_TABLE).unwrap();
+      let table: &mut dyn WritableTable<&str, u64> = &mut table;
+
+      table.insert("f", 6).unwrap();
+      table.remove("a").unwrap();
+
+      assert_eq!(
+        entries(table.range("b"..="f").unwrap()),
+        [("c".into(), 3), ("e".into(), 5), ("f".into(), 6)]
+      );
+    }
+    wtx.commit().unwrap();
+
+    let rtx = database.begin_read().unwrap();
+    let table = rtx.open_table(TEST_TABLE).unwrap();
+    assert_eq!(table.get("a").unwrap().map(|value| value.value()), None);
+    assert_eq!(table.get("f").unwrap().unwrap().value(), 6);
+  }
+}
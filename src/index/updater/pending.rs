@@ -0,0 +1,476 @@
+use {
+  super::{overlay::Overlay, *},
+  crate::drc20::{script_key::ScriptKey, Balance, Tick, TokenInfo},
+  bitcoin::{blockdata::locktime::PackedLockTime, TxMerkleNode},
+  redb::ReadTransaction,
+};
+
+/// How unconfirmed transactions would change a DRC-20 balance.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PendingBalance {
+  pub(crate) tick: Tick,
+  /// Decimals of `tick`, which may only be deployed in the mempool.
+  pub(crate) decimal: u8,
+  pub(crate) overall: i128,
+  pub(crate) transferable: i128,
+}
+
+/// The DRC-20 balance changes of one mempool snapshot, by script key.
+#[derive(Default)]
+pub(crate) struct PendingBalances {
+  /// The tip and the sorted mempool txids the changes were computed from.
+  snapshot: Option<(Option<BlockHash>, Vec<Txid>)>,
+  changes: HashMap<String, Vec<PendingBalance>>,
+}
+
+impl Index {
+  /// Returns how the mempool would change the balances of `script_key`, as of
+  /// the last `update_drc20_pending`. Inscriptions spread over several
+  /// transactions only count once they are confirmed, as do transactions
+  /// spending outputs whose value the index doesn't hold.
+  pub(crate) fn get_drc20_pending_balances(&self, script_key: &ScriptKey) -> Vec<PendingBalance> {
+    self
+      .drc20_pending
+      .lock()
+      .unwrap()
+      .changes
+      .get(&script_key.to_string())
+      .cloned()
+      .unwrap_or_default()
+  }
+
+  /// Indexes the node's mempool as if it were the next block, with every
+  /// write kept in memory on top of a read transaction, and caches how it
+  /// changes each balance. Does nothing if neither the tip nor the mempool
+  /// changed since the last call.
+  pub(crate) fn update_drc20_pending(&self) -> Result {
+    if !self.index_drc20 {
+      return Ok(());
+    }
+
+    let rtx = self.begin_read()?;
+
+    let mut txids = self.client.get_raw_mempool()?;
+    txids.sort();
+
+    let snapshot = (rtx.block_hash(None)?, txids);
+
+    if self.drc20_pending.lock().unwrap().snapshot.as_ref() == Some(&snapshot) {
+      return Ok(());
+    }
+
+    let height = rtx.block_count()?;
+
+    let (block, values) = self.mempool_block(&rtx.0, &snapshot, height)?;
+
+    let changes = if block.txdata.len() == 1 {
+      HashMap::new()
+    } else {
+      Self::index_mempool_block(&rtx.0, self.chain, height, &block, values)?
+    };
+
+    *self.drc20_pending.lock().unwrap() = PendingBalances {
+      snapshot: Some(snapshot),
+      changes,
+    };
+
+    Ok(())
+  }
+
+  /// The mempool transactions `txids` that only spend outputs of known value,
+  /// parents before children, behind a coinbase that collects the subsidy at
+  /// `height` and every fee and that builds on `tip`, and the values of the
+  /// outputs they spend.
+  fn mempool_block(
+    &self,
+    rtx: &ReadTransaction,
+    (tip, txids): &(Option<BlockHash>, Vec<Txid>),
+    height: u32,
+  ) -> Result<(BlockData, HashMap<OutPoint, u64>)> {
+    fn visit(
+      txid: Txid,
+      mempool: &mut BTreeMap<Txid, Transaction>,
+      txdata: &mut Vec<(Transaction, Txid)>,
+    ) {
+      let Some(tx) = mempool.remove(&txid) else {
+        return;
+      };
+
+      for input in &tx.input {
+        visit(input.previous_output.txid, mempool, txdata);
+      }
+
+      txdata.push((tx, txid));
+    }
+
+    let mut mempool = BTreeMap::new();
+
+    for txid in txids {
+      // transactions can leave the mempool while it's being fetched
+      if let Some(tx) = self.client.get_raw_transaction(txid, None).into_option()? {
+        mempool.insert(*txid, tx);
+      }
+    }
+
+    let mut txdata = Vec::new();
+
+    while let Some(txid) = mempool.keys().next().copied() {
+      visit(txid, &mut mempool, &mut txdata);
+    }
+
+    let (mut txdata, values) = Self::input_values(rtx, txdata)?;
+
+    let mut fees = 0;
+
+    for (tx, _) in &txdata {
+      let input_value = tx
+        .input
+        .iter()
+        .map(|input| values[&input.previous_output])
+        .sum::<u64>();
+
+      fees += input_value.saturating_sub(tx.output.iter().map(|output| output.value).sum());
+    }
+
+    let coinbase = Transaction {
+      version: 1,
+      lock_time: PackedLockTime::ZERO,
+      input: vec![TxIn {
+        previous_output: OutPoint::null(),
+        ..Default::default()
+      }],
+      output: vec![TxOut {
+        value: Height(height).subsidy() + fees,
+        script_pubkey: Script::new(),
+      }],
+    };
+
+    let coinbase_txid = coinbase.txid();
+
+    txdata.insert(0, (coinbase, coinbase_txid));
+
+    let block = BlockData {
+      header: BlockHeader {
+        version: 0,
+        prev_blockhash: tip.unwrap_or_else(BlockHash::all_zeros),
+        merkle_root: TxMerkleNode::all_zeros(),
+        time: SystemTime::now()
+          .duration_since(SystemTime::UNIX_EPOCH)?
+          .as_secs()
+          .try_into()?,
+        bits: 0,
+        nonce: 0,
+      },
+      auxpow: None,
+      txdata,
+    };
+
+    Ok((block, values))
+  }
+
+  /// The transactions of `txdata` whose inputs all have a known value, and
+  /// the values of the outputs they spend. Confirmed values are read from
+  /// `OUTPOINT_TO_VALUE`, which lacks outputs created below the first
+  /// inscription height unless a UTXO set was loaded, so transactions
+  /// spending those, and their descendants, are left out.
+  fn input_values(
+    rtx: &ReadTransaction,
+    txdata: Vec<(Transaction, Txid)>,
+  ) -> Result<(Vec<(Transaction, Txid)>, HashMap<OutPoint, u64>)> {
+    let outpoint_to_value = rtx.open_table(OUTPOINT_TO_VALUE)?;
+
+    let mut unconfirmed = HashMap::new();
+    let mut values = HashMap::new();
+    let mut included = Vec::new();
+
+    for (tx, txid) in txdata {
+      let mut tx_values = Vec::new();
+
+      for input in &tx.input {
+        let outpoint = input.previous_output;
+
+        let value = match unconfirmed.get(&outpoint) {
+          Some(value) => Some(*value),
+          None => outpoint_to_value
+            .get(&outpoint.store())?
+            .map(|value| value.value()),
+        };
+
+        match value {
+          Some(value) => tx_values.push((outpoint, value)),
+          None => break,
+        }
+      }
+
+      if tx_values.len() < tx.input.len() {
+        continue;
+      }
+
+      values.extend(tx_values);
+
+      for (vout, output) in tx.output.iter().enumerate() {
+        unconfirmed.insert(
+          OutPoint {
+            txid,
+            vout: vout.try_into().unwrap(),
+          },
+          output.value,
+        );
+      }
+
+      included.push((tx, txid));
+    }
+
+    Ok((included, values))
+  }
+
+  /// Runs the inscription and DRC-20 updaters over `block` at `height` on
+  /// overlays of `rtx` and returns the balance changes they make, by script
+  /// key. `values` holds the value of every output `block` spends.
+  fn index_mempool_block(
+    rtx: &ReadTransaction,
+    chain: Chain,
+    height: u32,
+    block: &BlockData,
+    values: HashMap<OutPoint, u64>,
+  ) -> Result<HashMap<String, Vec<PendingBalance>>> {
+    let mut value_cache = values
+      .into_iter()
+      .map(|(outpoint, value)| (outpoint, (value, [0; 34], height)))
+      .collect::<HashMap<OutPoint, OutPointMapValue>>();
+
+    // every input value is cached, so nothing is ever fetched
+    let (_, mut value_receiver) = tokio::sync::mpsc::channel::<u64>(1);
+
+    let mut inscription_id_to_inscription_entry =
+      Overlay::new(rtx.open_table(INSCRIPTION_ID_TO_INSCRIPTION_ENTRY)?);
+    let mut inscription_id_to_satpoint = Overlay::new(rtx.open_table(INSCRIPTION_ID_TO_SATPOINT)?);
+    let mut inscription_id_to_txids = Overlay::new(rtx.open_table(INSCRIPTION_ID_TO_TXIDS)?);
+    let mut inscription_txid_to_tx = Overlay::new(rtx.open_table(INSCRIPTION_TXID_TO_TX)?);
+    let mut partial_txid_to_inscription_txids =
+      Overlay::new(rtx.open_table(PARTIAL_TXID_TO_INSCRIPTION_TXIDS)?);
+    let mut inscription_id_to_content_hash =
+      Overlay::new(rtx.open_table(INSCRIPTION_ID_TO_CONTENT_HASH)?);
+    let mut content_hash_to_inscription_content =
+      Overlay::new(rtx.open_table(CONTENT_HASH_TO_INSCRIPTION_CONTENT)?);
+    let mut inscription_number_to_inscription_id =
+      Overlay::new(rtx.open_table(INSCRIPTION_NUMBER_TO_INSCRIPTION_ID)?);
+    let mut sat_to_inscription_id = Overlay::new(rtx.open_table(SAT_TO_INSCRIPTION_ID)?);
+    let mut satpoint_to_inscription_id = Overlay::new(rtx.open_table(SATPOINT_TO_INSCRIPTION_ID)?);
+    let mut transaction_id_to_transaction =
+      Overlay::new(rtx.open_table(TRANSACTION_ID_TO_TRANSACTION)?);
+    let mut outpoint_to_value = Overlay::new(rtx.open_table(OUTPOINT_TO_VALUE)?);
+    let mut address_tables = AddressTables::overlay(rtx)?;
+
+    let mut inscription_updater = InscriptionUpdater::new(
+      height,
+      &mut inscription_id_to_satpoint,
+      &mut inscription_id_to_txids,
+      &mut inscription_txid_to_tx,
+      &mut partial_txid_to_inscription_txids,
+      &mut inscription_id_to_content_hash,
+      &mut content_hash_to_inscription_content,
+      &mut value_receiver,
+      true,
+      Vec::new(),
+      &mut transaction_id_to_transaction,
+      &mut inscription_id_to_inscription_entry,
+      0,
+      &mut inscription_number_to_inscription_id,
+      &mut outpoint_to_value,
+      &mut address_tables,
+      &mut sat_to_inscription_id,
+      &mut satpoint_to_inscription_id,
+      block.header.time,
+      &mut value_cache,
+      false,
+      chain,
+    )?;
+
+    for (tx, txid) in block.txdata.iter().skip(1).chain(block.txdata.first()) {
+      inscription_updater.index_transaction_inscriptions(tx, *txid, None)?;
+    }
+
+    let operations = inscription_updater.operations.clone();
+
+    let mut drc20_token_info = Overlay::new(rtx.open_table(DRC20_TOKEN)?);
+    let mut drc20_tick_holder = Overlay::new(rtx.open_table(DRC20_TICK_HOLDER)?);
+    let mut drc20_tick_to_holder_count = Overlay::new(rtx.open_table(DRC20_TICK_TO_HOLDER_COUNT)?);
+    let mut drc20_tick_balance_holder = Overlay::new(rtx.open_table(DRC20_TICK_BALANCE_HOLDER)?);
+    let mut drc20_token_balance = Overlay::new(rtx.open_table(DRC20_BALANCES)?);
+    let mut drc20_inscribe_transfer = Overlay::new(rtx.open_table(DRC20_INSCRIBE_TRANSFER)?);
+    let mut drc20_transferable_log = Overlay::new(rtx.open_table(DRC20_TRANSFERABLELOG)?);
+    let mut drc20_tick_activity = Overlay::new(rtx.open_table(DRC20_TICK_ACTIVITY)?);
+    let mut drc20_height_to_state_hash = Overlay::new(rtx.open_table(DRC20_HEIGHT_TO_STATE_HASH)?);
+    let mut drc20_balance_history = Overlay::new(rtx.open_table(DRC20_BALANCE_HISTORY)?);
+
+    Drc20Updater::new(
+      &mut drc20_token_info,
+      &mut drc20_tick_holder,
+      &mut drc20_tick_to_holder_count,
+      &mut drc20_tick_balance_holder,
+      &mut drc20_token_balance,
+      &mut drc20_inscribe_transfer,
+      &mut drc20_transferable_log,
+      &mut drc20_tick_activity,
+      &mut drc20_height_to_state_hash,
+      &mut drc20_balance_history,
+      &inscription_id_to_inscription_entry,
+      &mut transaction_id_to_transaction,
+    )?
+    .index_block(
+      BlockContext {
+        chain,
+        network: Network::Bitcoin,
+        blockheight: height.into(),
+        blocktime: block.header.time,
+      },
+      block,
+      operations,
+    )?;
+
+    let token_info: &dyn WritableTable<&'static str, &'static [u8]> = &drc20_token_info;
+
+    let mut changes = HashMap::<String, Vec<PendingBalance>>::new();
+
+    for (key, before, after) in drc20_token_balance.changes()? {
+      let Some(after) = after else {
+        continue;
+      };
+
+      let balance = bincode::deserialize::<Balance>(after.value())?;
+
+      let (overall, transferable) = before
+        .map(|before| bincode::deserialize::<Balance>(before.value()))
+        .transpose()?
+        .map(|before| (before.overall_balance, before.transferable_balance))
+        .unwrap_or_default();
+
+      let decimal = token_info
+        .get(balance.tick.to_lowercase().hex().as_str())?
+        .map(|data| bincode::deserialize::<TokenInfo>(data.value()))
+        .transpose()?
+        .map(|token_info| token_info.decimal)
+        .unwrap_or_default();
+
+      let change = PendingBalance {
+        overall: i128::try_from(balance.overall_balance)? - i128::try_from(overall)?,
+        transferable: i128::try_from(balance.transferable_balance)? - i128::try_from(transferable)?,
+        tick: balance.tick,
+        decimal,
+      };
+
+      if change.overall == 0 && change.transferable == 0 {
+        continue;
+      }
+
+      let (script_key, _tick) = key
+        .value()
+        .rsplit_once('_')
+        .ok_or_else(|| anyhow!("invalid balance key {}", key.value()))?;
+
+      changes.entry(script_key.into()).or_default().push(change);
+    }
+
+    Ok(changes)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::index::testing::Context};
+
+  fn drc20(op: &str) -> Script {
+    inscription(
+      "text/plain;charset=utf-8",
+      format!(r#"{{"p":"drc-20",{op}}}"#),
+    )
+    .to_script_sig()
+  }
+
+  #[test]
+  fn unconfirmed_operations_are_pending() {
+    let context = Context::builder()
+      .args(["--index-drc20", "--index-transactions"])
+      .build();
+    context.mine_blocks(1);
+
+    context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      script_sig: drc20(r#""op":"deploy","tick":"doge","max":"1000","lim":"100","dec":"0""#),
+      ..Default::default()
+    });
+    context.mine_blocks(1);
+
+    let script_key = ScriptKey::from_script(&Script::new(), Network::Bitcoin);
+
+    context.index.update_drc20_pending().unwrap();
+
+    assert_eq!(context.index.get_drc20_pending_balances(&script_key), []);
+
+    context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 0, 0)],
+      script_sig: drc20(r#""op":"mint","tick":"doge","amt":"100""#),
+      ..Default::default()
+    });
+
+    context.index.update_drc20_pending().unwrap();
+
+    assert_eq!(
+      context.index.get_drc20_pending_balances(&script_key),
+      [PendingBalance {
+        tick: Tick::from_str("doge").unwrap(),
+        decimal: 0,
+        overall: 100,
+        transferable: 0,
+      }]
+    );
+
+    assert_eq!(context.index.get_drc20_balances(&script_key).unwrap(), []);
+
+    context.mine_blocks(1);
+
+    assert_eq!(
+      context.index.get_drc20_balances(&script_key).unwrap()[0].overall_balance,
+      100
+    );
+    context.index.update_drc20_pending().unwrap();
+
+    assert_eq!(context.index.get_drc20_pending_balances(&script_key), []);
+  }
+
+  #[test]
+  fn transactions_spending_outputs_of_unknown_value_are_left_out() {
+    let context = Context::builder()
+      .args(["--index-drc20", "--index-transactions"])
+      .build();
+    context.mine_blocks(1);
+
+    context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      script_sig: drc20(r#""op":"deploy","tick":"doge","max":"1000","lim":"100","dec":"0""#),
+      ..Default::default()
+    });
+    let coinbase = context.mine_blocks(1)[0].txdata[0].txid();
+
+    let wtx = context.index.database.begin_write().unwrap();
+    wtx
+      .open_table(OUTPOINT_TO_VALUE)
+      .unwrap()
+      .remove(&OutPoint::new(coinbase, 0).store())
+      .unwrap();
+    wtx.commit().unwrap();
+
+    context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 0, 0)],
+      script_sig: drc20(r#""op":"mint","tick":"doge","amt":"100""#),
+      ..Default::default()
+    });
+
+    context.index.update_drc20_pending().unwrap();
+
+    let script_key = ScriptKey::from_script(&Script::new(), Network::Bitcoin);
+
+    assert_eq!(context.index.get_drc20_pending_balances(&script_key), []);
+  }
+}
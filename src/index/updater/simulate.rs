@@ -0,0 +1,215 @@
+use {
+  super::{overlay::Overlay, *},
+  crate::drc20::{
+    operation::{deserialize_drc20_operation, Action},
+    script_key::ScriptKey,
+    DRC20Error, Event, Ruleset,
+  },
+};
+
+/// What a DRC-20 operation would do if it were confirmed in the next block.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Simulation {
+  pub height: u32,
+  pub rule_version: u32,
+  pub result: Result<Event, DRC20Error>,
+}
+
+impl Index {
+  /// Executes the DRC-20 operation in `content` against the current state,
+  /// keeping its writes in memory on top of a read transaction. Without
+  /// `transfer`, `from` inscribes `content` to `to`. With it, `from` sends the
+  /// already inscribed transfer `transfer` to `to`. Fails if `content` isn't
+  /// an operation.
+  pub(crate) fn simulate_drc20_operation(
+    &self,
+    content_type: &str,
+    content: &[u8],
+    transfer: Option<InscriptionId>,
+    from: ScriptKey,
+    to: ScriptKey,
+  ) -> Result<Simulation> {
+    ensure!(self.index_drc20, "--index-drc20 is not enabled");
+
+    let rtx = self.begin_read()?;
+
+    let height = rtx.block_count()?;
+    let rules = Ruleset::at(self.chain, height.into());
+
+    let inscription = Inscription {
+      content_type: Some(content_type.into()),
+      body: Some(content.into()),
+      delegate: None,
+    };
+
+    let (action, inscription_id) = match transfer {
+      Some(inscription_id) => (Action::Transfer, inscription_id),
+      None => (
+        Action::New {
+          inscription: inscription.clone(),
+        },
+        InscriptionId::from(Txid::all_zeros()),
+      ),
+    };
+
+    let op = deserialize_drc20_operation(&inscription, &action, rules)?;
+
+    let rtx = rtx.0;
+
+    let inscription_id_to_inscription_entry =
+      Overlay::new(rtx.open_table(INSCRIPTION_ID_TO_INSCRIPTION_ENTRY)?);
+    let mut transaction_id_to_transaction =
+      Overlay::new(rtx.open_table(TRANSACTION_ID_TO_TRANSACTION)?);
+    let mut drc20_token_info = Overlay::new(rtx.open_table(DRC20_TOKEN)?);
+    let mut drc20_tick_holder = Overlay::new(rtx.open_table(DRC20_TICK_HOLDER)?);
+    let mut drc20_tick_to_holder_count = Overlay::new(rtx.open_table(DRC20_TICK_TO_HOLDER_COUNT)?);
+    let mut drc20_tick_balance_holder = Overlay::new(rtx.open_table(DRC20_TICK_BALANCE_HOLDER)?);
+    let mut drc20_token_balance = Overlay::new(rtx.open_table(DRC20_BALANCES)?);
+    let mut drc20_inscribe_transfer = Overlay::new(rtx.open_table(DRC20_INSCRIBE_TRANSFER)?);
+    let mut drc20_transferable_log = Overlay::new(rtx.open_table(DRC20_TRANSFERABLELOG)?);
+    let mut drc20_tick_activity = Overlay::new(rtx.open_table(DRC20_TICK_ACTIVITY)?);
+    let mut drc20_height_to_state_hash = Overlay::new(rtx.open_table(DRC20_HEIGHT_TO_STATE_HASH)?);
+    let mut drc20_balance_history = Overlay::new(rtx.open_table(DRC20_BALANCE_HISTORY)?);
+
+    let result = Drc20Updater::new(
+      &mut drc20_token_info,
+      &mut drc20_tick_holder,
+      &mut drc20_tick_to_holder_count,
+      &mut drc20_tick_balance_holder,
+      &mut drc20_token_balance,
+      &mut drc20_inscribe_transfer,
+      &mut drc20_transferable_log,
+      &mut drc20_tick_activity,
+      &mut drc20_height_to_state_hash,
+      &mut drc20_balance_history,
+      &inscription_id_to_inscription_entry,
+      &mut transaction_id_to_transaction,
+    )?
+    .simulate(
+      BlockContext {
+        chain: self.chain,
+        network: Network::Bitcoin,
+        blockheight: height.into(),
+        blocktime: SystemTime::now()
+          .duration_since(SystemTime::UNIX_EPOCH)?
+          .as_secs()
+          .try_into()?,
+      },
+      inscription_id,
+      op,
+      from,
+      to,
+    )?;
+
+    Ok(Simulation {
+      height,
+      rule_version: rules.version,
+      result,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::index::testing::Context};
+
+  fn simulate(context: &Context, content: &str) -> Result<Event, DRC20Error> {
+    let script_key = ScriptKey::from_script(&Script::new(), Network::Bitcoin);
+
+    context
+      .index
+      .simulate_drc20_operation(
+        "text/plain;charset=utf-8",
+        content.as_bytes(),
+        None,
+        script_key.clone(),
+        script_key,
+      )
+      .unwrap()
+      .result
+  }
+
+  #[test]
+  fn operations_are_simulated_without_writing() {
+    let context = Context::builder()
+      .args(["--index-drc20", "--index-transactions"])
+      .build();
+    context.mine_blocks(1);
+
+    let deploy = r#"{"p":"drc-20","op":"deploy","tick":"dogi","max":"1000","lim":"100","dec":"0"}"#;
+
+    assert!(matches!(simulate(&context, deploy), Ok(Event::Deploy(_))));
+    assert!(matches!(simulate(&context, deploy), Ok(Event::Deploy(_))));
+
+    assert_eq!(
+      simulate(
+        &context,
+        r#"{"p":"drc-20","op":"mint","tick":"dogi","amt":"100"}"#
+      ),
+      Err(DRC20Error::TickNotFound("dogi".into()))
+    );
+  }
+
+  #[test]
+  fn errors_are_exact() {
+    let context = Context::builder()
+      .args(["--index-drc20", "--index-transactions"])
+      .build();
+    context.mine_blocks(1);
+
+    context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      script_sig: inscription(
+        "text/plain;charset=utf-8",
+        r#"{"p":"drc-20","op":"deploy","tick":"dogi","max":"1000","lim":"100","dec":"0"}"#,
+      )
+      .to_script_sig(),
+      ..Default::default()
+    });
+    context.mine_blocks(1);
+
+    assert!(matches!(
+      simulate(
+        &context,
+        r#"{"p":"drc-20","op":"mint","tick":"dogi","amt":"100"}"#
+      ),
+      Ok(Event::Mint(_))
+    ));
+
+    assert_eq!(
+      simulate(
+        &context,
+        r#"{"p":"drc-20","op":"mint","tick":"dogi","amt":"1000"}"#
+      ),
+      Err(DRC20Error::AmountExceedLimit("1000".into()))
+    );
+
+    assert_eq!(
+      simulate(
+        &context,
+        r#"{"p":"drc-20","op":"transfer","tick":"dogi","amt":"1"}"#
+      ),
+      Err(DRC20Error::InsufficientBalance("0".into(), "1".into()))
+    );
+  }
+
+  #[test]
+  fn content_that_is_no_operation_is_rejected() {
+    let context = Context::builder()
+      .args(["--index-drc20", "--index-transactions"])
+      .build();
+
+    let script_key = ScriptKey::from_script(&Script::new(), Network::Bitcoin);
+
+    assert!(context
+      .index
+      .simulate_drc20_operation(
+        "text/plain;charset=utf-8",
+        br#"{"p":"brc-20","op":"mint","tick":"dogi","amt":"100000000"}"#,
+        None,
+        script_key.clone(),
+        script_key,
+      )
+      .is_err());
+  }
+}
@@ -44,6 +44,15 @@ impl Inscription {
     InscriptionParser::parse(sig_scripts)
   }
 
+  /// Serialized content type, body and delegate, as stored in the index.
+  pub(crate) fn to_bytes(&self) -> Vec<u8> {
+    bincode::serialize(self).expect("in-memory serialization doesn't fail")
+  }
+
+  pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self> {
+    Ok(bincode::deserialize(bytes)?)
+  }
+
   pub(crate) fn from_file(chain: Chain, path: impl AsRef<Path>) -> Result<Self, Error> {
     let path = path.as_ref();
 
@@ -170,6 +179,32 @@ impl Inscription {
 
     witness
   }
+
+  /// Signature script revealing the inscription in a single transaction, the
+  /// way dogecoin inscriptions are revealed, with the body pushed in 240 byte
+  /// pieces.
+  #[cfg(test)]
+  pub(crate) fn to_script_sig(&self) -> Script {
+    let chunks = self
+      .body
+      .as_deref()
+      .unwrap_or_default()
+      .chunks(240)
+      .collect::<Vec<&[u8]>>();
+
+    let mut builder = script::Builder::new()
+      .push_slice(PROTOCOL_ID)
+      .push_int(chunks.len().try_into().unwrap())
+      .push_slice(self.content_type.as_deref().unwrap_or_default());
+
+    for (i, chunk) in chunks.iter().enumerate() {
+      builder = builder
+        .push_int((chunks.len() - i - 1).try_into().unwrap())
+        .push_slice(chunk);
+    }
+
+    builder.into_script()
+  }
 }
 
 struct InscriptionParser {}
@@ -884,6 +919,28 @@ mod tests {
     );
   }
 
+  #[test]
+  fn script_sig_round_trips() {
+    let inscription = inscription("text/plain;charset=utf-8", "a".repeat(1000));
+
+    let tx = Transaction {
+      version: 0,
+      lock_time: bitcoin::PackedLockTime(0),
+      input: vec![TxIn {
+        previous_output: OutPoint::null(),
+        script_sig: inscription.to_script_sig(),
+        sequence: Sequence(0),
+        witness: Witness::new(),
+      }],
+      output: Vec::new(),
+    };
+
+    assert_eq!(
+      Inscription::from_transactions(vec![tx]),
+      ParsedInscription::Complete(inscription),
+    );
+  }
+
   #[test]
   fn do_not_extract_from_second_input() {
     let mut script: Vec<&[u8]> = Vec::new();
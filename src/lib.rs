@@ -17,7 +17,7 @@ use {
     config::Config,
     decimal::Decimal,
     deserialize_from_str::DeserializeFromStr,
-    epoch::Epoch,
+    epoch::{Epoch, Schedule},
     height::Height,
     index::{Index, List, DuneEntry},
     inscription::Inscription,
@@ -103,6 +103,7 @@ macro_rules! tprintln {
 }
 
 mod arguments;
+mod auxpow;
 mod blocktime;
 mod chain;
 mod config;
@@ -118,6 +119,7 @@ mod inscription;
 mod inscription_id;
 mod tag;
 mod media;
+mod milestone;
 mod object;
 mod options;
 mod outgoing;
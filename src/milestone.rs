@@ -0,0 +1,107 @@
+use super::*;
+
+/// Blocks before DigiShield retargeted difficulty every this many blocks.
+const RETARGET_INTERVAL: u32 = 240;
+
+/// A block dogecoin's history singles out, whose first sat is therefore
+/// rarer than the first sat of any other block.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub(crate) enum Milestone {
+  Genesis,
+  PermanentReward,
+  DigiShield,
+  RewardEra,
+  AuxPow,
+  Retarget,
+}
+
+impl Milestone {
+  pub(crate) fn of(sat: Sat) -> Option<Self> {
+    if sat.third() != 0 {
+      return None;
+    }
+
+    Self::of_block(sat.height())
+  }
+
+  /// The milestone the block at `height` marks, if any.
+  pub(crate) fn of_block(height: Height) -> Option<Self> {
+    if height == 0 {
+      Some(Self::Genesis)
+    } else if height == Epoch::LAST.starting_height() {
+      Some(Self::PermanentReward)
+    } else if height == Height::DIGISHIELD {
+      Some(Self::DigiShield)
+    } else if height > Height::DIGISHIELD && height == Epoch::from(height).starting_height() {
+      Some(Self::RewardEra)
+    } else if height == Height::AUXPOW {
+      Some(Self::AuxPow)
+    } else if height < Height::DIGISHIELD && height.n() % RETARGET_INTERVAL == 0 {
+      Some(Self::Retarget)
+    } else {
+      None
+    }
+  }
+
+  pub(crate) fn rarity(self) -> Rarity {
+    match self {
+      Self::Genesis => Rarity::Mythic,
+      Self::PermanentReward => Rarity::Legendary,
+      Self::DigiShield | Self::RewardEra => Rarity::Epic,
+      Self::AuxPow | Self::Retarget => Rarity::Rare,
+    }
+  }
+}
+
+impl Display for Milestone {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{}",
+      match self {
+        Self::Genesis => "genesis",
+        Self::PermanentReward => "permanent 10,000 DOGE reward",
+        Self::DigiShield => "DigiShield activation and 250,000 DOGE reward",
+        Self::RewardEra => "reward halving",
+        Self::AuxPow => "AuxPoW activation",
+        Self::Retarget => "difficulty retarget",
+      }
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn of() {
+    #[track_caller]
+    fn case(height: u32, expected: Option<Milestone>) {
+      let starting_sat = Height(height).starting_sat();
+      assert_eq!(Milestone::of(starting_sat), expected);
+      assert_eq!(Milestone::of(starting_sat + 1), None);
+    }
+
+    case(0, Some(Milestone::Genesis));
+    case(1, None);
+    case(240, Some(Milestone::Retarget));
+    case(144_960, Some(Milestone::Retarget));
+    case(145_000, Some(Milestone::DigiShield));
+    case(145_200, None);
+    case(200_000, Some(Milestone::RewardEra));
+    case(371_337, Some(Milestone::AuxPow));
+    case(500_000, Some(Milestone::RewardEra));
+    case(600_000, Some(Milestone::PermanentReward));
+    case(600_001, None);
+  }
+
+  #[test]
+  fn display() {
+    assert_eq!(Milestone::AuxPow.to_string(), "AuxPoW activation");
+    assert_eq!(
+      Milestone::PermanentReward.to_string(),
+      "permanent 10,000 DOGE reward"
+    );
+  }
+}
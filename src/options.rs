@@ -16,6 +16,16 @@ pub(crate) struct Options {
     help = "Use <CHAIN>."
   )]
   pub(crate) chain_argument: Chain,
+  #[arg(
+    long,
+    help = "Commit to the index once <COMMIT_CACHE_SIZE> outputs are held in memory, however few blocks that takes. [default: 2000000]"
+  )]
+  pub(crate) commit_cache_size: Option<usize>,
+  #[arg(
+    long,
+    help = "Commit to the index at least every <COMMIT_INTERVAL> blocks. [default: 1000]"
+  )]
+  pub(crate) commit_interval: Option<u32>,
   #[arg(long, help = "Load configuration from <CONFIG>.")]
   pub(crate) config: Option<PathBuf>,
   #[arg(long, help = "Load configuration from <CONFIG_DIR>.")]
@@ -45,6 +55,11 @@ pub(crate) struct Options {
   pub(crate) height_limit: Option<u32>,
   #[arg(long, help = "Use index at <INDEX>.")]
   pub(crate) index: Option<PathBuf>,
+  #[arg(
+    long,
+    help = "Read blocks from Dogecoin Core's blk*.dat files during initial sync instead of fetching each one over RPC. Requires read access to the node's data directory."
+  )]
+  pub(crate) index_from_blk_files: bool,
   #[arg(long, help = "Track drc20 tokens and balances.")]
   pub(crate) index_drc20: bool,
   #[arg(
@@ -56,8 +71,18 @@ pub(crate) struct Options {
   pub(crate) index_sats: bool,
   #[arg(long, help = "Store transactions in index.")]
   pub(crate) index_transactions: bool,
+  #[arg(
+    long,
+    help = "Track the value of every unspent output from genesis, so input values never have to be fetched from Dogecoin Core and it doesn't need `txindex=1`."
+  )]
+  pub(crate) index_utxos: bool,
   #[arg(long, short, help = "Use regtest. Equivalent to `--chain regtest`.")]
   pub(crate) regtest: bool,
+  #[arg(
+    long,
+    help = "Index blocks recorded with `ord index record` in <REPLAY_DIR> instead of fetching them from Dogecoin Core."
+  )]
+  pub(crate) replay_dir: Option<PathBuf>,
   #[arg(long, help = "Connect to Dogecoin Core RPC at <RPC_URL>.")]
   pub(crate) rpc_url: Option<String>,
   #[arg(
@@ -86,6 +111,14 @@ impl Options {
     }
   }
 
+  pub(crate) fn commit_cache_size(&self) -> usize {
+    self.commit_cache_size.unwrap_or(2_000_000).max(1)
+  }
+
+  pub(crate) fn commit_interval(&self) -> u32 {
+    self.commit_interval.unwrap_or(1000).max(1)
+  }
+
   pub(crate) fn csp_origin(&self) -> Option<String> {
     self.csp_origin.clone()
   }
@@ -137,6 +170,18 @@ impl Options {
       return Ok(cookie_file.clone());
     }
 
+    Ok(self.dogecoin_chain_dir()?.join(".cookie"))
+  }
+
+  pub(crate) fn blocks_dir(&self) -> Result<Option<PathBuf>> {
+    if !self.index_from_blk_files {
+      return Ok(None);
+    }
+
+    Ok(Some(self.dogecoin_chain_dir()?.join("blocks")))
+  }
+
+  fn dogecoin_chain_dir(&self) -> Result<PathBuf> {
     let path = if let Some(dogecoin_data_dir) = &self.dogecoin_data_dir {
       dogecoin_data_dir.clone()
     } else if cfg!(target_os = "linux") {
@@ -149,9 +194,7 @@ impl Options {
         .join("Dogecoin")
     };
 
-    let path = self.chain().join_with_data_dir(&path);
-
-    Ok(path.join(".cookie"))
+    Ok(self.chain().join_with_data_dir(&path))
   }
 
   pub(crate) fn data_dir(&self) -> Result<PathBuf> {
@@ -382,6 +425,33 @@ mod tests {
     }));
   }
 
+  #[test]
+  fn blocks_dir_is_opt_in() {
+    assert_eq!(
+      Arguments::try_parse_from(["ord", "--dogecoin-data-dir=foo", "index"])
+        .unwrap()
+        .options
+        .blocks_dir()
+        .unwrap(),
+      None
+    );
+
+    assert_eq!(
+      Arguments::try_parse_from([
+        "ord",
+        "--dogecoin-data-dir=foo",
+        "--chain=testnet",
+        "--index-from-blk-files",
+        "index"
+      ])
+      .unwrap()
+      .options
+      .blocks_dir()
+      .unwrap(),
+      Some(Path::new("foo").join("testnet3").join("blocks"))
+    );
+  }
+
   #[test]
   fn mainnet_data_dir() {
     let data_dir = Arguments::try_parse_from(["ord", "index"])
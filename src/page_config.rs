@@ -6,4 +6,5 @@ pub(crate) struct PageConfig {
   pub(crate) domain: Option<String>,
   pub(crate) index_sats: bool,
   pub(crate) csp_origin: Option<String>,
+  pub(crate) page_size: usize,
 }
@@ -51,14 +51,10 @@ impl Display for Rarity {
 
 impl From<Sat> for Rarity {
   fn from(sat: Sat) -> Self {
-    if sat.0 == 0 {
-      return Self::Mythic;
-    } else if sat == sat.epoch().starting_sat() {
-      return Self::Legendary;
-    } else if !sat.is_common() {
-      return Self::Uncommon;
-    } else {
-      return Self::Common;
+    match sat.milestone() {
+      Some(milestone) => milestone.rarity(),
+      None if sat.is_common() => Self::Common,
+      None => Self::Uncommon,
     }
   }
 }
@@ -105,6 +101,29 @@ mod tests {
   fn rarity() {
     assert_eq!(Sat(0).rarity(), Rarity::Mythic);
     assert_eq!(Sat(1).rarity(), Rarity::Common);
+
+    #[track_caller]
+    fn case(height: u32, expected: Rarity) {
+      let starting_sat = Height(height).starting_sat();
+      assert_eq!(Sat(starting_sat.n() - 1).rarity(), Rarity::Common);
+      assert_eq!(starting_sat.rarity(), expected);
+      assert_eq!((starting_sat + 1).rarity(), Rarity::Common);
+    }
+
+    case(1, Rarity::Uncommon);
+    case(239, Rarity::Uncommon);
+    case(240, Rarity::Rare);
+    case(144_960, Rarity::Rare);
+    case(145_000, Rarity::Epic);
+    case(145_200, Rarity::Uncommon);
+    case(200_000, Rarity::Epic);
+    case(371_337, Rarity::Rare);
+    case(400_000, Rarity::Epic);
+    case(500_000, Rarity::Epic);
+    case(599_999, Rarity::Uncommon);
+    case(600_000, Rarity::Legendary);
+    case(600_001, Rarity::Uncommon);
+    case(1_000_000, Rarity::Uncommon);
   }
 
   #[test]
@@ -1,6 +1,9 @@
 use crate::decimal_sat::DecimalSat;
+use crate::milestone::Milestone;
 use super::*;
 
+/// A sat number. Its methods number sats by the mainnet schedule, an index
+/// numbers them by `Index::schedule`.
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Ord, PartialOrd, Deserialize, Serialize)]
 #[serde(transparent)]
 pub struct Sat(pub u64);
@@ -35,6 +38,10 @@ impl Sat {
     self.into()
   }
 
+  pub(crate) fn milestone(self) -> Option<Milestone> {
+    Milestone::of(self)
+  }
+
   pub(crate) fn is_common(self) -> bool {
     let epoch = self.epoch();
     (self.0 - epoch.starting_sat().0) % epoch.subsidy() != 0
@@ -96,7 +103,7 @@ impl FromStr for Sat {
 
 #[cfg(test)]
 mod tests {
-  use super::*;
+  use {super::*, bitcoin::blockdata::constants::COIN_VALUE};
 
   #[test]
   fn n() {
@@ -109,7 +116,12 @@ mod tests {
     assert_eq!(Sat(0).height(), 0);
     assert_eq!(Sat(1).height(), 0);
     assert_eq!(Sat(Epoch(0).subsidy()).height(), 1);
-    assert_eq!(Sat(Epoch(0).subsidy() * 2).height(), 2);
+    assert_eq!(Epoch(145_000).starting_sat().height(), 145_000);
+    assert_eq!(
+      (Epoch(145_000).starting_sat() + 250_000 * COIN_VALUE * 2).height(),
+      145_002
+    );
+    assert_eq!(Epoch::LAST.starting_sat().height(), 600_000);
   }
 
   #[test]
@@ -121,6 +133,7 @@ mod tests {
   fn epoch() {
     assert_eq!(Sat(0).epoch(), 0);
     assert_eq!(Sat(1).epoch(), 0);
+    assert_eq!(Sat(u64::MAX).epoch(), Epoch::LAST);
   }
 
   #[test]
@@ -135,15 +148,12 @@ mod tests {
   fn subsidy_position() {
     assert_eq!(Sat(0).third(), 0);
     assert_eq!(Sat(1).third(), 1);
+    assert_eq!(Sat(Height(0).subsidy() - 1).third(), Height(0).subsidy() - 1);
+    assert_eq!(Sat(Height(0).subsidy()).third(), 0);
+    assert_eq!(Sat(Height(0).subsidy() + 1).third(), 1);
     assert_eq!(
-      Sat(Height(0).subsidy() as u128 - 1).third(),
-      Height(0).subsidy() - 1
-    );
-    assert_eq!(Sat(Height(0).subsidy() as u128).third(), 0);
-    assert_eq!(Sat(Height(0).subsidy() as u128 + 1).third(), 1);
-    assert_eq!(
-      Sat(Epoch(1).starting_sat().n() + Epoch(1).subsidy() as u128).third(),
-      0
+      (Epoch(145_001).starting_sat() + Epoch(145_001).subsidy() + 1).third(),
+      1
     );
   }
 
@@ -205,17 +215,19 @@ mod tests {
 
   #[test]
   fn is_common() {
-    fn case(n: u128) {
+    fn case(n: u64) {
       assert_eq!(Sat(n).is_common(), Sat(n).rarity() == Rarity::Common);
     }
 
+    let epoch = Epoch(145_000).starting_sat().n();
+
     case(0);
     case(1);
-    case(50 * COIN_VALUE as u128 - 1);
-    case(50 * COIN_VALUE as u128);
-    case(50 * COIN_VALUE as u128 + 1);
-    case(2067187500000000 - 1);
-    case(2067187500000000);
-    case(2067187500000000 + 1);
+    case(epoch - 1);
+    case(epoch);
+    case(epoch + 1);
+    case(epoch + 250_000 * COIN_VALUE - 1);
+    case(epoch + 250_000 * COIN_VALUE);
+    case(epoch + 250_000 * COIN_VALUE + 1);
   }
 }
@@ -1,6 +1,7 @@
 use super::*;
 
 pub mod balances;
+mod drc20;
 pub mod epochs;
 pub mod find;
 mod index;
@@ -24,12 +25,14 @@ fn print_json(output: impl Serialize) -> Result {
 pub(crate) enum Subcommand {
   #[command(about = "List all dune balances")]
   Balances,
+  #[command(subcommand, about = "DRC-20 commands")]
+  Drc20(drc20::Drc20),
   #[command(about = "List the first satoshis of each reward epoch")]
   Epochs,
   #[command(about = "Find a satoshi's current location")]
   Find(find::Find),
-  #[command(about = "Update the index")]
-  Index,
+  #[command(about = "Update and manage the index")]
+  Index(index::IndexCommand),
   #[command(about = "Display index statistics")]
   Info(info::Info),
   #[command(about = "List the satoshis in an output")]
@@ -54,16 +57,17 @@ impl Subcommand {
   pub(crate) fn run(self, options: Options) -> SubcommandResult {
     match self {
       Self::Balances => balances::run(options),
+      Self::Drc20(drc20) => drc20.run(options),
       Self::Epochs => epochs::run(),
       Self::Find(find) => find.run(options),
-      Self::Index => index::run(options),
+      Self::Index(index) => index.run(options),
       Self::Info(info) => info.run(options),
       Self::List(list) => list.run(options),
       Self::Parse(parse) => parse.run(),
       Self::Preview(preview) => preview.run(),
       Self::Dunes => dunes::run(options),
       Self::Server(server) => {
-        let index = Arc::new(Index::open(&options)?);
+        let index = Arc::new(server.open_index(&options)?);
         let handle = axum_server::Handle::new();
         LISTENERS.lock().unwrap().push(handle.clone());
         server.run(options, index, handle)
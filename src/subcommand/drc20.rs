@@ -0,0 +1,25 @@
+use super::*;
+
+mod check;
+mod diff;
+mod snapshot;
+
+#[derive(Debug, Parser)]
+pub(crate) enum Drc20 {
+  #[command(about = "Check whether a DRC-20 operation would succeed")]
+  Check(check::Check),
+  #[command(about = "Find the first block where another indexer's DRC-20 state differs")]
+  Diff(diff::Diff),
+  #[command(about = "List the holders of a tick as of a block")]
+  Snapshot(snapshot::Snapshot),
+}
+
+impl Drc20 {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    match self {
+      Self::Check(check) => check.run(options),
+      Self::Diff(diff) => diff.run(options),
+      Self::Snapshot(snapshot) => snapshot.run(options),
+    }
+  }
+}
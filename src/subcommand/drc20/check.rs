@@ -0,0 +1,45 @@
+use {super::*, crate::drc20::script_key::ScriptKey};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Check {
+  #[arg(long, help = "Inscribe or send the operation from <FROM>.")]
+  from: Address,
+  #[arg(long, help = "Send the operation to <TO>. [default: <FROM>]")]
+  to: Option<Address>,
+  #[arg(long, help = "Check sending the transfer inscription <TRANSFER>.")]
+  transfer: Option<InscriptionId>,
+  #[arg(
+    long,
+    default_value = "text/plain;charset=utf-8",
+    help = "Treat <CONTENT> as <CONTENT_TYPE>."
+  )]
+  content_type: String,
+  #[arg(help = "Check the DRC-20 operation <CONTENT>.")]
+  content: String,
+}
+
+impl Check {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let index = Index::open(&options)?;
+    index.update()?;
+
+    let network = index.get_network()?;
+    let from = ScriptKey::from_address(self.from, network);
+    let to = match self.to {
+      Some(to) => ScriptKey::from_address(to, network),
+      None => from.clone(),
+    };
+
+    let output = index.simulate_drc20_operation(
+      &self.content_type,
+      self.content.as_bytes(),
+      self.transfer,
+      from,
+      to,
+    )?;
+
+    print_json(&output)?;
+
+    Ok(Box::new(output))
+  }
+}
@@ -0,0 +1,204 @@
+use {
+  super::*,
+  crate::drc20::StateHash,
+  bitcoin::hashes::sha256,
+  reqwest::{blocking::Response, StatusCode},
+  url::Url,
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Diff {
+  #[arg(long, help = "Compare against the ord server at <AGAINST>.")]
+  against: Url,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub compared_through: u32,
+  pub first_divergent_height: Option<u32>,
+  pub local: Option<sha256::Hash>,
+  pub remote: Option<sha256::Hash>,
+}
+
+impl Diff {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let index = Index::open(&options)?;
+    index.update()?;
+
+    let (first, last) = index
+      .get_drc20_state_hash_heights()?
+      .ok_or_else(|| anyhow!("index has no DRC-20 state hashes, run with --index-drc20"))?;
+
+    let remote = Remote::new(self.against)?;
+
+    let last = last.min(
+      remote
+        .block_count()?
+        .checked_sub(1)
+        .ok_or_else(|| anyhow!("`{}` has not indexed any blocks", remote.url))?,
+    );
+
+    ensure!(
+      first <= last,
+      "`{}` has not indexed block {first} yet",
+      remote.url
+    );
+
+    let local = |height| {
+      index
+        .get_drc20_state_hash(height)
+        .map(|state_hash| state_hash.map(|state_hash| state_hash.hash))
+    };
+
+    let first_divergent_height = first_divergence(first, last, |height| {
+      Ok(local(height)? == remote.state_hash(height)?)
+    })?;
+
+    let (local, remote) = match first_divergent_height {
+      Some(height) => (local(height)?, remote.state_hash(height)?),
+      None => (None, None),
+    };
+
+    let output = Output {
+      compared_through: last,
+      first_divergent_height,
+      local,
+      remote,
+    };
+
+    print_json(&output)?;
+
+    Ok(Box::new(output))
+  }
+}
+
+struct Remote {
+  client: reqwest::blocking::Client,
+  url: Url,
+}
+
+impl Remote {
+  fn new(mut url: Url) -> Result<Self> {
+    // Without a trailing slash, joining would replace the last path segment
+    // of servers hosted under a prefix.
+    if !url.path().ends_with('/') {
+      url.set_path(&format!("{}/", url.path()));
+    }
+
+    Ok(Self {
+      client: reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()?,
+      url,
+    })
+  }
+
+  fn get(&self, path: &str) -> Result<Option<Response>> {
+    let url = self.url.join(path)?;
+
+    let response = self
+      .client
+      .get(url.clone())
+      .send()
+      .with_context(|| format!("failed to fetch `{url}`"))?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+      return Ok(None);
+    }
+
+    Ok(Some(response.error_for_status()?))
+  }
+
+  fn block_count(&self) -> Result<u32> {
+    let response = self
+      .get("block-count")?
+      .ok_or_else(|| anyhow!("`{}` does not serve /block-count", self.url))?;
+
+    Ok(response.text()?.trim().parse()?)
+  }
+
+  /// Returns `None` if the remote has no state hash for `height`, which
+  /// counts as a mismatch.
+  fn state_hash(&self, height: u32) -> Result<Option<sha256::Hash>> {
+    self
+      .get(&format!("drc20/state-hash/{height}"))?
+      .map(|response| Ok(response.json::<StateHash>()?.hash))
+      .transpose()
+  }
+}
+
+/// Returns the first height in `first..=last` for which `matches` is false.
+/// State hashes are chained, so once two indexers differ they differ at every
+/// later height, which makes a binary search sufficient.
+fn first_divergence(
+  first: u32,
+  last: u32,
+  mut matches: impl FnMut(u32) -> Result<bool>,
+) -> Result<Option<u32>> {
+  if matches(last)? {
+    return Ok(None);
+  }
+
+  let (mut low, mut high) = (first, last);
+
+  while low < high {
+    let mid = low + (high - low) / 2;
+
+    if matches(mid)? {
+      low = mid + 1;
+    } else {
+      high = mid;
+    }
+  }
+
+  Ok(Some(high))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn divergence(first: u32, last: u32, diverged_at: u32) -> (Option<u32>, usize) {
+    let mut requests = 0;
+
+    let height = first_divergence(first, last, |height| {
+      requests += 1;
+      Ok(height < diverged_at)
+    })
+    .unwrap();
+
+    (height, requests)
+  }
+
+  #[test]
+  fn identical_indexers_have_no_divergence() {
+    assert_eq!(divergence(0, 100, 101), (None, 1));
+  }
+
+  #[test]
+  fn first_divergent_height_is_found() {
+    for diverged_at in 10..=20 {
+      assert_eq!(divergence(10, 20, diverged_at).0, Some(diverged_at));
+    }
+
+    assert_eq!(divergence(5, 5, 5), (Some(5), 1));
+  }
+
+  #[test]
+  fn divergence_is_found_by_bisection() {
+    let (height, requests) = divergence(0, 1_000_000, 123_456);
+
+    assert_eq!(height, Some(123_456));
+    assert!(requests <= 22);
+  }
+
+  #[test]
+  fn errors_are_returned() {
+    assert_eq!(
+      first_divergence(0, 10, |_| Err(anyhow!("connection refused")))
+        .unwrap_err()
+        .to_string(),
+      "connection refused"
+    );
+  }
+}
@@ -0,0 +1,123 @@
+use {
+  super::*,
+  crate::drc20::{format_balance, Tick},
+  clap::ValueEnum,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum Format {
+  Csv,
+  Json,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct Snapshot {
+  #[arg(long, help = "List holders of <TICK>.")]
+  tick: String,
+  #[arg(long, help = "List balances as of block <HEIGHT>.")]
+  height: u32,
+  #[arg(long, value_enum, default_value_t = Format::Json, help = "Print holders as <FORMAT>.")]
+  format: Format,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Holder {
+  pub script_key: String,
+  pub overall_balance: String,
+  pub transferable_balance: String,
+  pub available_balance: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub tick: String,
+  pub height: u32,
+  pub holders: Vec<Holder>,
+}
+
+impl Snapshot {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let tick = Tick::from_str(&self.tick).map_err(|err| anyhow!("invalid tick: {err}"))?;
+
+    let index = Index::open(&options)?;
+    index.update()?;
+
+    let token_info = index
+      .get_drc20_token_info(&tick)?
+      .ok_or_else(|| anyhow!("tick {tick} has not been deployed"))?;
+
+    let holders = index
+      .get_drc20_holders_at(&tick, self.height)?
+      .into_iter()
+      .map(|(script_key, balance)| Holder {
+        script_key: script_key.to_string(),
+        overall_balance: format_balance(balance.overall_balance, token_info.decimal),
+        transferable_balance: format_balance(balance.transferable_balance, token_info.decimal),
+        available_balance: format_balance(
+          balance.overall_balance - balance.transferable_balance,
+          token_info.decimal,
+        ),
+      })
+      .collect();
+
+    let output = Output {
+      tick: token_info.tick.to_string(),
+      height: self.height,
+      holders,
+    };
+
+    match self.format {
+      Format::Csv => write_csv(&mut io::stdout().lock(), &output.holders)?,
+      Format::Json => print_json(&output)?,
+    }
+
+    Ok(Box::new(output))
+  }
+}
+
+fn write_csv(writer: &mut impl io::Write, holders: &[Holder]) -> io::Result<()> {
+  writeln!(
+    writer,
+    "script_key,overall_balance,transferable_balance,available_balance"
+  )?;
+
+  for holder in holders {
+    writeln!(
+      writer,
+      "{},{},{},{}",
+      holder.script_key,
+      holder.overall_balance,
+      holder.transferable_balance,
+      holder.available_balance
+    )?;
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn holders_are_written_as_csv() {
+    let mut csv = Vec::new();
+
+    write_csv(
+      &mut csv,
+      &[Holder {
+        script_key: "DH5yaieqoZN36fDVciNyRueRGvGLR3mr7L".into(),
+        overall_balance: "100.5".into(),
+        transferable_balance: "40".into(),
+        available_balance: "60.5".into(),
+      }],
+    )
+    .unwrap();
+
+    assert_eq!(
+      String::from_utf8(csv).unwrap(),
+      "script_key,overall_balance,transferable_balance,available_balance\n\
+       DH5yaieqoZN36fDVciNyRueRGvGLR3mr7L,100.5,40,60.5\n"
+    );
+  }
+}
@@ -6,10 +6,7 @@ pub struct Output {
 }
 
 pub(crate) fn run() -> SubcommandResult {
-  let mut starting_sats = Vec::new();
-  for sat in Epoch::get_starting_sats() {
-    starting_sats.push(sat.clone());
-  }
-
-  Ok(Box::new(Output { starting_sats }))
+  Ok(Box::new(Output {
+    starting_sats: Epoch::get_starting_sats(),
+  }))
 }
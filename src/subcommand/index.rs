@@ -1,10 +1,51 @@
 use super::*;
 
+mod disable;
+mod enable;
+mod export;
+mod import;
+mod load_utxos;
+mod migrate;
+mod record;
 mod update;
 
-pub(crate) fn run(options: Options) -> SubcommandResult {
-  let index = Index::open(&options)?;
+#[derive(Debug, Parser)]
+pub(crate) struct IndexCommand {
+  #[command(subcommand)]
+  subcommand: Option<IndexSubcommand>,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) enum IndexSubcommand {
+  #[command(about = "Turn off an optional index and delete its data")]
+  Disable(disable::Disable),
+  #[command(about = "Turn on an optional index and backfill it")]
+  Enable(enable::Enable),
+  #[command(about = "Write a compressed snapshot of the index")]
+  Export(export::Export),
+  #[command(about = "Restore an empty index from a snapshot and resume indexing")]
+  Import(import::Import),
+  #[command(about = "Seed an empty index with a UTXO snapshot")]
+  LoadUtxos(load_utxos::LoadUtxos),
+  #[command(about = "Upgrade the index to the current schema")]
+  Migrate(migrate::Migrate),
+  #[command(about = "Record a range of blocks for replay with `--replay-dir`")]
+  Record(record::Record),
+  #[command(about = "Update the index")]
+  Update,
+}
 
-  index.update()?;
-  Ok(Box::new(()))
+impl IndexCommand {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    match self.subcommand {
+      Some(IndexSubcommand::Disable(disable)) => disable.run(options),
+      Some(IndexSubcommand::Enable(enable)) => enable.run(options),
+      Some(IndexSubcommand::Export(export)) => export.run(options),
+      Some(IndexSubcommand::Import(import)) => import.run(options),
+      Some(IndexSubcommand::LoadUtxos(load_utxos)) => load_utxos.run(options),
+      Some(IndexSubcommand::Migrate(migrate)) => migrate.run(options),
+      Some(IndexSubcommand::Record(record)) => record.run(options),
+      Some(IndexSubcommand::Update) | None => update::run(options),
+    }
+  }
 }
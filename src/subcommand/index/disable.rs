@@ -0,0 +1,23 @@
+use {super::*, crate::index::OptionalIndex};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Disable {
+  #[arg(long, help = "Compact the database afterwards to shrink the index file.")]
+  compact: bool,
+  #[arg(help = "Disable <INDEX> and delete its tables.")]
+  index: OptionalIndex,
+}
+
+impl Disable {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let mut index = Index::open(&options)?;
+
+    index.disable_optional_index(self.index)?;
+
+    if self.compact {
+      index.compact()?;
+    }
+
+    Ok(Box::new(Empty {}))
+  }
+}
@@ -0,0 +1,22 @@
+use {super::*, crate::index::OptionalIndex};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Enable {
+  #[arg(long, help = "Backfill from block <FROM> instead of the index's first height.")]
+  from: Option<u32>,
+  #[arg(help = "Enable <INDEX>.")]
+  index: OptionalIndex,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Output {
+  pub blocks: u32,
+}
+
+impl Enable {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let blocks = Index::open(&options)?.enable_optional_index(self.index, self.from)?;
+
+    Ok(Box::new(Output { blocks }))
+  }
+}
@@ -0,0 +1,20 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Export {
+  #[arg(help = "Write snapshot to <OUTPUT>.")]
+  output: PathBuf,
+}
+
+impl Export {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let index = Index::open(&options)?;
+
+    let file = File::create(&self.output)
+      .with_context(|| format!("failed to create snapshot `{}`", self.output.display()))?;
+
+    let header = index.export_snapshot(io::BufWriter::new(file))?;
+
+    Ok(Box::new(header))
+  }
+}
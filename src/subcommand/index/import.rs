@@ -0,0 +1,36 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Import {
+  #[arg(help = "Read snapshot from <SNAPSHOT>.")]
+  snapshot: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Output {
+  pub block_hash: BlockHash,
+  pub entries: u64,
+  pub height: u32,
+}
+
+impl Import {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let (header, entries) = {
+      let index = Index::open(&options)?;
+
+      let snapshot = File::open(&self.snapshot)
+        .with_context(|| format!("failed to open snapshot `{}`", self.snapshot.display()))?;
+
+      index.import_snapshot(io::BufReader::new(snapshot))?
+    };
+
+    // Reopen so the settings recorded in the snapshot's statistics apply.
+    Index::open(&options)?.update()?;
+
+    Ok(Box::new(Output {
+      block_hash: header.block_hash,
+      entries,
+      height: header.height,
+    }))
+  }
+}
@@ -0,0 +1,31 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct LoadUtxos {
+  #[arg(long, help = "Snapshot contains the outputs unspent as of block <HEIGHT>.")]
+  height: u32,
+  #[arg(help = "Read `<TXID>:<VOUT> <VALUE>` lines from <SNAPSHOT>.")]
+  snapshot: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Output {
+  pub height: u32,
+  pub utxos: u64,
+}
+
+impl LoadUtxos {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let index = Index::open(&options)?;
+
+    let snapshot = File::open(&self.snapshot)
+      .with_context(|| format!("failed to open UTXO snapshot `{}`", self.snapshot.display()))?;
+
+    let utxos = index.load_utxo_snapshot(self.height, io::BufReader::new(snapshot))?;
+
+    Ok(Box::new(Output {
+      height: self.height,
+      utxos,
+    }))
+  }
+}
@@ -0,0 +1,51 @@
+use {super::*, crate::index::migration};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Migrate {
+  #[arg(long, help = "List the pending migrations without running them.")]
+  dry_run: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Step {
+  pub from: u64,
+  pub to: u64,
+  pub description: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Output {
+  pub dry_run: bool,
+  pub schema_version: u64,
+  pub steps: Vec<Step>,
+}
+
+impl Migrate {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let path = Index::index_path(&options)?;
+
+    let database = unsafe { redb::Database::builder().open(&path) }
+      .with_context(|| format!("failed to open index `{}`", path.display()))?;
+
+    let schema_version = migration::schema_version(&database)?;
+
+    let migrations = if self.dry_run {
+      migration::plan(schema_version)?
+    } else {
+      migration::migrate(&database, options.chain())?
+    };
+
+    Ok(Box::new(Output {
+      dry_run: self.dry_run,
+      schema_version,
+      steps: migrations
+        .iter()
+        .map(|migration| Step {
+          from: migration.from,
+          to: migration.to,
+          description: migration.description.into(),
+        })
+        .collect(),
+    }))
+  }
+}
@@ -0,0 +1,38 @@
+use {
+  super::*,
+  crate::index::block_source::{BlockSource, DirectoryBlockSource, RpcBlockSource},
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Record {
+  #[arg(long, help = "Record blocks starting at <FROM>.")]
+  from: u32,
+  #[arg(long, help = "Record blocks up to and including <TO>.")]
+  to: u32,
+  #[arg(help = "Write blocks and the transactions they spend to <OUTPUT>.")]
+  output: PathBuf,
+}
+
+impl Record {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    if self.to < self.from {
+      bail!("--to must not be below --from");
+    }
+
+    let source = RpcBlockSource::new(
+      &options.rpc_url(),
+      Index::rpc_auth(&options)?,
+      options.nr_parallel_requests(),
+    )?;
+
+    let tip = source.tip_height()?;
+
+    if self.to > tip {
+      bail!("--to {} is above the chain tip at {tip}", self.to);
+    }
+
+    DirectoryBlockSource::record(&source, &self.output, self.from..self.to + 1)?;
+
+    Ok(Box::new(Empty {}))
+  }
+}
@@ -27,7 +27,7 @@ impl List {
     match index.list(self.outpoint)? {
       Some(crate::index::List::Unspent(ranges)) => {
         let mut outputs = Vec::new();
-        for (output, start, size, rarity) in list(self.outpoint, ranges) {
+        for (output, start, size, rarity) in list(&index.schedule(), self.outpoint, ranges) {
           outputs.push(Output {
             output,
             start,
@@ -44,12 +44,16 @@ impl List {
   }
 }
 
-fn list(outpoint: OutPoint, ranges: Vec<(u64, u64)>) -> Vec<(OutPoint, u64, u64, Rarity)> {
+fn list(
+  schedule: &Schedule,
+  outpoint: OutPoint,
+  ranges: Vec<(u64, u64)>,
+) -> Vec<(OutPoint, u64, u64, Rarity)> {
   ranges
     .into_iter()
     .map(|(start, end)| {
       let size = u64::try_from(end - start).unwrap();
-      let rarity = Sat(start).rarity();
+      let rarity = schedule.rarity(Sat(start));
 
       (outpoint, start, size, rarity)
     })
@@ -72,7 +76,7 @@ mod tests {
       (1050000000000000 as u128, 1150000000000000 as u128),
     ];
     assert_eq!(
-      list(outpoint, ranges),
+      list(Schedule::mainnet(), outpoint, ranges),
       vec![
         (
           OutPoint::from_str("1a91e3dace36e2be3bf030a65679fe821aa1d6ef92e7c9902eb318182c355691:5")
@@ -4,16 +4,21 @@ use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use serde_json::json;
 use {
   self::{
+    byte_range::ByteRange,
+    cursor::Cursor,
     deserialize_from_str::DeserializeFromStr,
     error::{OptionExt, ServerError, ServerResult},
+    replica::Publisher,
   },
   super::*,
   crate::{
-    drc20::{script_key::ScriptKey, Tick},
+    drc20::{script_key::ScriptKey, StateHash, Tick},
+    index::entry::Entry,
     page_config::PageConfig,
     templates::{
-      AddressOutputJson, BlockHtml, BlockJson, DuneAddressJson, DuneBalance, DuneBalancesHtml,
-      DuneEntryJson, DuneHtml, DuneJson, DuneOutput, DuneOutputJson, DunesHtml, HomeHtml,
+      AddressHtml, AddressOutputJson, BlockHtml, BlockJson, Drc20TickHtml, Drc20TickSummary,
+      Drc20TicksHtml, DuneAddressJson, DuneBalance, DuneBalancesHtml, DuneEntryJson, DuneHtml,
+      DuneJson, DuneOutput, DuneOutputJson, DunesHtml, HomeHtml,
       InputHtml, InscriptionByAddressJson, InscriptionHtml, InscriptionJson, InscriptionsHtml,
       OutputHtml, OutputJson, PageContent, PageHtml, PreviewAudioHtml, PreviewImageHtml,
       PreviewModelHtml, PreviewPdfHtml, PreviewTextHtml, Operation, PreviewUnknownHtml, PreviewVideoHtml,
@@ -25,8 +30,9 @@ use {
     extract::{Extension, Json, Path, Query},
     headers::UserAgent,
     http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
+    middleware::{self, Next},
     response::{IntoResponse, Redirect, Response},
-    routing::get,
+    routing::{get, post},
     Router, TypedHeader,
   },
   axum_server::Handle,
@@ -39,20 +45,29 @@ use {
   },
   serde_json::to_string,
   std::collections::HashMap,
-  std::{cmp::Ordering, str},
+  std::{cmp::Ordering, str, sync::RwLock},
   tokio_stream::StreamExt,
   tower_http::{
-    compression::CompressionLayer,
+    compression::{
+      predicate::{DefaultPredicate, Predicate},
+      CompressionLayer,
+    },
     cors::{Any, CorsLayer},
     set_header::SetResponseHeaderLayer,
   },
 };
+use crate::drc20::{format_balance, format_balance_change};
 use crate::drc20::operation::{deserialize_drc20_operation, Action};
-use crate::drc20::token_info::{ExtendedTokenInfo, HolderBalanceForTick, HoldersInfoForTick};
-use crate::templates::{DRC20Balance, DRC20Output, DRC20UtxoOutput};
+use crate::drc20::token_info::{
+  ExtendedTokenInfo, HolderBalanceForTick, HoldersInfoForTick, TickInfo,
+};
+use crate::templates::{DRC20Balance, DRC20Output, DRC20PendingBalance, DRC20UtxoOutput};
 
+mod byte_range;
+mod cursor;
 mod error;
 mod query;
+mod replica;
 
 // Helper function to get transaction details
 fn get_transaction_details(
@@ -105,19 +120,24 @@ fn get_transaction_details(
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct InscriptionAddressJson {
   pub(crate) inscriptions: Vec<InscriptionByAddressJson>,
-  pub(crate) total_inscriptions: usize,
+  pub(crate) total_inscriptions: Option<usize>,
+  pub(crate) height: u32,
+  pub(crate) next_cursor: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct UtxoAddressJson {
   pub(crate) utxos: Vec<Utxo>,
-  pub(crate) total_utxos: usize,
-  pub(crate) total_shibes: u128,
-  pub(crate) total_inscription_shibes: u128,
+  pub(crate) total_utxos: Option<usize>,
+  pub(crate) total_shibes: Option<u128>,
+  pub(crate) total_inscription_shibes: Option<u128>,
+  pub(crate) height: u32,
+  pub(crate) next_cursor: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct UtxoBalanceQuery {
+  cursor: Option<DeserializeFromStr<Cursor>>,
   limit: Option<usize>,
   show_all: Option<bool>,
   show_unsafe: Option<bool>,
@@ -126,17 +146,34 @@ struct UtxoBalanceQuery {
 
 #[derive(Deserialize)]
 struct Drc20TickInfoQuery {
+  json: Option<bool>,
   show_holder: Option<bool>,
 }
 
+#[derive(Deserialize)]
+struct Drc20TickHolderQuery {
+  cursor: Option<DeserializeFromStr<Cursor>>,
+  limit: Option<usize>,
+  height: Option<u32>,
+}
+
 #[derive(Deserialize)]
 struct Drc20BalanceQuery {
+  pending: Option<bool>,
   show_all: Option<bool>,
   show_utxos: Option<bool>,
   tick: Option<String>,
   value_filter: Option<u64>,
 }
 
+#[derive(Deserialize)]
+struct Drc20SimulateQuery {
+  content_type: Option<String>,
+  from: String,
+  to: Option<String>,
+  transfer: Option<InscriptionId>,
+}
+
 #[derive(Deserialize)]
 struct OutputsQuery {
   outputs: String,
@@ -189,6 +226,8 @@ struct ValidityQuery {
 
 #[derive(Deserialize)]
 struct DunesBalanceQuery {
+  cursor: Option<DeserializeFromStr<Cursor>>,
+  limit: Option<usize>,
   show_all: Option<bool>,
   list_dunes: Option<bool>,
   filter: Option<SpacedDune>,
@@ -199,6 +238,34 @@ struct Search {
   query: String,
 }
 
+#[derive(Deserialize)]
+struct SuggestQuery {
+  q: Option<String>,
+}
+
+const SUGGESTION_LIMIT: usize = 10;
+
+/// Page size of the deprecated `/:page` balance routes.
+const LEGACY_PAGE_SIZE: usize = 10;
+
+const ADDRESS_ACTIVITY: usize = 10;
+
+/// Number of an address's most recent outputs whose inscriptions and dunes are
+/// shown on its page.
+const ADDRESS_OUTPUT_SCAN: usize = 1000;
+
+const DRC20_TOP_HOLDERS: usize = 20;
+
+const DRC20_RECENT_ACTIVITY: usize = 20;
+
+const CONTENT_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SuggestJson {
+  pub(crate) dunes: Vec<SpacedDune>,
+  pub(crate) ticks: Vec<String>,
+}
+
 #[derive(RustEmbed)]
 #[folder = "static"]
 struct StaticAssets;
@@ -254,23 +321,106 @@ pub(crate) struct Server {
   https: bool,
   #[clap(long, help = "Redirect HTTP traffic to HTTPS.")]
   redirect_http_to_https: bool,
+  #[clap(
+    long,
+    default_value = "100",
+    help = "Return at most <PAGE_SIZE> entries per page from paginated endpoints."
+  )]
+  page_size: usize,
+  #[clap(
+    long,
+    requires = "replica_of",
+    help = "Serve snapshots published by another server instead of updating the index."
+  )]
+  read_only: bool,
+  #[clap(
+    long,
+    requires = "read_only",
+    help = "Serve the newest snapshot published to <REPLICA_OF> by `ord server --publish`, switching to each new one as it appears. Every replica needs its own --data-dir."
+  )]
+  replica_of: Option<PathBuf>,
+  #[clap(
+    long,
+    conflicts_with = "read_only",
+    help = "Publish a snapshot of the index to <PUBLISH> whenever a new block is indexed, at most once every <PUBLISH_INTERVAL> seconds."
+  )]
+  publish: Option<PathBuf>,
+  #[clap(
+    long,
+    default_value = "600",
+    requires = "publish",
+    help = "Wait at least <PUBLISH_INTERVAL> seconds between snapshots. Each snapshot is a full export of the index, during which indexing pauses, so use several times as long as `ord index export` takes."
+  )]
+  publish_interval: u64,
 }
 
 impl Server {
+  pub(crate) fn open_index(&self, options: &Options) -> Result<Index> {
+    match &self.replica_of {
+      Some(dir) => replica::load(dir, options)?
+        .ok_or_else(|| anyhow!("no snapshot has been published to `{}` yet", dir.display())),
+      None => Index::open(options),
+    }
+  }
+
   pub(crate) fn run(self, options: Options, index: Arc<Index>, handle: Handle) -> SubcommandResult {
     Runtime::new()?.block_on(async {
-      let index_clone = index.clone();
+      let index_sats = index.has_sat_index();
 
-      let index_thread = thread::spawn(move || loop {
-        if SHUTTING_DOWN.load(atomic::Ordering::Relaxed) {
-          break;
-        }
-        if let Err(error) = index_clone.update() {
-          log::warn!("{error}");
-        }
-        thread::sleep(Duration::from_millis(5000));
-      });
-      INDEXER.lock().unwrap().replace(index_thread);
+      // Replicas swap in a new index after every snapshot, so handlers get
+      // whichever one is current when their request arrives.
+      let index = Arc::new(RwLock::new(index));
+
+      let index_thread = if let Some(dir) = self.replica_of.clone() {
+        let index = index.clone();
+        let options = options.clone();
+
+        Some(thread::spawn(move || loop {
+          if SHUTTING_DOWN.load(atomic::Ordering::Relaxed) {
+            break;
+          }
+          match replica::refresh(&dir, &options, &index) {
+            Ok(Some(name)) => log::info!("Serving snapshot `{name}`"),
+            Ok(None) => {}
+            Err(error) => log::warn!("{error}"),
+          }
+          thread::sleep(Duration::from_millis(5000));
+        }))
+      } else {
+        let index_clone = index.read().unwrap().clone();
+        let interval = Duration::from_secs(self.publish_interval);
+        let mut publisher = self
+          .publish
+          .clone()
+          .map(|dir| Publisher::new(dir, interval))
+          .transpose()?;
+
+        Some(thread::spawn(move || loop {
+          if SHUTTING_DOWN.load(atomic::Ordering::Relaxed) {
+            break;
+          }
+          if let Err(error) = index_clone.update() {
+            log::warn!("{error}");
+          } else {
+            if let Err(error) = index_clone.update_drc20_pending() {
+              log::warn!("failed to index mempool: {error}");
+            }
+
+            if let Some(publisher) = &mut publisher {
+              match publisher.publish(&index_clone) {
+                Ok(Some(name)) => log::info!("Published snapshot `{name}`"),
+                Ok(None) => {}
+                Err(error) => log::warn!("failed to publish snapshot: {error}"),
+              }
+            }
+          }
+          thread::sleep(Duration::from_millis(5000));
+        }))
+      };
+
+      if let Some(index_thread) = index_thread {
+        INDEXER.lock().unwrap().replace(index_thread);
+      }
 
       let config = options.load_config()?;
       let acme_domains = self.acme_domains()?;
@@ -278,8 +428,9 @@ impl Server {
       let page_config = Arc::new(PageConfig {
         chain: options.chain(),
         domain: acme_domains.first().cloned(),
-        index_sats: index.has_sat_index(),
+        index_sats,
         csp_origin: options.csp_origin(),
+        page_size: self.page_size.max(1),
       });
 
       let router = Router::new()
@@ -311,32 +462,33 @@ impl Server {
         .route("/ordinal/:sat", get(Self::ordinal))
         .route("/output/:output", get(Self::output))
         .route("/outputs/:output_list", get(Self::outputs))
-        .route("/address/:address", get(Self::outputs_by_address))
+        .route("/address/:address", get(Self::address))
         .route("/preview/:inscription_id", get(Self::preview))
         .route("/range/:start/:end", get(Self::range))
         .route("/rare.txt", get(Self::rare_txt))
         .route("/dune/:dune", get(Self::dune))
         .route("/dunes", get(Self::dunes))
         .route("/dunes/balances", get(Self::dunes_balances))
+        .route("/dunes/balance/:address", get(Self::dunes_by_address))
         .route(
-          "/dunes/balance/:address",
-          get(Self::dunes_by_address_unpaginated),
+          "/dunes/balance/:address/:page",
+          get(Self::dunes_by_address_page),
         )
-        .route("/dunes/balance/:address/:page", get(Self::dunes_by_address))
+        .route("/utxos/balance/:address", get(Self::utxos_by_address))
         .route(
-          "/utxos/balance/:address",
-          get(Self::utxos_by_address_unpaginated),
+          "/utxos/balance/:address/:page",
+          get(Self::utxos_by_address_page),
         )
-        .route("/utxos/balance/:address/:page", get(Self::utxos_by_address))
         .route(
           "/inscriptions/balance/:address",
-          get(Self::inscriptions_by_address_unpaginated),
+          get(Self::inscriptions_by_address),
         )
         .route(
           "/inscriptions/balance/:address/:page",
-          get(Self::inscriptions_by_address),
+          get(Self::inscriptions_by_address_page),
         )
         .route("/inscriptions/validate", get(Self::inscriptions_validate))
+        .route("/drc20", get(Self::drc20_ticks))
         .route("/drc20/tick/:tick", get(Self::drc20_tick_info))
         .route("/drc20/tick", get(Self::drc20_all_tick_info))
         .route(
@@ -344,16 +496,26 @@ impl Server {
             get(Self::drc20_by_address_unpaginated),
         )
         .route("/drc20/validate", get(Self::drc20_validate))
+        .route("/drc20/simulate", post(Self::drc20_simulate))
+        .route("/drc20/state-hash/:height", get(Self::drc20_state_hash))
         .route("/drc20/ticks", get(Self::drc20_all_ticks))
         .route("/drc20/tick/holder/:tick", get(Self::drc20_tick_holder))
         .route("/dunes_on_outputs", get(Self::dunes_by_outputs))
         .route("/sat/:sat", get(Self::sat))
         .route("/search", get(Self::search_by_query))
+        .route("/search/suggest", get(Self::search_suggest))
         .route("/search/*query", get(Self::search_by_path))
         .route("/static/*path", get(Self::static_asset))
         .route("/status", get(Self::status))
         .route("/tx/:txid", get(Self::transaction))
-        .layer(Extension(index))
+        .layer(middleware::from_fn(
+          move |mut request: http::Request<body::Body>, next: Next<body::Body>| {
+            request
+              .extensions_mut()
+              .insert(index.read().unwrap().clone());
+            next.run(request)
+          },
+        ))
         .layer(Extension(page_config))
         .layer(Extension(Arc::new(config)))
         .layer(SetResponseHeaderLayer::if_not_present(
@@ -369,7 +531,11 @@ impl Server {
             .allow_methods([http::Method::GET])
             .allow_origin(Any),
         )
-        .layer(CompressionLayer::new());
+        .layer(
+          CompressionLayer::new()
+            .compress_when(DefaultPredicate::new().and(Self::not_partial_content)),
+        )
+        .layer(middleware::map_response(Self::weaken_compressed_etag));
 
       match (self.http_port(), self.https_port()) {
         (Some(http_port), None) => {
@@ -543,16 +709,23 @@ impl Server {
   async fn sat(
     Extension(page_config): Extension<Arc<PageConfig>>,
     Extension(index): Extension<Arc<Index>>,
-    Path(DeserializeFromStr(sat)): Path<DeserializeFromStr<Sat>>,
+    Path(sat): Path<String>,
   ) -> ServerResult<PageHtml<SatHtml>> {
+    let schedule = index.schedule();
+
+    let sat = schedule
+      .parse_sat(&sat)
+      .map_err(|err| ServerError::BadRequest(err.to_string()))?;
+
     let satpoint = index.rare_sat_satpoint(sat)?;
 
     Ok(
       SatHtml {
         sat,
         satpoint,
-        blocktime: index.blocktime(sat.height())?,
+        blocktime: index.blocktime(schedule.height(sat))?,
         inscription: index.get_inscription_id_by_sat(sat)?,
+        schedule,
       }
       .page(page_config),
     )
@@ -604,111 +777,246 @@ impl Server {
         chain: page_config.chain,
         output,
         dunes,
+        schedule: index.schedule(),
       }
       .page(page_config),
     )
   }
 
+  /// Walks the outputs of `address` and their values in outpoint order,
+  /// starting after `cursor`, until `visit` reports that the page is full.
+  /// Each batch is a range scan from the last output seen, so a page only
+  /// reads the outputs it covers. Returns the height the page was read at
+  /// and, if outputs remain, the cursor for the next page.
+  fn scan_account_outputs(
+    index: &Index,
+    address: &str,
+    cursor: Option<&Cursor>,
+    limit: usize,
+    mut visit: impl FnMut(OutPoint, u64) -> ServerResult<bool>,
+  ) -> ServerResult<(u32, Option<String>)> {
+    Index::indexed_address(address).map_err(|err| ServerError::BadRequest(err.to_string()))?;
+
+    let height = index.block_count()?;
+
+    let mut after = cursor
+      .map(|cursor| {
+        <[u8; 36]>::try_from(cursor.after.as_slice())
+          .map(OutPoint::load)
+          .map_err(|_| ServerError::BadRequest("invalid cursor".into()))
+      })
+      .transpose()?;
+
+    loop {
+      let (outputs, more) = index.get_account_outputs_page(address, after, limit)?;
+      let len = outputs.len();
+
+      for (i, (outpoint, value)) in outputs.into_iter().enumerate() {
+        after = Some(outpoint);
+
+        if visit(outpoint, value)? {
+          let next_cursor = (more || i + 1 < len)
+            .then(|| Cursor::new(outpoint.store().to_vec()).to_string());
+          return Ok((height, next_cursor));
+        }
+      }
+
+      if !more {
+        return Ok((height, None));
+      }
+    }
+  }
+
+  /// Visits every output of `address` and its value, for listings that aren't
+  /// paged.
+  fn for_each_account_output(
+    index: &Index,
+    address: &str,
+    mut visit: impl FnMut(OutPoint, u64) -> ServerResult<()>,
+  ) -> ServerResult<()> {
+    Index::indexed_address(address).map_err(|err| ServerError::BadRequest(err.to_string()))?;
+
+    for (outpoint, value) in index.get_account_outputs_page(address, None, usize::MAX)?.0 {
+      visit(outpoint, value)?;
+    }
+
+    Ok(())
+  }
+
+  /// Whether a page read from the start without skipping anything, that left
+  /// no `next_cursor`, covered every output of an address. Only such pages
+  /// carry totals, which would otherwise take a pass over every output.
+  fn covers_every_output(
+    cursor: Option<&DeserializeFromStr<Cursor>>,
+    skip: usize,
+    next_cursor: &Option<String>,
+  ) -> bool {
+    cursor.is_none() && skip == 0 && next_cursor.is_none()
+  }
+
+  fn page_limit(page_config: &PageConfig, limit: Option<usize>) -> usize {
+    limit
+      .unwrap_or(page_config.page_size)
+      .clamp(1, page_config.page_size)
+  }
+
+  /// The page size of a cursor-paged listing, or no limit with `show_all`.
+  fn listing_limit(
+    page_config: &PageConfig,
+    limit: Option<usize>,
+    show_all: Option<bool>,
+  ) -> usize {
+    if show_all.unwrap_or_default() {
+      usize::MAX
+    } else {
+      Self::page_limit(page_config, limit)
+    }
+  }
+
+  /// The page size and the number of entries to skip for the deprecated
+  /// `/:page` routes, where pages count from 1 and hold `limit` entries, 10 by
+  /// default.
+  fn legacy_page(page: usize, limit: Option<usize>, show_all: Option<bool>) -> (usize, usize) {
+    if show_all.unwrap_or_default() {
+      return (usize::MAX, 0);
+    }
+
+    let limit = limit.unwrap_or(LEGACY_PAGE_SIZE).max(1);
+
+    (limit, page.saturating_sub(1).saturating_mul(limit))
+  }
+
+  /// Marks the response of a route that is only kept for existing clients.
+  fn deprecated(response: impl IntoResponse) -> Response {
+    (
+      [(
+        HeaderName::from_static("deprecation"),
+        HeaderValue::from_static("true"),
+      )],
+      response,
+    )
+      .into_response()
+  }
+
+  /// Whether an output of `value` is listed by `/utxos/balance`, and whether
+  /// it carries inscriptions.
+  fn utxo_status(
+    index: &Index,
+    outpoint: OutPoint,
+    value: u64,
+    value_filter: u64,
+    show_unsafe: bool,
+  ) -> ServerResult<(bool, bool)> {
+    if !index.get_dune_balances_for_outpoint(outpoint)?.is_empty()
+      || (value_filter > 0 && value <= value_filter)
+    {
+      return Ok((false, false));
+    }
+
+    let inscribed = !index.get_inscriptions_on_output(outpoint)?.is_empty();
+
+    Ok((show_unsafe || !inscribed, inscribed))
+  }
+
   async fn utxos_by_address(
+    Extension(page_config): Extension<Arc<PageConfig>>,
     Extension(index): Extension<Arc<Index>>,
-    Path(params): Path<(String, u32)>,
+    Path(address): Path<String>,
     Query(query): Query<UtxoBalanceQuery>,
   ) -> ServerResult<Response> {
-    Self::get_utxos_by_address(index, params.0, Some(params.1), query).await
+    let limit = Self::listing_limit(&page_config, query.limit, query.show_all);
+    let utxos = Self::get_utxos_by_address(&index, &address, &query, limit, 0)?;
+    Ok(Json(utxos).into_response())
   }
 
-  async fn utxos_by_address_unpaginated(
+  /// Deprecated in favor of `/utxos/balance/:address` and its `next_cursor`.
+  async fn utxos_by_address_page(
     Extension(index): Extension<Arc<Index>>,
-    Path(params): Path<String>,
+    Path((address, page)): Path<(String, usize)>,
     Query(query): Query<UtxoBalanceQuery>,
   ) -> ServerResult<Response> {
-    Self::get_utxos_by_address(index, params, None, query).await
-  }
-
-  async fn get_utxos_by_address(
-    index: Arc<Index>,
-    address: String,
-    page: Option<u32>,
-    query: UtxoBalanceQuery,
-  ) -> ServerResult<Response> {
-    let (address, page) = (address, page.unwrap_or(0));
-    let show_all = query.show_all.unwrap_or(false);
+    let (limit, skip) = Self::legacy_page(page, query.limit, query.show_all);
+    let utxos = Self::get_utxos_by_address(&index, &address, &query, limit, skip)?;
+    Ok(Self::deprecated(Json(utxos)))
+  }
+
+  fn get_utxos_by_address(
+    index: &Index,
+    address: &str,
+    query: &UtxoBalanceQuery,
+    limit: usize,
+    skip: usize,
+  ) -> ServerResult<UtxoAddressJson> {
     let value_filter = query.value_filter.unwrap_or(0);
     let show_unsafe = query.show_unsafe.unwrap_or(false);
 
-    let items_per_page = query.limit.unwrap_or(10);
-    let page = page as usize;
-    let start_index = if page == 0 || page == 1 {
-      0
-    } else {
-      (page - 1) * items_per_page + 1
-    };
-    let mut element_counter = 0;
-
-    let outpoints: Vec<OutPoint> = index.get_account_outputs(address.clone())?;
-
-    let mut utxos = Vec::new();
     let mut total_shibes = 0u128;
-    let mut inscription_shibes = 0u128;
-
-    for outpoint in outpoints {
-      if !index.get_dune_balances_for_outpoint(outpoint)?.is_empty() {
-        continue;
-      }
-      if !show_all
-        && (element_counter < start_index || element_counter > start_index + items_per_page - 1)
-      {
-        continue;
-      }
+    let mut total_inscription_shibes = 0u128;
+    let mut utxos = Vec::new();
+    let mut remaining = skip;
+
+    let (height, next_cursor) = Self::scan_account_outputs(
+      index,
+      address,
+      query.cursor.as_ref().map(|cursor| &cursor.0),
+      limit,
+      |outpoint, value| {
+        let (listed, inscribed) =
+          Self::utxo_status(index, outpoint, value, value_filter, show_unsafe)?;
+
+        if inscribed {
+          total_inscription_shibes += u128::from(value);
+        }
 
-      let txid = outpoint.txid;
-      let vout = outpoint.vout;
-      let output = index
-        .get_transaction(txid)?
-        .ok_or_not_found(|| format!("{txid} current transaction"))?
-        .output
-        .into_iter()
-        .nth(vout.try_into().unwrap())
-        .ok_or_not_found(|| format!("{vout} current transaction output"))?;
+        if !listed {
+          return Ok(false);
+        }
 
-      if value_filter > 0 && output.value <= value_filter {
-        continue;
-      }
+        total_shibes += u128::from(value);
 
-      if !index.get_inscriptions_on_output(outpoint)?.is_empty() {
-        inscription_shibes += output.value as u128;
-        if !show_unsafe {
-          continue;
+        if remaining > 0 {
+          remaining -= 1;
+          return Ok(false);
         }
-      }
 
-      element_counter += 1;
+        let txid = outpoint.txid;
+        let vout = outpoint.vout;
+        let output = index
+          .get_transaction(txid)?
+          .ok_or_not_found(|| format!("{txid} current transaction"))?
+          .output
+          .into_iter()
+          .nth(vout.try_into().unwrap())
+          .ok_or_not_found(|| format!("{vout} current transaction output"))?;
 
-      total_shibes += output.value as u128;
+        let confirmations = if let Some(block_hash_info) = index.get_transaction_blockhash(txid)? {
+          block_hash_info.confirmations
+        } else {
+          None
+        };
 
-      let confirmations = if let Some(block_hash_info) = index.get_transaction_blockhash(txid)? {
-        block_hash_info.confirmations
-      } else {
-        None
-      };
+        utxos.push(Utxo {
+          txid,
+          vout,
+          script: output.script_pubkey,
+          shibes: output.value,
+          confirmations,
+        });
 
-      utxos.push(Utxo {
-        txid,
-        vout,
-        script: output.script_pubkey,
-        shibes: output.value,
-        confirmations,
-      });
-    }
-    Ok(
-      Json(UtxoAddressJson {
-        utxos,
-        total_shibes,
-        total_utxos: element_counter,
-        total_inscription_shibes: inscription_shibes,
-      })
-      .into_response(),
-    )
+        Ok(utxos.len() >= limit)
+      },
+    )?;
+
+    let complete = Self::covers_every_output(query.cursor.as_ref(), skip, &next_cursor);
+
+    Ok(UtxoAddressJson {
+      total_utxos: complete.then_some(utxos.len()),
+      total_shibes: complete.then_some(total_shibes),
+      total_inscription_shibes: complete.then_some(total_inscription_shibes),
+      utxos,
+      height,
+      next_cursor,
+    })
   }
 
   async fn drc20_by_address(
@@ -737,6 +1045,7 @@ impl Server {
       let (address, page) = (address.clone(), page.unwrap_or(0));
       let address_from_str =
         Address::from_str(&address).map_err(|err| ServerError::BadRequest(err.to_string()))?;
+      Index::indexed_address(&address).map_err(|err| ServerError::BadRequest(err.to_string()))?;
       let value_filter = query.value_filter.unwrap_or(0);
       let show_utxos = query.show_utxos.unwrap_or(true);
 
@@ -865,11 +1174,10 @@ impl Server {
 
       let mut drc20balances: Vec<DRC20Balance> = Vec::new();
 
+      let script_key = ScriptKey::from_address(address_from_str, index.get_network()?);
+
       let balance = index
-        .get_drc20_balances(&ScriptKey::from_address(
-          address_from_str,
-          index.get_network()?,
-        ))
+        .get_drc20_balances(&script_key)
         .map_err(|err| ServerError::BadRequest(err.to_string()))?;
 
       if !balance.is_empty() {
@@ -917,210 +1225,280 @@ impl Server {
           }
         }
       }
-      Ok(Json(json!({"drc20": drc20balances})).into_response())
+
+      if !query.pending.unwrap_or(false) {
+        return Ok(Json(json!({"drc20": drc20balances})).into_response());
+      }
+
+      let mut pending = Vec::new();
+
+      for change in index.get_drc20_pending_balances(&script_key) {
+        if let Some(filter) = &query.tick {
+          if filter != change.tick.as_str() {
+            continue;
+          }
+        }
+
+        pending.push(DRC20PendingBalance {
+          tick: change.tick.to_string(),
+          transferable: format_balance_change(change.transferable, change.decimal),
+          available: format_balance_change(change.overall - change.transferable, change.decimal),
+        });
+      }
+
+      Ok(Json(json!({"drc20": drc20balances, "pending": pending})).into_response())
     })
   }
 
   async fn inscriptions_by_address(
+    Extension(page_config): Extension<Arc<PageConfig>>,
     Extension(index): Extension<Arc<Index>>,
-    Path(params): Path<(String, u32)>,
+    Path(address): Path<String>,
     Query(query): Query<UtxoBalanceQuery>,
   ) -> ServerResult<Response> {
-    Self::get_inscriptions_by_address(index, params.0, Some(params.1), query).await
+    let limit = Self::listing_limit(&page_config, query.limit, query.show_all);
+    let inscriptions = Self::get_inscriptions_by_address(&index, &address, &query, limit, 0)?;
+    Ok(Json(inscriptions).into_response())
   }
 
-  async fn inscriptions_by_address_unpaginated(
+  /// Deprecated in favor of `/inscriptions/balance/:address` and its
+  /// `next_cursor`.
+  async fn inscriptions_by_address_page(
     Extension(index): Extension<Arc<Index>>,
-    Path(params): Path<String>,
+    Path((address, page)): Path<(String, usize)>,
     Query(query): Query<UtxoBalanceQuery>,
   ) -> ServerResult<Response> {
-    Self::get_inscriptions_by_address(index, params, None, query).await
-  }
-
-  async fn get_inscriptions_by_address(
-    index: Arc<Index>,
-    address: String,
-    page: Option<u32>,
-    query: UtxoBalanceQuery,
-  ) -> ServerResult<Response> {
-    let (address, page) = (address, page.unwrap_or(0));
-    let show_all = query.show_all.unwrap_or(false);
+    let (limit, skip) = Self::legacy_page(page, query.limit, query.show_all);
+    let inscriptions = Self::get_inscriptions_by_address(&index, &address, &query, limit, skip)?;
+    Ok(Self::deprecated(Json(inscriptions)))
+  }
+
+  fn get_inscriptions_by_address(
+    index: &Index,
+    address: &str,
+    query: &UtxoBalanceQuery,
+    limit: usize,
+    skip: usize,
+  ) -> ServerResult<InscriptionAddressJson> {
     let value_filter = query.value_filter.unwrap_or(0);
 
-    let items_per_page = query.limit.unwrap_or(10);
-    let page = page as usize;
-    let start_index = if page == 0 || page == 1 {
-      0
-    } else {
-      (page - 1) * items_per_page + 1
-    };
-    let mut element_counter = 0;
-
     let mut all_inscriptions_json = Vec::new();
-    let outpoints: Vec<OutPoint> = index.get_account_outputs(address)?;
-
-    for outpoint in outpoints {
-      let inscriptions = index.get_inscriptions_on_output(outpoint)?;
-
-      if inscriptions.is_empty() {
-        continue;
-      }
-
-      element_counter += 1;
-      if !show_all
-        && (element_counter < start_index || element_counter > start_index + items_per_page - 1)
-      {
-        continue;
-      }
-
-      let txid = outpoint.txid;
-      let vout = outpoint.vout;
-
-      let output = index
-        .get_transaction(txid)?
-        .ok_or_not_found(|| format!("dunes {txid} current transaction"))?
-        .output
-        .into_iter()
-        .nth(vout.try_into().unwrap())
-        .ok_or_not_found(|| format!("dunes {vout} current transaction output"))?;
-      let shibes = output.value;
-      let script = output.script_pubkey;
+    let mut remaining = skip;
+
+    let (height, next_cursor) = Self::scan_account_outputs(
+      index,
+      address,
+      query.cursor.as_ref().map(|cursor| &cursor.0),
+      limit,
+      |outpoint, value| {
+        if value_filter > 0 && value <= value_filter {
+          return Ok(false);
+        }
 
-      if value_filter > 0 && shibes <= value_filter {
-        element_counter -= 1;
-        continue;
-      }
+        let inscriptions = index.get_inscriptions_on_output(outpoint)?;
 
-      for inscription_id in inscriptions {
-        let inscription = index
-          .get_inscription_by_id(inscription_id)?
-          .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
-
-        let entry = index
-          .get_inscription_entry(inscription_id)?
-          .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
+        if inscriptions.is_empty() {
+          return Ok(false);
+        }
 
-        let satpoint = index
-          .get_inscription_satpoint_by_id(inscription_id)?
-          .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
+        let txid = outpoint.txid;
+        let vout = outpoint.vout;
 
-        let content_type = inscription.content_type().map(|s| s.to_string());
-        let content_length = inscription.content_length();
-        let content = inscription.into_body();
+        let output = index
+          .get_transaction(txid)?
+          .ok_or_not_found(|| format!("dunes {txid} current transaction"))?
+          .output
+          .into_iter()
+          .nth(vout.try_into().unwrap())
+          .ok_or_not_found(|| format!("dunes {vout} current transaction output"))?;
+        let shibes = output.value;
+        let script = output.script_pubkey;
+
+        for inscription_id in inscriptions {
+          let inscription = index
+            .get_inscription_by_id(inscription_id)?
+            .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
+
+          let entry = index
+            .get_inscription_entry(inscription_id)?
+            .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
+
+          let satpoint = index
+            .get_inscription_satpoint_by_id(inscription_id)?
+            .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
+
+          let content_type = inscription.content_type().map(|s| s.to_string());
+          let content_length = inscription.content_length();
+          let content = inscription.into_body();
+
+          let str_content = match (content_type.clone(), content) {
+            (Some(ref ct), Some(c))
+              if ct.starts_with("application/json") || ct.starts_with("text") =>
+            {
+              Some(String::from_utf8_lossy(c.as_slice()).to_string())
+            }
+            (None, Some(c)) => Some(String::from_utf8_lossy(c.as_slice()).to_string()),
+            _ => None,
+          };
 
-        let str_content = match (content_type.clone(), content) {
-          (Some(ref ct), Some(c))
-            if ct.starts_with("application/json") || ct.starts_with("text") =>
-          {
-            Some(String::from_utf8_lossy(c.as_slice()).to_string())
-          }
-          (None, Some(c)) => Some(String::from_utf8_lossy(c.as_slice()).to_string()),
-          _ => None,
-        };
+          if let Some(content) = str_content.clone() {
+            if DRC20::from_json_string(content.as_str()).is_some() {
+              continue;
+            }
+          };
 
-        if let Some(content) = str_content.clone() {
-          let drc20 = DRC20::from_json_string(content.as_str());
-          if drc20.is_some() {
-            element_counter = element_counter.checked_sub(1).unwrap_or(0);
+          if remaining > 0 {
+            remaining -= 1;
             continue;
           }
-        };
 
-        let confirmations = if let Some(block_hash_info) = index.get_transaction_blockhash(txid)? {
-          block_hash_info.confirmations
-        } else {
-          None
-        };
+          let confirmations =
+            if let Some(block_hash_info) = index.get_transaction_blockhash(txid)? {
+              block_hash_info.confirmations
+            } else {
+              None
+            };
 
-        let inscription_json = InscriptionByAddressJson {
-          utxo: Utxo {
-            txid,
-            vout,
-            script: script.clone(),
-            shibes,
-            confirmations,
-          },
-          content: str_content,
-          content_length,
-          content_type,
-          genesis_height: entry.height,
-          inscription_id,
-          inscription_number: entry.inscription_number,
-          timestamp: entry.timestamp,
-          offset: satpoint.offset,
-        };
+          all_inscriptions_json.push(InscriptionByAddressJson {
+            utxo: Utxo {
+              txid,
+              vout,
+              script: script.clone(),
+              shibes,
+              confirmations,
+            },
+            content: str_content,
+            content_length,
+            content_type,
+            genesis_height: entry.height,
+            inscription_id,
+            inscription_number: entry.inscription_number,
+            timestamp: entry.timestamp,
+            offset: satpoint.offset,
+          });
+        }
 
-        all_inscriptions_json.push(inscription_json);
-      }
-    }
-    Ok(
-      Json(InscriptionAddressJson {
-        inscriptions: all_inscriptions_json,
-        total_inscriptions: element_counter,
-      })
-      .into_response(),
-    )
+        Ok(all_inscriptions_json.len() >= limit)
+      },
+    )?;
+
+    let complete = Self::covers_every_output(query.cursor.as_ref(), skip, &next_cursor);
+
+    Ok(InscriptionAddressJson {
+      total_inscriptions: complete.then_some(all_inscriptions_json.len()),
+      inscriptions: all_inscriptions_json,
+      height,
+      next_cursor,
+    })
   }
 
   async fn dunes_by_address(
+    Extension(page_config): Extension<Arc<PageConfig>>,
     Extension(index): Extension<Arc<Index>>,
-    Path(params): Path<(String, u32)>,
+    Path(address): Path<String>,
     Query(query): Query<DunesBalanceQuery>,
   ) -> ServerResult<Response> {
-    Self::get_dunes_by_address(index, params.0, Some(params.1), query).await
+    let limit = Self::listing_limit(&page_config, query.limit, query.show_all);
+    let dunes = Self::get_dunes_by_address(&index, &address, &query, limit, 0)?;
+    Ok(Json(dunes).into_response())
   }
 
-  async fn dunes_by_address_unpaginated(
+  /// Deprecated in favor of `/dunes/balance/:address` and its `next_cursor`.
+  async fn dunes_by_address_page(
     Extension(index): Extension<Arc<Index>>,
-    Path(params): Path<String>,
+    Path((address, page)): Path<(String, usize)>,
     Query(query): Query<DunesBalanceQuery>,
   ) -> ServerResult<Response> {
-    Self::get_dunes_by_address(index, params, None, query).await
-  }
+    let (limit, skip) = Self::legacy_page(page, query.limit, query.show_all);
+    let dunes = Self::get_dunes_by_address(&index, &address, &query, limit, skip)?;
+    Ok(Self::deprecated(Json(dunes)))
+  }
+
+  /// Lists the dune balances of `address`. Pages hold up to `limit` balances.
+  /// Each dune's totals, `total_dunes` and `total_elements` are only given
+  /// when the response covers every output. With `list_dunes`, every dune is
+  /// listed with its totals but without its balances or paging.
+  fn get_dunes_by_address(
+    index: &Index,
+    address: &str,
+    query: &DunesBalanceQuery,
+    limit: usize,
+    skip: usize,
+  ) -> ServerResult<DuneAddressJson> {
+    let matches = |dune: &SpacedDune| query.filter.map_or(true, |filter| *dune == filter);
+
+    fn add<'a>(
+      dune_balances_map: &'a mut LinkedHashMap<SpacedDune, DuneBalance>,
+      dune: SpacedDune,
+      balances: &Pile,
+    ) -> &'a mut DuneBalance {
+      let dune_balance = dune_balances_map
+        .entry(dune)
+        .or_insert_with(|| DuneBalance {
+          dune,
+          divisibility: balances.divisibility,
+          symbol: balances.symbol,
+          total_balance: None,
+          total_outputs: None,
+          balances: Vec::new(),
+        });
 
-  async fn get_dunes_by_address(
-    index: Arc<Index>,
-    address: String,
-    page: Option<u32>,
-    query: DunesBalanceQuery,
-  ) -> ServerResult<Response> {
-    let (address, page) = (address, page.unwrap_or(0));
-    let show_all = query.show_all.unwrap_or(false);
-    let list_dunes = query.list_dunes.unwrap_or(false);
+      *dune_balance.total_balance.get_or_insert(0) += balances.amount;
+      *dune_balance.total_outputs.get_or_insert(0) += 1;
 
-    let outpoints = index.get_account_outputs(address)?;
+      dune_balance
+    }
 
-    let items_per_page = 10usize;
-    let page = page as usize;
-    let mut start_index = if page == 0 {
-      0
-    } else {
-      (page - 1) * items_per_page
-    };
-    let mut elements_counter = 0;
+    let mut total_elements = 0;
 
     let mut dune_balances_map: LinkedHashMap<SpacedDune, DuneBalance> = LinkedHashMap::new();
 
-    for outpoint in outpoints {
-      let dunes = index.get_dune_balances_for_outpoint(outpoint)?;
-      for (dune, balances) in dunes {
-        if let Some(filter) = query.filter {
-          if dune != filter {
-            continue;
+    if query.list_dunes.unwrap_or(false) {
+      Self::for_each_account_output(index, address, |outpoint, _| {
+        for (dune, balances) in index.get_dune_balances_for_outpoint(outpoint)? {
+          if matches(&dune) {
+            add(&mut dune_balances_map, dune, &balances);
+            total_elements += 1;
           }
         }
-        let dune_balance = dune_balances_map
-          .entry(dune.clone())
-          .or_insert_with(|| DuneBalance {
-            dune: dune.clone(),
-            divisibility: balances.divisibility,
-            symbol: balances.symbol,
-            total_balance: 0,
-            total_outputs: 0,
-            balances: Vec::new(),
-          });
 
-        if !list_dunes {
+        Ok(())
+      })?;
+
+      return Ok(DuneAddressJson {
+        total_dunes: Some(dune_balances_map.len()),
+        total_elements: Some(total_elements),
+        dunes: dune_balances_map
+          .into_iter()
+          .map(|(_, balance)| balance)
+          .collect(),
+        height: index.block_count()?,
+        next_cursor: None,
+      });
+    }
+
+    let mut remaining = skip;
+    let mut elements = 0;
+
+    let (height, next_cursor) = Self::scan_account_outputs(
+      index,
+      address,
+      query.cursor.as_ref().map(|cursor| &cursor.0),
+      limit,
+      |outpoint, _| {
+        for (dune, balances) in index.get_dune_balances_for_outpoint(outpoint)? {
+          if !matches(&dune) {
+            continue;
+          }
+
+          let dune_balance = add(&mut dune_balances_map, dune, &balances);
+          total_elements += 1;
+
+          if remaining > 0 {
+            remaining -= 1;
+            continue;
+          }
+
           let txid = outpoint.txid;
           let vout = outpoint.vout;
           let output = index
@@ -1138,77 +1516,142 @@ impl Server {
             shibes: output.value,
             balance: balances.amount,
           });
+
+          elements += 1;
         }
 
-        dune_balance.total_balance += balances.amount;
-        dune_balance.total_outputs += 1;
-        elements_counter += 1;
+        Ok(elements >= limit)
+      },
+    )?;
+
+    let complete = Self::covers_every_output(query.cursor.as_ref(), skip, &next_cursor);
+
+    Ok(DuneAddressJson {
+      total_dunes: complete.then_some(dune_balances_map.len()),
+      total_elements: complete.then_some(total_elements),
+      dunes: dune_balances_map
+        .into_iter()
+        .map(|(_, mut balance)| {
+          if !complete {
+            balance.total_balance = None;
+            balance.total_outputs = None;
+          }
+          balance
+        })
+        .filter(|balance| !balance.balances.is_empty())
+        .collect(),
+      height,
+      next_cursor,
+    })
+  }
+
+  async fn address(
+    Extension(page_config): Extension<Arc<PageConfig>>,
+    Extension(index): Extension<Arc<Index>>,
+    Path(address): Path<String>,
+    Query(query): Query<JsonQuery>,
+    headers: HeaderMap,
+  ) -> ServerResult<Response> {
+    let parsed =
+      Address::from_str(&address).map_err(|err| ServerError::BadRequest(err.to_string()))?;
+
+    Index::indexed_address(&address).map_err(|err| ServerError::BadRequest(err.to_string()))?;
+
+    if !Self::accepts_html(&headers, query.json) {
+      let outpoints = index.get_account_outputs(address)?;
+      return Ok(Json(vec![AddressOutputJson::new(outpoints)]).into_response());
+    }
+
+    let (outputs, shibes) = index.get_address_balance(&address)?;
+
+    let recent = index.get_address_recent_outputs(&address, ADDRESS_OUTPUT_SCAN)?;
+
+    let mut inscriptions = Vec::new();
+    let mut dunes: BTreeMap<SpacedDune, Pile> = BTreeMap::new();
+
+    for (outpoint, _, _) in &recent {
+      if inscriptions.len() < page_config.page_size {
+        inscriptions.extend(index.get_inscriptions_on_output(*outpoint)?);
+      }
+
+      for (dune, pile) in index.get_dune_balances_for_outpoint(*outpoint)? {
+        dunes
+          .entry(dune)
+          .or_insert(Pile {
+            amount: 0,
+            divisibility: pile.divisibility,
+            symbol: pile.symbol,
+          })
+          .amount += pile.amount;
       }
     }
 
-    let dune_balances: Vec<DuneBalance> = if show_all {
-      dune_balances_map.values().cloned().collect()
-    } else if list_dunes {
-      dune_balances_map
-        .values()
-        .cloned()
-        .skip(start_index)
-        .take(items_per_page)
-        .collect()
-    } else {
-      let values: Vec<DuneBalance> = dune_balances_map.values().cloned().collect();
-      let mut items_collected = 0;
-      let mut result = Vec::new();
-      for value in values.iter() {
-        let balances: Vec<DuneOutput> = value
-          .balances
-          .iter()
-          .skip(start_index)
-          .take(items_per_page - items_collected)
-          .cloned()
-          .collect();
-        items_collected += balances.len();
-        start_index -= value.balances.len().min(start_index);
-        if balances.is_empty() {
-          continue;
-        }
-        result.push(DuneBalance {
-          dune: value.dune.clone(),
-          divisibility: value.divisibility,
-          symbol: value.symbol.clone(),
-          total_balance: value.total_balance,
-          total_outputs: value.total_outputs,
-          balances,
-        });
-        if items_collected >= items_per_page {
-          break;
-        }
+    inscriptions.truncate(page_config.page_size);
+
+    let script_key = ScriptKey::from_address(parsed.clone(), index.get_network()?);
+
+    let mut drc20 = Vec::new();
+
+    for balance in index.get_drc20_balances(&script_key)? {
+      if balance.overall_balance == 0 {
+        continue;
       }
-      result
-    };
+
+      let decimals = index
+        .get_drc20_token_info(&balance.tick)?
+        .map(|token_info| token_info.decimal)
+        .unwrap_or_default();
+
+      drc20.push((
+        balance.tick.to_string(),
+        format_balance(balance.overall_balance - balance.transferable_balance, decimals),
+        format_balance(balance.transferable_balance, decimals),
+      ));
+    }
+
+    let block_count = index.block_count()?;
+
+    let mut activity = Vec::new();
+
+    for (outpoint, value, height) in recent.iter().take(ADDRESS_ACTIVITY) {
+      // outputs indexed before heights were recorded have height 0
+      let confirmations = if *height > 0 {
+        Some(block_count.saturating_sub(*height))
+      } else {
+        index
+          .get_transaction_blockhash(outpoint.txid)?
+          .and_then(|info| info.confirmations)
+      };
+
+      activity.push((*outpoint, *value, confirmations));
+    }
 
     Ok(
-      Json(DuneAddressJson {
-        dunes: dune_balances,
-        total_dunes: dune_balances_map.len(),
-        total_elements: elements_counter,
-      })
+      AddressHtml {
+        address: parsed,
+        shibes,
+        outputs,
+        scanned: recent.len(),
+        inscriptions,
+        dunes: dunes.into_iter().collect(),
+        drc20,
+        activity,
+      }
+      .page(page_config)
       .into_response(),
     )
   }
 
-  async fn outputs_by_address(
-    Extension(index): Extension<Arc<Index>>,
-    Path(address): Path<String>,
-  ) -> Result<String, ServerError> {
-    let mut outputs = vec![];
-    let outpoints = index.get_account_outputs(address)?;
-
-    outputs.push(AddressOutputJson::new(outpoints));
-
-    let outputs_json = to_string(&outputs).context("Failed to serialize outputs")?;
-
-    Ok(outputs_json)
+  /// Whether a request prefers HTML, as browsers' `Accept` headers do.
+  /// Everything else, including requests with `?json=true`, gets JSON.
+  fn accepts_html(headers: &HeaderMap, json: Option<bool>) -> bool {
+    !json.unwrap_or_default()
+      && headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|media_type| media_type.trim().starts_with("text/html"))
   }
 
   async fn outputs(
@@ -1265,23 +1708,56 @@ impl Server {
     Ok(outputs_json)
   }
 
+  async fn drc20_ticks(
+    Extension(page_config): Extension<Arc<PageConfig>>,
+    Extension(index): Extension<Arc<Index>>,
+  ) -> ServerResult<PageHtml<Drc20TicksHtml>> {
+    let mut ticks = Vec::new();
+
+    for token in index.get_drc20_tokens_info()? {
+      ticks.push(Drc20TickSummary {
+        holders: index.get_drc20_token_holder_count(&token.tick)?,
+        token,
+      });
+    }
+
+    Ok(Drc20TicksHtml { ticks }.page(page_config))
+  }
+
   async fn drc20_tick_info(
+    Extension(page_config): Extension<Arc<PageConfig>>,
     Extension(index): Extension<Arc<Index>>,
     Path(tick): Path<String>,
     Query(query): Query<Drc20TickInfoQuery>,
+    headers: HeaderMap,
   ) -> Result<Response, ServerError> {
     let tick =
       &Tick::from_str(tick.as_str()).map_err(|err| ServerError::BadRequest(err.to_string()))?;
     let token_info = index.get_drc20_token_info(&tick.clone())?;
 
+    if !query.show_holder.unwrap_or_default() && Self::accepts_html(&headers, query.json) {
+      let token = token_info.ok_or_not_found(|| format!("tick {tick}"))?;
+
+      return Ok(
+        Drc20TickHtml {
+          holders: index.get_drc20_token_holder_count(tick)?,
+          top_holders: index.get_drc20_top_holders(tick, DRC20_TOP_HOLDERS)?,
+          activity: index.get_drc20_tick_activity(tick, DRC20_RECENT_ACTIVITY)?,
+          token,
+        }
+        .page(page_config)
+        .into_response(),
+      );
+    }
+
     if query.show_holder.unwrap_or(false) {
       let holder = index.get_drc20_token_holder(&tick.clone())?;
 
-      let mut holder_to_balance: HashMap<String, HolderBalanceForTick> = HashMap::new();
+      let mut holder_to_balance: BTreeMap<String, HolderBalanceForTick> = BTreeMap::new();
 
       for script_key in holder.clone() {
         if let Some(balance) = index
-          .get_drc20_balance(&script_key, &tick)
+          .get_drc20_balance(&script_key, &tick, None)
           .map_err(|err| ServerError::BadRequest(err.to_string()))?
         {
           let token_info_clone = token_info.clone().unwrap();
@@ -1299,21 +1775,23 @@ impl Server {
         }
       }
 
-      let nr_of_holder = holder.len();
+      let nr_of_holder = Some(holder.len());
 
       Ok(
         Json(ExtendedTokenInfo {
-          token_info,
+          token_info: token_info.map(TickInfo::from),
           holder_info: HoldersInfoForTick {
             holder_to_balance,
             nr_of_holder,
+            height: None,
+            next_cursor: None,
           },
         })
         .into_response(),
       )
     } else {
       if let Some(token_info) = token_info {
-        Ok(Json(token_info).into_response())
+        Ok(Json(TickInfo::from(token_info)).into_response())
       } else {
         Err(ServerError::BadRequest("No token info found".to_string()))
       }
@@ -1321,44 +1799,87 @@ impl Server {
   }
 
   async fn drc20_tick_holder(
+    Extension(page_config): Extension<Arc<PageConfig>>,
     Extension(index): Extension<Arc<Index>>,
     Path(tick): Path<String>,
+    Query(query): Query<Drc20TickHolderQuery>,
   ) -> Result<Response, ServerError> {
     let tick =
       &Tick::from_str(tick.as_str()).map_err(|err| ServerError::BadRequest(err.to_string()))?;
-    let holder = index.get_drc20_token_holder(&tick.clone())?;
-    let token_info = index.get_drc20_token_info(&tick.clone())?;
+    let cursor = query.cursor.as_ref().map(|cursor| &cursor.0);
+    let after = cursor
+      .map(|cursor| {
+        str::from_utf8(&cursor.after).map_err(|_| ServerError::BadRequest("invalid cursor".into()))
+      })
+      .transpose()?;
+    let limit = Self::page_limit(&page_config, query.limit);
+
+    // Historical pages are read from the balance history, so they stay
+    // consistent without a snapshot height; `height` is repeated per page.
+    let (height, holder, nr_of_holder, last) = match query.height {
+      Some(height) => {
+        let (page, last) = index
+          .get_drc20_holder_page_at(tick, height, after, limit)
+          .map_err(|err| ServerError::BadRequest(err.to_string()))?;
+
+        (height, page, None, last)
+      }
+      None => {
+        let height = index.block_count()?;
+        let (holder, last) = index.get_drc20_token_holder_page(tick, after, limit)?;
 
-    let mut holder_to_balance: HashMap<String, HolderBalanceForTick> = HashMap::new();
+        let mut page = Vec::new();
 
-    for script_key in holder.clone() {
-      if let Some(balance) = index
-        .get_drc20_balance(&script_key, &tick)
-        .map_err(|err| ServerError::BadRequest(err.to_string()))
-        .unwrap_or(None)
-      {
-        let token_info_clone = token_info.clone().unwrap();
-        let decimals = token_info_clone.decimal;
-        let overall_balance = balance.overall_balance;
-        let transferable_balance = balance.transferable_balance;
-        holder_to_balance.insert(
-          script_key.to_string(),
-          HolderBalanceForTick {
-            overall_balance: format_balance(overall_balance, decimals),
-            transferable_balance: format_balance(transferable_balance, decimals),
-            available_balance: format_balance(overall_balance - transferable_balance, decimals),
-          },
-        );
+        for script_key in holder {
+          if let Some(balance) = index
+            .get_drc20_balance(&script_key, tick, None)
+            .map_err(|err| ServerError::BadRequest(err.to_string()))
+            .unwrap_or(None)
+          {
+            page.push((script_key, balance));
+          }
+        }
+
+        (
+          height,
+          page,
+          Some(index.get_drc20_token_holder_count(tick)?),
+          last,
+        )
       }
-    }
+    };
 
-    let nr_of_holder = holder.len();
+    let found = match nr_of_holder {
+      Some(nr_of_holder) => nr_of_holder > 0,
+      None => after.is_some() || !holder.is_empty(),
+    };
+
+    let token_info = index.get_drc20_token_info(&tick.clone())?;
 
-    if nr_of_holder > 0 {
+    let mut holder_to_balance: BTreeMap<String, HolderBalanceForTick> = BTreeMap::new();
+
+    for (script_key, balance) in holder {
+      let token_info_clone = token_info.clone().unwrap();
+      let decimals = token_info_clone.decimal;
+      let overall_balance = balance.overall_balance;
+      let transferable_balance = balance.transferable_balance;
+      holder_to_balance.insert(
+        script_key.to_string(),
+        HolderBalanceForTick {
+          overall_balance: format_balance(overall_balance, decimals),
+          transferable_balance: format_balance(transferable_balance, decimals),
+          available_balance: format_balance(overall_balance - transferable_balance, decimals),
+        },
+      );
+    }
+
+    if found {
       Ok(
         Json(HoldersInfoForTick {
           holder_to_balance,
           nr_of_holder,
+          height: Some(height),
+          next_cursor: last.map(|last| Cursor::new(last).to_string()),
         })
         .into_response(),
       )
@@ -1386,11 +1907,11 @@ impl Server {
             .get_drc20_token_holder(&tick.clone())
             .unwrap_or(Vec::new());
 
-          let mut holder_to_balance: HashMap<String, HolderBalanceForTick> = HashMap::new();
+          let mut holder_to_balance: BTreeMap<String, HolderBalanceForTick> = BTreeMap::new();
 
           for script_key in holder.clone() {
             if let Some(balance) = index
-              .get_drc20_balance(&script_key, &tick)
+              .get_drc20_balance(&script_key, &tick, None)
               .map_err(|err| ServerError::BadRequest(err.to_string()))
               .unwrap_or(None)
             {
@@ -1412,20 +1933,30 @@ impl Server {
             }
           }
 
-          let nr_of_holder = holder.len();
+          let nr_of_holder = Some(holder.len());
 
           ExtendedTokenInfo {
-            token_info: Some(info.clone()),
+            token_info: Some(info.clone().into()),
             holder_info: HoldersInfoForTick {
               holder_to_balance,
               nr_of_holder,
+              height: None,
+              next_cursor: None,
             },
           }
         })
         .collect();
       Ok(Json(extended_token_info).into_response())
     } else {
-      Ok(Json(token_info).into_response())
+      Ok(
+        Json(
+          token_info
+            .into_iter()
+            .map(TickInfo::from)
+            .collect::<Vec<TickInfo>>(),
+        )
+        .into_response(),
+      )
     }
   }
 
@@ -1442,6 +1973,54 @@ impl Server {
     Ok(Json(token_info).into_response())
   }
 
+  async fn drc20_state_hash(
+    Extension(index): Extension<Arc<Index>>,
+    Path(height): Path<u32>,
+  ) -> ServerResult<Json<StateHash>> {
+    Ok(Json(
+      index
+        .get_drc20_state_hash(height)?
+        .ok_or_not_found(|| format!("DRC-20 state hash for block {height}"))?,
+    ))
+  }
+
+  async fn drc20_simulate(
+    Extension(index): Extension<Arc<Index>>,
+    Query(query): Query<Drc20SimulateQuery>,
+    body: String,
+  ) -> ServerResult<Response> {
+    task::block_in_place(|| {
+      let network = index.get_network()?;
+
+      let script_key = |address: &str| {
+        Address::from_str(address)
+          .map(|address| ScriptKey::from_address(address, network))
+          .map_err(|err| ServerError::BadRequest(err.to_string()))
+      };
+
+      let from = script_key(&query.from)?;
+      let to = match &query.to {
+        Some(to) => script_key(to)?,
+        None => from.clone(),
+      };
+
+      let simulation = index
+        .simulate_drc20_operation(
+          query
+            .content_type
+            .as_deref()
+            .unwrap_or("text/plain;charset=utf-8"),
+          body.as_bytes(),
+          query.transfer,
+          from,
+          to,
+        )
+        .map_err(|err| ServerError::BadRequest(err.to_string()))?;
+
+      Ok(Json(simulation).into_response())
+    })
+  }
+
   async fn drc20_validate(
     Extension(index): Extension<Arc<Index>>,
     Extension(server_config): Extension<Arc<PageConfig>>,
@@ -1519,6 +2098,7 @@ impl Server {
 
   async fn range(
     Extension(page_config): Extension<Arc<PageConfig>>,
+    Extension(index): Extension<Arc<Index>>,
     Path((DeserializeFromStr(start), DeserializeFromStr(end))): Path<(
       DeserializeFromStr<Sat>,
       DeserializeFromStr<Sat>,
@@ -1529,7 +2109,14 @@ impl Server {
       Ordering::Greater => Err(ServerError::BadRequest(
         "range start greater than range end".to_string(),
       )),
-      Ordering::Less => Ok(RangeHtml { start, end }.page(page_config)),
+      Ordering::Less => Ok(
+        RangeHtml {
+          start,
+          end,
+          schedule: index.schedule(),
+        }
+        .page(page_config),
+      ),
     }
   }
 
@@ -1811,6 +2398,8 @@ impl Server {
       })
       .collect();
 
+    let auxpow = index.get_auxpow(height, &block)?;
+
     Ok(
       BlockHtml::new(
         block,
@@ -1823,6 +2412,7 @@ impl Server {
         output_values_per_tx,
         inscriptions_per_tx,
         output_addresses_per_tx,
+        auxpow,
       )
       .page(page_config),
     )
@@ -2003,6 +2593,8 @@ impl Server {
         HashMap::new()
       };
 
+      let auxpow = index.get_auxpow(height, &block)?;
+
       blocks.push(BlockJson::new(
         block,
         Height(height).0,
@@ -2015,6 +2607,7 @@ impl Server {
         inscriptions_per_tx,
         output_addresses_per_tx,
         output_scripts_per_tx,
+        auxpow,
       ));
     }
 
@@ -2086,6 +2679,30 @@ impl Server {
     Self::search(&index, &search.query).await
   }
 
+  async fn search_suggest(
+    Extension(index): Extension<Arc<Index>>,
+    Query(suggest): Query<SuggestQuery>,
+  ) -> ServerResult<Response> {
+    let prefix = suggest.q.unwrap_or_default();
+    let prefix = prefix.trim();
+
+    if prefix.is_empty() {
+      return Ok(Json(SuggestJson::default()).into_response());
+    }
+
+    Ok(
+      Json(SuggestJson {
+        dunes: index.suggest_dunes(prefix, SUGGESTION_LIMIT)?,
+        ticks: index
+          .suggest_drc20_ticks(prefix, SUGGESTION_LIMIT)?
+          .iter()
+          .map(|tick| tick.to_string())
+          .collect(),
+      })
+      .into_response(),
+    )
+  }
+
   async fn search(index: &Index, query: &str) -> ServerResult<Redirect> {
     Self::search_inner(index, query)
   }
@@ -2097,9 +2714,11 @@ impl Server {
       static ref INSCRIPTION_ID: Regex = Regex::new(r"^[[:xdigit:]]{64}i\d+$").unwrap();
       static ref DUNE: Regex = Regex::new(r"^[A-Z•.]+$").unwrap();
       static ref DUNE_ID: Regex = Regex::new(r"^[0-9]+:[0-9]+$").unwrap();
+      static ref INSCRIPTION_NUMBER: Regex = Regex::new(r"^#\s*\d+$").unwrap();
     }
 
     let query = query.trim();
+    let network = index.get_network()?;
 
     if HASH.is_match(query) {
       if index.block_header(query.parse().unwrap())?.is_some() {
@@ -2111,7 +2730,18 @@ impl Server {
       Ok(Redirect::to(&format!("/output/{query}")))
     } else if INSCRIPTION_ID.is_match(query) {
       Ok(Redirect::to(&format!("/shibescription/{query}")))
-    } else if DUNE.is_match(query) {
+    } else if INSCRIPTION_NUMBER.is_match(query) {
+      let number = query[1..]
+        .trim_start()
+        .parse::<u64>()
+        .map_err(|err| ServerError::BadRequest(err.to_string()))?;
+
+      let inscription_id = index
+        .get_inscription_id_by_inscription_number(number)?
+        .ok_or_not_found(|| format!("inscription {number}"))?;
+
+      Ok(Redirect::to(&format!("/shibescription/{inscription_id}")))
+    } else if DUNE.is_match(query) && !Self::is_drc20_tick_rather_than_dune(index, query)? {
       Ok(Redirect::to(&format!("/dune/{query}")))
     } else if DUNE_ID.is_match(query) {
       let id = query
@@ -2121,11 +2751,35 @@ impl Server {
       let dune = index.get_dune_by_id(id)?.ok_or_not_found(|| "dune ID")?;
 
       Ok(Redirect::to(&format!("/dune/{dune}")))
+    } else if Address::from_str(query).map_or(false, |address| address.network == network) {
+      Ok(Redirect::to(&format!("/address/{query}")))
+    } else if let Some(tick) = Tick::from_str(query)
+      .ok()
+      .filter(|tick| matches!(index.get_drc20_token_info(tick), Ok(Some(_))))
+    {
+      Ok(Redirect::to(&format!("/drc20/tick/{tick}")))
     } else {
       Ok(Redirect::to(&format!("/sat/{query}")))
     }
   }
 
+  /// Upper case ticks look like dune names, so a query that names a deployed
+  /// tick but no etched dune goes to the tick instead.
+  fn is_drc20_tick_rather_than_dune(index: &Index, query: &str) -> ServerResult<bool> {
+    let Ok(tick) = Tick::from_str(query) else {
+      return Ok(false);
+    };
+
+    if index.get_drc20_token_info(&tick)?.is_none() {
+      return Ok(false);
+    }
+
+    Ok(match query.parse::<SpacedDune>() {
+      Ok(spaced_dune) => !index.has_dune_index() || index.dune(spaced_dune.dune)?.is_none(),
+      Err(_) => true,
+    })
+  }
+
   async fn favicon(user_agent: Option<TypedHeader<UserAgent>>) -> ServerResult<Response> {
     if user_agent
       .map(|user_agent| {
@@ -2256,11 +2910,32 @@ impl Server {
     Extension(config): Extension<Arc<Config>>,
     Path(inscription_id): Path<InscriptionId>,
     Extension(page_config): Extension<Arc<PageConfig>>,
+    request_headers: HeaderMap,
   ) -> ServerResult<Response> {
     if config.is_hidden(inscription_id) {
       return Ok(PreviewUnknownHtml.into_response());
     }
 
+    index
+      .get_inscription_entry(inscription_id)?
+      .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
+
+    // Content is immutable, so the inscription id alone makes a strong validator
+    let etag = HeaderValue::from_str(&format!("\"{inscription_id}\"")).unwrap();
+
+    if Self::etag_matches(&request_headers, header::IF_NONE_MATCH, &etag) {
+      return Ok(
+        (
+          StatusCode::NOT_MODIFIED,
+          [
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, HeaderValue::from_static(CONTENT_CACHE_CONTROL)),
+          ],
+        )
+          .into_response(),
+      );
+    }
+
     let mut inscription = index
       .get_inscription_by_id(inscription_id)?
       .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
@@ -2271,11 +2946,107 @@ impl Server {
         .ok_or_not_found(|| format!("delegate {inscription_id}"))?
     }
 
-    Ok(
-      Self::content_response(inscription, &page_config)
-        .ok_or_not_found(|| format!("inscription {inscription_id} content"))?
-        .into_response(),
-    )
+    let (mut headers, body) = Self::content_response(inscription, &page_config)
+      .ok_or_not_found(|| format!("inscription {inscription_id} content"))?;
+
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    headers.insert(header::ETAG, etag.clone());
+
+    let body = body::Bytes::from(body);
+    let len = body.len();
+
+    let range = request_headers
+      .get(header::RANGE)
+      .and_then(|range| range.to_str().ok())
+      .filter(|_| {
+        !request_headers.contains_key(header::IF_RANGE)
+          || Self::etag_matches(&request_headers, header::IF_RANGE, &etag)
+      })
+      .and_then(|range| ByteRange::parse(range, len));
+
+    match range {
+      None => Ok(Self::content_body(StatusCode::OK, headers, body)),
+      Some(ByteRange::Satisfiable(range)) => {
+        headers.insert(
+          header::CONTENT_RANGE,
+          HeaderValue::from_str(&format!(
+            "bytes {}-{}/{len}",
+            range.start,
+            range.end - 1
+          ))
+          .unwrap(),
+        );
+        Ok(Self::content_body(
+          StatusCode::PARTIAL_CONTENT,
+          headers,
+          body.slice(range),
+        ))
+      }
+      Some(ByteRange::Unsatisfiable) => {
+        headers.remove(header::CONTENT_TYPE);
+        headers.insert(
+          header::CONTENT_RANGE,
+          HeaderValue::from_str(&format!("bytes */{len}")).unwrap(),
+        );
+        Ok((StatusCode::RANGE_NOT_SATISFIABLE, headers).into_response())
+      }
+    }
+  }
+
+  // Compressing a partial response would break the byte offsets it advertises
+  fn not_partial_content(
+    status: StatusCode,
+    _: http::Version,
+    _: &HeaderMap,
+    _: &http::Extensions,
+  ) -> bool {
+    status != StatusCode::PARTIAL_CONTENT
+  }
+
+  /// A compressed body isn't byte-for-byte the body a strong ETag was issued
+  /// for, so compressed responses carry the weak form of their ETag.
+  async fn weaken_compressed_etag(mut response: Response) -> Response {
+    let weak = response
+      .headers()
+      .get(header::ETAG)
+      .filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+      .filter(|_| response.headers().contains_key(header::CONTENT_ENCODING))
+      .map(|etag| HeaderValue::from_bytes(&[b"W/", etag.as_bytes()].concat()).unwrap());
+
+    if let Some(weak) = weak {
+      response.headers_mut().insert(header::ETAG, weak);
+    }
+
+    response
+  }
+
+  /// Whether header `name` lists `etag`. `If-None-Match` uses the weak
+  /// comparison, so ETags weakened by compression still match, while
+  /// `If-Range` uses the strong one.
+  fn etag_matches(headers: &HeaderMap, name: HeaderName, etag: &HeaderValue) -> bool {
+    let weak = name == header::IF_NONE_MATCH;
+
+    headers
+      .get_all(name)
+      .iter()
+      .filter_map(|value| value.to_str().ok())
+      .flat_map(|value| value.split(','))
+      .map(str::trim)
+      .map(|candidate| {
+        if weak {
+          candidate.strip_prefix("W/").unwrap_or(candidate)
+        } else {
+          candidate
+        }
+      })
+      .any(|candidate| candidate == "*" || candidate.as_bytes() == etag.as_bytes())
+  }
+
+  /// Content is stored whole, so the body is read into memory in full and a
+  /// range is a slice of it.
+  fn content_body(status: StatusCode, mut headers: HeaderMap, body: body::Bytes) -> Response {
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+    (status, headers, body).into_response()
   }
 
   fn content_response(
@@ -2306,7 +3077,7 @@ impl Server {
     }
     headers.insert(
       header::CACHE_CONTROL,
-      HeaderValue::from_static("max-age=31536000, immutable"),
+      HeaderValue::from_static(CONTENT_CACHE_CONTROL),
     );
     headers.insert(
       header::CONTENT_TYPE,
@@ -2808,32 +3579,6 @@ async fn process_inscriptions(
   Ok(inscriptions_json)
 }
 
-fn format_balance(balance: u128, decimal_places: u8) -> String {
-  let factor = 10u128.pow(decimal_places as u32);
-  let integer_part = balance / factor; // Get the integer part
-  let fractional_part = balance % factor; // Get the fractional part
-
-  // If balance is zero or the fractional part is zero, return just the integer part
-  if fractional_part == 0 {
-    return format!("{}", integer_part);
-  }
-
-  // Format the fractional part, trimming trailing zeros
-  let mut fractional_string = format!(
-    "{:0>width$}",
-    fractional_part,
-    width = decimal_places as usize
-  );
-
-  // Remove trailing zeros from the fractional part
-  while fractional_string.ends_with('0') {
-    fractional_string.pop();
-  }
-
-  // Combine integer and cleaned-up fractional part
-  format!("{}.{}", integer_part, fractional_string)
-}
-
 #[cfg(test)]
 mod tests {
   use bitcoin::blockdata::constants::COIN_VALUE;
@@ -2841,6 +3586,7 @@ mod tests {
   use {super::*, reqwest::Url, std::net::TcpListener};
 
   use crate::dunes::{Dunestone, Edict, Etching};
+  use crate::drc20::DRC20Error;
 
   struct TestServer {
     dogecoin_rpc_server: test_bitcoincore_rpc::Handle,
@@ -2957,6 +3703,17 @@ mod tests {
       reqwest::blocking::get(self.join_url(path.as_ref())).unwrap()
     }
 
+    fn get_html(&self, path: impl AsRef<str>) -> reqwest::blocking::Response {
+      if let Err(error) = self.index.update() {
+        log::error!("{error}");
+      }
+      reqwest::blocking::Client::new()
+        .get(self.join_url(path.as_ref()))
+        .header(header::ACCEPT, "text/html,application/xhtml+xml")
+        .send()
+        .unwrap()
+    }
+
     fn join_url(&self, url: &str) -> Url {
       self.url.join(url).unwrap()
     }
@@ -3048,6 +3805,24 @@ mod tests {
     );
   }
 
+  #[test]
+  fn replicas_must_be_read_only() {
+    assert!(Arguments::try_parse_from(["ord", "server", "--replica-of", "foo"]).is_err());
+    assert!(
+      Arguments::try_parse_from(["ord", "server", "--read-only", "--replica-of", "foo"]).is_ok()
+    );
+  }
+
+  #[test]
+  fn read_only_servers_need_a_replica_source() {
+    assert!(Arguments::try_parse_from(["ord", "server", "--read-only"]).is_err());
+  }
+
+  #[test]
+  fn read_only_servers_cannot_publish() {
+    assert!(Arguments::try_parse_from(["ord", "server", "--read-only", "--publish", "foo"]).is_err());
+  }
+
   #[test]
   fn http_port_defaults_to_80() {
     assert_eq!(parse_server_args("ord server").1.http_port(), Some(80));
@@ -3287,6 +4062,219 @@ mod tests {
     );
   }
 
+  #[test]
+  fn search_for_address_returns_address() {
+    TestServer::new().assert_redirect(
+      "/search/bcrt1qs758ursh4q9z627kt3pp5yysm78ddny6txaqgw",
+      "/address/bcrt1qs758ursh4q9z627kt3pp5yysm78ddny6txaqgw",
+    );
+  }
+
+  #[test]
+  fn address_page() {
+    let response = TestServer::new().get_html("/address/mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_regex_match!(
+      response.text().unwrap(),
+      ".*<title>Address mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn</title>.*
+<h1>Address <span class=monospace>mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn</span></h1>
+<dl>
+  <dt>balance</dt><dd>0.00000000 DOGE</dd>
+  <dt>outputs</dt><dd>0</dd>
+</dl>
+.*"
+    );
+  }
+
+  #[test]
+  fn address_json() {
+    let server = TestServer::new();
+
+    server.assert_response(
+      "/address/mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn",
+      StatusCode::OK,
+      r#"[{"outpoint":[]}]"#,
+    );
+
+    let response = server.get_html("/address/mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn?json=true");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().unwrap(), r#"[{"outpoint":[]}]"#);
+  }
+
+  #[test]
+  fn invalid_address_returns_400() {
+    TestServer::new().assert_response_regex("/address/foo", StatusCode::BAD_REQUEST, ".*");
+  }
+
+  #[test]
+  fn addresses_that_are_not_indexed_return_400() {
+    let server = TestServer::new();
+
+    for path in [
+      "/address/bcrt1qs758ursh4q9z627kt3pp5yysm78ddny6txaqgw",
+      "/utxos/balance/bcrt1qs758ursh4q9z627kt3pp5yysm78ddny6txaqgw",
+    ] {
+      server.assert_response(
+        path,
+        StatusCode::BAD_REQUEST,
+        "address `bcrt1qs758ursh4q9z627kt3pp5yysm78ddny6txaqgw` is not indexed, only 34 character addresses are",
+      );
+    }
+  }
+
+  #[test]
+  fn drc20_ticks_page() {
+    TestServer::new().assert_response_regex(
+      "/drc20",
+      StatusCode::OK,
+      ".*<title>DRC-20</title>.*<h1>DRC-20</h1>.*",
+    );
+  }
+
+  #[test]
+  fn drc20_holders_at_unindexed_heights_are_rejected() {
+    let server = TestServer::new_with_args(&["--index-drc20", "--index-transactions"], &[]);
+
+    server.mine_blocks(1);
+
+    server.assert_response(
+      "/drc20/tick/holder/doge?height=5",
+      StatusCode::BAD_REQUEST,
+      "block 5 has not been indexed, the index has 2 blocks",
+    );
+  }
+
+  #[test]
+  fn drc20_state_hashes_are_served() {
+    let server = TestServer::new_with_args(&["--index-drc20", "--index-transactions"], &[]);
+
+    server.mine_blocks(1);
+
+    let response = server.get("/drc20/state-hash/1");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+      response.json::<StateHash>().unwrap(),
+      server.index.get_drc20_state_hash(1).unwrap().unwrap()
+    );
+
+    server.assert_response(
+      "/drc20/state-hash/2",
+      StatusCode::NOT_FOUND,
+      "DRC-20 state hash for block 2 not found",
+    );
+  }
+
+  #[test]
+  fn drc20_operations_can_be_simulated() {
+    let server = TestServer::new_with_args(&["--index-drc20", "--index-transactions"], &[]);
+
+    server.mine_blocks(1);
+
+    let simulate = |query: &str, body: &str| {
+      reqwest::blocking::Client::new()
+        .post(server.join_url(&format!("/drc20/simulate?{query}")))
+        .body(body.to_string())
+        .send()
+        .unwrap()
+    };
+
+    let mint = r#"{"p":"drc-20","op":"mint","tick":"dogi","amt":"100"}"#;
+
+    let response = simulate("from=mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn", mint);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+      response.json::<serde_json::Value>().unwrap()["result"],
+      serde_json::to_value(Err::<(), _>(DRC20Error::TickNotFound("dogi".into()))).unwrap()
+    );
+
+    assert_eq!(simulate("from=foo", mint).status(), StatusCode::BAD_REQUEST);
+
+    assert_eq!(
+      simulate("from=mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn", "hello").status(),
+      StatusCode::BAD_REQUEST
+    );
+  }
+
+  #[test]
+  fn unknown_drc20_tick_page_returns_404() {
+    let server = TestServer::new();
+
+    let response = server.get_html("/drc20/tick/dogi");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.text().unwrap(), "tick dogi not found");
+
+    assert_eq!(
+      server.get("/drc20/tick/dogi").status(),
+      StatusCode::BAD_REQUEST
+    );
+  }
+
+  #[test]
+  fn search_for_unknown_inscription_number_returns_404() {
+    TestServer::new().assert_response(
+      "/search?query=%23100",
+      StatusCode::NOT_FOUND,
+      "inscription 100 not found",
+    );
+  }
+
+  #[test]
+  fn search_for_inscription_number_returns_inscription() {
+    let server = TestServer::new();
+    server.mine_blocks(1);
+
+    let txid = server
+      .dogecoin_rpc_server
+      .broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0)],
+        script_sig: inscription("text/plain;charset=utf-8", "hello").to_script_sig(),
+        ..Default::default()
+      });
+
+    server.mine_blocks(1);
+
+    let location = format!("/shibescription/{}", InscriptionId::from(txid));
+
+    server.assert_redirect("/search?query=%230", &location);
+    server.assert_redirect("/search?query=%23%200", &location);
+    server.assert_redirect("/search/%230", &location);
+  }
+
+  #[test]
+  fn search_for_upper_case_tick_returns_tick() {
+    let server = TestServer::new_with_args(&["--index-drc20", "--index-transactions"], &[]);
+    server.mine_blocks(1);
+
+    let deploy = r#"{"p":"drc-20","op":"deploy","tick":"dogi","max":"1000","lim":"100"}"#;
+
+    server
+      .dogecoin_rpc_server
+      .broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0)],
+        script_sig: inscription("text/plain;charset=utf-8", deploy).to_script_sig(),
+        ..Default::default()
+      });
+
+    server.mine_blocks(1);
+
+    server.assert_redirect("/search/DOGI", "/drc20/tick/DOGI");
+    server.assert_redirect("/search/ABCD", "/dune/ABCD");
+  }
+
+  #[test]
+  fn search_for_unknown_tick_falls_through_to_sat() {
+    TestServer::new().assert_redirect("/search/dogi", "/sat/dogi");
+  }
+
+  #[test]
+  fn search_suggest_without_matches() {
+    TestServer::new().assert_response(
+      "/search/suggest?q=dog",
+      StatusCode::OK,
+      r#"{"dunes":[],"ticks":[]}"#,
+    );
+  }
+
   #[test]
   fn search_by_path_returns_dune() {
     TestServer::new().assert_redirect("/search/ABCD", "/dune/ABCD");
@@ -3405,6 +4393,49 @@ mod tests {
     TestServer::new().assert_response("/range/0/0", StatusCode::BAD_REQUEST, "empty range");
   }
 
+  #[test]
+  fn utxos_by_address_empty_page_has_no_cursor() {
+    TestServer::new().assert_response_regex(
+      "/utxos/balance/mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn",
+      StatusCode::OK,
+      r#"\{"utxos":\[\],"total_utxos":0,"total_shibes":0,"total_inscription_shibes":0,"height":1,"next_cursor":null\}"#,
+    );
+  }
+
+  #[test]
+  fn legacy_page_routes_are_deprecated() {
+    let server = TestServer::new();
+
+    for path in [
+      "/utxos/balance/mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn/2",
+      "/inscriptions/balance/mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn/2",
+      "/dunes/balance/mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn/2",
+    ] {
+      let response = server.get(path);
+      assert_eq!(response.status(), StatusCode::OK, "{path}");
+      assert_eq!(response.headers().get("deprecation").unwrap(), "true");
+    }
+  }
+
+  #[test]
+  fn later_pages_leave_out_totals() {
+    let cursor = Cursor::new(vec![0; 36]);
+    TestServer::new().assert_response_regex(
+      format!("/utxos/balance/mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn?cursor={cursor}"),
+      StatusCode::OK,
+      r#"\{"utxos":\[\],"total_utxos":null,"total_shibes":null,"total_inscription_shibes":null,"height":1,"next_cursor":null\}"#,
+    );
+  }
+
+  #[test]
+  fn malformed_cursor_is_rejected() {
+    TestServer::new().assert_response_regex(
+      "/dunes/balance/mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn?cursor=AAA",
+      StatusCode::BAD_REQUEST,
+      ".*invalid cursor.*",
+    );
+  }
+
   #[test]
   fn range() {
     TestServer::new().assert_response_regex(
@@ -4353,7 +5384,123 @@ mod tests {
     assert_eq!(response.status(), StatusCode::OK);
     assert_eq!(
       response.headers().get(header::CACHE_CONTROL).unwrap(),
-      "max-age=31536000, immutable"
+      "public, max-age=31536000, immutable"
+    );
+    assert_eq!(
+      response.headers().get(header::ETAG).unwrap(),
+      &format!("\"{}\"", InscriptionId::from(txid)),
+    );
+    assert_eq!(response.headers().get(header::ACCEPT_RANGES).unwrap(), "bytes");
+  }
+
+  #[test]
+  fn content_supports_range_and_conditional_requests() {
+    let server = TestServer::new();
+    server.mine_blocks(1);
+
+    let txid = server
+      .dogecoin_rpc_server
+      .broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0)],
+        script_sig: inscription("text/foo", "hello").to_script_sig(),
+        ..Default::default()
+      });
+
+    server.mine_blocks(1);
+    server.index.update().unwrap();
+
+    let inscription_id = InscriptionId::from(txid);
+    let url = server.join_url(&format!("/content/{inscription_id}"));
+    let client = reqwest::blocking::Client::new();
+
+    let response = client
+      .get(url.clone())
+      .header(header::RANGE, "bytes=1-3")
+      .send()
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+      response.headers().get(header::CONTENT_RANGE).unwrap(),
+      "bytes 1-3/5"
+    );
+    assert_eq!(response.text().unwrap(), "ell");
+
+    let response = client
+      .get(url.clone())
+      .header(header::RANGE, "bytes=5-")
+      .send()
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(
+      response.headers().get(header::CONTENT_RANGE).unwrap(),
+      "bytes */5"
+    );
+
+    let response = client
+      .get(url.clone())
+      .header(header::RANGE, "bytes=1-3")
+      .header(header::IF_RANGE, "\"stale\"")
+      .send()
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().unwrap(), "hello");
+
+    let response = client
+      .get(url.clone())
+      .header(header::IF_NONE_MATCH, format!("\"{inscription_id}\""))
+      .send()
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    let response = client
+      .get(url)
+      .header(header::IF_NONE_MATCH, format!("W/\"{inscription_id}\""))
+      .send()
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    let response = client
+      .get(
+        server
+          .join_url("/content/0000000000000000000000000000000000000000000000000000000000000000i0"),
+      )
+      .header(header::IF_NONE_MATCH, "*")
+      .send()
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+  }
+
+  #[test]
+  fn compressed_content_has_weak_etag() {
+    let server = TestServer::new();
+    server.mine_blocks(1);
+
+    let txid = server
+      .dogecoin_rpc_server
+      .broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0)],
+        script_sig: inscription("text/plain;charset=utf-8", &"hello ".repeat(100)).to_script_sig(),
+        ..Default::default()
+      });
+
+    server.mine_blocks(1);
+    server.index.update().unwrap();
+
+    let inscription_id = InscriptionId::from(txid);
+
+    let response = reqwest::blocking::Client::new()
+      .get(server.join_url(&format!("/content/{inscription_id}")))
+      .header(header::ACCEPT_ENCODING, "gzip")
+      .send()
+      .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+      response.headers().get(header::CONTENT_ENCODING).unwrap(),
+      "gzip"
+    );
+    assert_eq!(
+      response.headers().get(header::ETAG).unwrap(),
+      &format!("W/\"{inscription_id}\"")
     );
   }
 
@@ -0,0 +1,118 @@
+use super::*;
+
+/// A single `Range: bytes=…` request resolved against a body of known length.
+#[derive(Debug, PartialEq)]
+pub(super) enum ByteRange {
+  Satisfiable(std::ops::Range<usize>),
+  Unsatisfiable,
+}
+
+impl ByteRange {
+  /// Resolves a `Range` header value. Returns `None` when the header should be
+  /// ignored and the full body served, which covers malformed values, units
+  /// other than bytes, and multi-range requests.
+  pub(super) fn parse(value: &str, len: usize) -> Option<Self> {
+    let spec = value.trim().strip_prefix("bytes=")?.trim();
+
+    if spec.contains(',') {
+      return None;
+    }
+
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    let range = if start.is_empty() {
+      let suffix = end.parse::<usize>().ok()?;
+
+      if suffix == 0 {
+        return Some(Self::Unsatisfiable);
+      }
+
+      len.saturating_sub(suffix)..len
+    } else {
+      let start = start.parse::<usize>().ok()?;
+
+      let end = if end.is_empty() {
+        len
+      } else {
+        let end = end.parse::<usize>().ok()?;
+
+        if end < start {
+          return None;
+        }
+
+        end.saturating_add(1).min(len)
+      };
+
+      start..end
+    };
+
+    if range.start >= len {
+      return Some(Self::Unsatisfiable);
+    }
+
+    Some(Self::Satisfiable(range))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn bounded() {
+    assert_eq!(
+      ByteRange::parse("bytes=0-1", 10),
+      Some(ByteRange::Satisfiable(0..2))
+    );
+    assert_eq!(
+      ByteRange::parse("bytes=2-100", 10),
+      Some(ByteRange::Satisfiable(2..10))
+    );
+  }
+
+  #[test]
+  fn open_ended() {
+    assert_eq!(
+      ByteRange::parse("bytes=4-", 10),
+      Some(ByteRange::Satisfiable(4..10))
+    );
+  }
+
+  #[test]
+  fn suffix() {
+    assert_eq!(
+      ByteRange::parse("bytes=-3", 10),
+      Some(ByteRange::Satisfiable(7..10))
+    );
+    assert_eq!(
+      ByteRange::parse("bytes=-30", 10),
+      Some(ByteRange::Satisfiable(0..10))
+    );
+    assert_eq!(
+      ByteRange::parse("bytes=-0", 10),
+      Some(ByteRange::Unsatisfiable)
+    );
+  }
+
+  #[test]
+  fn unsatisfiable() {
+    assert_eq!(
+      ByteRange::parse("bytes=10-", 10),
+      Some(ByteRange::Unsatisfiable)
+    );
+    assert_eq!(
+      ByteRange::parse("bytes=0-", 0),
+      Some(ByteRange::Unsatisfiable)
+    );
+  }
+
+  #[test]
+  fn ignored() {
+    assert_eq!(ByteRange::parse("items=0-1", 10), None);
+    assert_eq!(ByteRange::parse("bytes=0-1,4-5", 10), None);
+    assert_eq!(ByteRange::parse("bytes=5-1", 10), None);
+    assert_eq!(ByteRange::parse("bytes=a-b", 10), None);
+    assert_eq!(ByteRange::parse("bytes=", 10), None);
+  }
+}
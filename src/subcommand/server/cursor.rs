@@ -0,0 +1,65 @@
+use super::*;
+
+/// Opaque pagination cursor handed out by listing endpoints.
+///
+/// A cursor is the last table key that was scanned. The next page is a range
+/// scan that resumes strictly after that key, so no entry is repeated and no
+/// entry present throughout paging is skipped. Pages are not a snapshot: each
+/// one is read at the tip, so while blocks are indexed, entries added or
+/// removed between requests may or may not appear, and pages shift
+/// accordingly. Responses report the height they were read at.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Cursor {
+  pub(super) after: Vec<u8>,
+}
+
+impl Cursor {
+  pub(super) fn new(after: impl Into<Vec<u8>>) -> Self {
+    Self {
+      after: after.into(),
+    }
+  }
+}
+
+impl Display for Cursor {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(
+      f,
+      "{}",
+      base64::encode_config(&self.after, base64::URL_SAFE_NO_PAD)
+    )
+  }
+}
+
+impl FromStr for Cursor {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let after = base64::decode_config(s, base64::URL_SAFE_NO_PAD)
+      .map_err(|err| anyhow!("invalid cursor `{s}`: {err}"))?;
+
+    if after.is_empty() {
+      bail!("invalid cursor `{s}`: empty");
+    }
+
+    Ok(Self { after })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn round_trip() {
+    let cursor = Cursor::new(vec![0, 1, 2, 255]);
+    assert_eq!(cursor.to_string().parse::<Cursor>().unwrap(), cursor);
+  }
+
+  #[test]
+  fn invalid() {
+    assert!("".parse::<Cursor>().is_err());
+    assert!("A".parse::<Cursor>().is_err());
+    assert!("not base64!".parse::<Cursor>().is_err());
+  }
+}
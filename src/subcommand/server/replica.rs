@@ -0,0 +1,324 @@
+use {
+  super::*,
+  std::{path::Path, sync::RwLock, time::Instant},
+};
+
+/// File in a publish directory naming the newest snapshot. It is replaced by
+/// rename, so replicas never read a partially written name.
+const CURRENT: &str = "CURRENT";
+
+/// Writes a snapshot of the index to a directory for `ord server --read-only
+/// --replica-of` processes to pick up. Every snapshot is a full export, which
+/// takes as long as `ord index export` and holds up the indexer while it is
+/// written, so they are written at most once per `interval` rather than for
+/// every block.
+pub(super) struct Publisher {
+  dir: PathBuf,
+  interval: Duration,
+  published: Option<(BlockHash, Instant)>,
+}
+
+impl Publisher {
+  pub(super) fn new(dir: PathBuf, interval: Duration) -> Result<Self> {
+    fs::create_dir_all(&dir)
+      .with_context(|| format!("failed to create publish dir `{}`", dir.display()))?;
+
+    Ok(Self {
+      dir,
+      interval,
+      published: None,
+    })
+  }
+
+  /// Publishes the current tip unless it already was or the last snapshot is
+  /// less than `interval` old, and returns the name of the new snapshot. The
+  /// snapshot is read from a single read transaction, so it is consistent
+  /// even while the index keeps updating.
+  pub(super) fn publish(&mut self, index: &Index) -> Result<Option<String>> {
+    if let Some((_, at)) = self.published {
+      if at.elapsed() < self.interval {
+        return Ok(None);
+      }
+    }
+
+    let Some(tip) = index.block_hash(None)? else {
+      return Ok(None);
+    };
+
+    if self.published.map(|(hash, _)| hash) == Some(tip) {
+      return Ok(None);
+    }
+
+    let start = Instant::now();
+
+    let tmp = self.dir.join("snapshot.tmp");
+
+    let mut writer = io::BufWriter::new(File::create(&tmp)?);
+    let header = index.export_snapshot(&mut writer)?;
+    writer.into_inner()?.sync_all()?;
+
+    let name = format!("{}-{}.ordsnap", header.height, header.block_hash);
+
+    fs::rename(&tmp, self.dir.join(&name))?;
+
+    let previous = current(&self.dir)?;
+
+    let tmp = self.dir.join(format!("{CURRENT}.tmp"));
+    fs::write(&tmp, &name)?;
+    File::open(&tmp)?.sync_all()?;
+    fs::rename(&tmp, self.dir.join(CURRENT))?;
+
+    // Keep the previous snapshot around for replicas that are still importing it.
+    for entry in fs::read_dir(&self.dir)? {
+      let file_name = entry?.file_name();
+      let Some(file_name) = file_name.to_str() else {
+        continue;
+      };
+
+      if file_name.ends_with(".ordsnap")
+        && file_name != name
+        && Some(file_name) != previous.as_deref()
+      {
+        fs::remove_file(self.dir.join(file_name))?;
+      }
+    }
+
+    let elapsed = start.elapsed();
+
+    if elapsed > self.interval / 2 {
+      log::warn!(
+        "Publishing snapshot `{name}` took {}s, more than half of --publish-interval {}s",
+        elapsed.as_secs(),
+        self.interval.as_secs(),
+      );
+    }
+
+    self.published = Some((header.block_hash, Instant::now()));
+
+    Ok(Some(name))
+  }
+}
+
+fn current(dir: &Path) -> Result<Option<String>> {
+  match fs::read_to_string(dir.join(CURRENT)) {
+    Ok(name) => Ok(Some(name.trim().into())),
+    Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+    Err(err) => {
+      Err(err).with_context(|| format!("failed to read `{}`", dir.join(CURRENT).display()))
+    }
+  }
+}
+
+fn replica_dir(options: &Options) -> Result<PathBuf> {
+  let dir = options.data_dir()?.join("replica");
+  fs::create_dir_all(&dir)
+    .with_context(|| format!("failed to create replica dir `{}`", dir.display()))?;
+  Ok(dir)
+}
+
+/// Imports the snapshot `name` into a fresh index of this replica's own.
+fn import(dir: &Path, name: &str, options: &Options) -> Result<Index> {
+  let path = replica_dir(options)?.join(format!("{name}.redb"));
+
+  if path.exists() {
+    fs::remove_file(&path)?;
+  }
+
+  let options = Options {
+    index: Some(path.clone()),
+    ..options.clone()
+  };
+
+  let imported = Index::open(&options).and_then(|index| {
+    let snapshot = File::open(dir.join(name))
+      .with_context(|| format!("failed to open snapshot `{}`", dir.join(name).display()))?;
+
+    index.import_snapshot(io::BufReader::new(snapshot))
+  });
+
+  if let Err(err) = imported {
+    fs::remove_file(&path).ok();
+    return Err(err);
+  }
+
+  // Reopen so the settings recorded in the snapshot's statistics apply.
+  Index::open(&options)
+}
+
+/// Opens the newest snapshot published to `dir`, or returns `None` if nothing
+/// has been published there yet.
+pub(super) fn load(dir: &Path, options: &Options) -> Result<Option<Index>> {
+  current(dir)?
+    .map(|name| import(dir, &name, options))
+    .transpose()
+}
+
+/// Switches `index` to the newest snapshot published to `dir` if it changed,
+/// and returns the snapshot's name. Requests that already hold the previous
+/// index finish against it.
+pub(super) fn refresh(
+  dir: &Path,
+  options: &Options,
+  index: &RwLock<Arc<Index>>,
+) -> Result<Option<String>> {
+  let Some(name) = current(dir)? else {
+    return Ok(None);
+  };
+
+  let replica_dir = replica_dir(options)?;
+
+  if index.read().unwrap().path() == replica_dir.join(format!("{name}.redb")) {
+    return Ok(None);
+  }
+
+  let replacement = Arc::new(import(dir, &name, options)?);
+
+  *index.write().unwrap() = replacement.clone();
+
+  // Files of indices that are still open can't be removed on every platform,
+  // so anything left over is retried on the next refresh.
+  for entry in fs::read_dir(&replica_dir)? {
+    let path = entry?.path();
+    if path != replacement.path() {
+      if let Err(err) = fs::remove_file(&path) {
+        log::debug!("failed to remove `{}`: {err}", path.display());
+      }
+    }
+  }
+
+  Ok(Some(name))
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::index::testing::Context};
+
+  fn replica_options(context: &Context, tempdir: &TempDir) -> Options {
+    Options {
+      data_dir: Some(tempdir.path().into()),
+      index: None,
+      ..context.options.clone()
+    }
+  }
+
+  fn snapshots(dir: &Path) -> Vec<String> {
+    let mut snapshots = fs::read_dir(dir)
+      .unwrap()
+      .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+      .filter(|name| name.ends_with(".ordsnap"))
+      .collect::<Vec<String>>();
+    snapshots.sort();
+    snapshots
+  }
+
+  #[test]
+  fn nothing_is_loaded_before_the_first_snapshot() {
+    let context = Context::builder().build();
+    let publish_dir = TempDir::new().unwrap();
+    let replica = TempDir::new().unwrap();
+
+    assert!(load(publish_dir.path(), &replica_options(&context, &replica))
+      .unwrap()
+      .is_none());
+  }
+
+  #[test]
+  fn replicas_load_published_snapshots() {
+    let context = Context::builder().build();
+    context.mine_blocks(2);
+
+    let publish_dir = TempDir::new().unwrap();
+    let mut publisher = Publisher::new(publish_dir.path().into(), Duration::ZERO).unwrap();
+
+    let name = publisher.publish(&context.index).unwrap().unwrap();
+
+    assert_eq!(
+      name,
+      format!(
+        "2-{}.ordsnap",
+        context.index.block_hash(None).unwrap().unwrap()
+      )
+    );
+    assert_eq!(current(publish_dir.path()).unwrap(), Some(name));
+    assert_eq!(publisher.publish(&context.index).unwrap(), None);
+
+    let replica = TempDir::new().unwrap();
+    let index = load(publish_dir.path(), &replica_options(&context, &replica))
+      .unwrap()
+      .unwrap();
+
+    assert_eq!(index.block_count().unwrap(), 3);
+    assert_eq!(
+      index.block_hash(None).unwrap(),
+      context.index.block_hash(None).unwrap()
+    );
+  }
+
+  #[test]
+  fn replicas_switch_to_new_snapshots() {
+    let context = Context::builder().build();
+    context.mine_blocks(1);
+
+    let publish_dir = TempDir::new().unwrap();
+    let mut publisher = Publisher::new(publish_dir.path().into(), Duration::ZERO).unwrap();
+    publisher.publish(&context.index).unwrap().unwrap();
+
+    let replica = TempDir::new().unwrap();
+    let options = replica_options(&context, &replica);
+
+    let index = RwLock::new(Arc::new(
+      load(publish_dir.path(), &options).unwrap().unwrap(),
+    ));
+    let old = index.read().unwrap().clone();
+
+    assert_eq!(refresh(publish_dir.path(), &options, &index).unwrap(), None);
+
+    context.mine_blocks(1);
+    let name = publisher.publish(&context.index).unwrap().unwrap();
+
+    assert_eq!(
+      refresh(publish_dir.path(), &options, &index).unwrap(),
+      Some(name)
+    );
+
+    assert_eq!(index.read().unwrap().block_count().unwrap(), 3);
+    assert_eq!(old.block_count().unwrap(), 2);
+    assert_eq!(
+      fs::read_dir(replica_dir(&options).unwrap()).unwrap().count(),
+      1
+    );
+  }
+
+  #[test]
+  fn old_snapshots_are_pruned() {
+    let context = Context::builder().build();
+
+    let publish_dir = TempDir::new().unwrap();
+    let mut publisher = Publisher::new(publish_dir.path().into(), Duration::ZERO).unwrap();
+
+    let mut names = Vec::new();
+
+    for _ in 0..3 {
+      context.mine_blocks(1);
+      names.push(publisher.publish(&context.index).unwrap().unwrap());
+    }
+
+    assert_eq!(snapshots(publish_dir.path()), names[1..]);
+  }
+
+  #[test]
+  fn snapshots_wait_for_the_interval() {
+    let context = Context::builder().build();
+    context.mine_blocks(1);
+
+    let publish_dir = TempDir::new().unwrap();
+    let mut publisher =
+      Publisher::new(publish_dir.path().into(), Duration::from_secs(3600)).unwrap();
+
+    let name = publisher.publish(&context.index).unwrap().unwrap();
+
+    context.mine_blocks(1);
+    assert_eq!(publisher.publish(&context.index).unwrap(), None);
+    assert_eq!(snapshots(publish_dir.path()), [name]);
+  }
+}
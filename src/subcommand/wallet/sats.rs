@@ -50,7 +50,7 @@ impl Sats {
       Ok(Box::new(output))
     } else {
       let mut output = Vec::new();
-      for (outpoint, sat, offset, rarity) in rare_sats(utxos) {
+      for (outpoint, sat, offset, rarity) in rare_sats(&index.schedule(), utxos) {
         output.push(OutputRare {
           sat,
           output: outpoint,
@@ -63,14 +63,17 @@ impl Sats {
   }
 }
 
-fn rare_sats(utxos: Vec<(OutPoint, Vec<(u64, u64)>)>) -> Vec<(OutPoint, Sat, u64, Rarity)> {
+fn rare_sats(
+  schedule: &Schedule,
+  utxos: Vec<(OutPoint, Vec<(u64, u64)>)>,
+) -> Vec<(OutPoint, Sat, u64, Rarity)> {
   utxos
     .into_iter()
     .flat_map(|(outpoint, sat_ranges)| {
       let mut offset = 0;
       sat_ranges.into_iter().filter_map(move |(start, end)| {
         let sat = Sat(start);
-        let rarity = sat.rarity();
+        let rarity = schedule.rarity(sat);
         let start_offset = offset;
         offset += u64::try_from(end - start).unwrap();
         if rarity > Rarity::Common {
@@ -144,7 +147,7 @@ mod tests {
   #[test]
   fn identify_no_rare_sats() {
     assert_eq!(
-      rare_sats(vec![(
+      rare_sats(Schedule::mainnet(), vec![(
         outpoint(1),
         vec![
           (51 * COIN_VALUE as u128, 100 * COIN_VALUE as u128),
@@ -159,7 +162,7 @@ mod tests {
   #[ignore]
   fn identify_one_rare_sat() {
     assert_eq!(
-      rare_sats(vec![(
+      rare_sats(Schedule::mainnet(), vec![(
         outpoint(1),
         vec![
           (10, 80),
@@ -179,7 +182,7 @@ mod tests {
   #[ignore]
   fn identify_two_rare_sats() {
     assert_eq!(
-      rare_sats(vec![(
+      rare_sats(Schedule::mainnet(), vec![(
         outpoint(1),
         vec![(0, 100), (1050000000000000, 1150000000000000)],
       )]),
@@ -194,7 +197,7 @@ mod tests {
   #[ignore]
   fn identify_rare_sats_in_different_outpoints() {
     assert_eq!(
-      rare_sats(vec![
+      rare_sats(Schedule::mainnet(), vec![
         (
           outpoint(1),
           vec![(50 * COIN_VALUE as u128, 55 * COIN_VALUE as u128)]
@@ -1,9 +1,12 @@
 use {boilerplate::Boilerplate, super::*};
 pub(crate) use {
+  address::AddressHtml,
   block::BlockHashAndConfirmations,
   block::BlockHtml,
   block::BlockJson,
-  drc20::{DRC20, DRC20Balance, DRC20Output, DRC20UtxoOutput, Operation},
+  drc20::{DRC20, DRC20Balance, DRC20Output, DRC20PendingBalance, DRC20UtxoOutput, Operation},
+  drc20_tick::Drc20TickHtml,
+  drc20_ticks::{Drc20TickSummary, Drc20TicksHtml},
   dune::{
     DuneAddressJson, DuneBalance, DuneEntryJson, DuneHtml, DuneJson, DuneOutput, DuneOutputJson,
   },
@@ -29,8 +32,11 @@ pub(crate) use {
   utxo::Utxo,
 };
 
+mod address;
 mod block;
 mod drc20;
+mod drc20_tick;
+mod drc20_ticks;
 mod dune;
 mod dune_balances;
 mod dunes;
@@ -118,6 +124,8 @@ mod tests {
         chain: Chain::Mainnet,
         domain: Some("signet.ordinals.com".into()),
         index_sats: true,
+        csp_origin: None,
+        page_size: 100,
       }),),
       r"<!doctype html>
 <html lang=en>
@@ -162,6 +170,8 @@ mod tests {
         chain: Chain::Mainnet,
         domain: None,
         index_sats: true,
+        csp_origin: None,
+        page_size: 100,
       }),),
       r".*<nav>\s*<a href=/>Doginals<sup>alpha</sup></a>.*"
     );
@@ -174,6 +184,8 @@ mod tests {
         chain: Chain::Mainnet,
         domain: None,
         index_sats: false,
+        csp_origin: None,
+        page_size: 100,
       }),),
       r".*<nav>\s*<a href=/>Doginals<sup>alpha</sup></a>.*\s*<form action=/search.*",
     );
@@ -186,6 +198,8 @@ mod tests {
         chain: Chain::Signet,
         domain: None,
         index_sats: true,
+        csp_origin: None,
+        page_size: 100,
       }),),
       r".*<nav>\s*<a href=/>Doginals<sup>signet</sup></a>.*"
     );
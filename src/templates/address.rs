@@ -0,0 +1,167 @@
+use {super::*, bitcoin::blockdata::constants::COIN_VALUE};
+
+#[derive(Boilerplate)]
+pub(crate) struct AddressHtml {
+  pub(crate) address: Address,
+  pub(crate) shibes: u64,
+  pub(crate) outputs: u64,
+  /// Number of the most recent outputs whose inscriptions and dunes are
+  /// listed, which is less than `outputs` for large addresses.
+  pub(crate) scanned: usize,
+  pub(crate) inscriptions: Vec<InscriptionId>,
+  pub(crate) dunes: Vec<(SpacedDune, Pile)>,
+  /// Tick, available balance and transferable balance, already formatted
+  /// with the token's decimals.
+  pub(crate) drc20: Vec<(String, String, String)>,
+  /// Most recent outputs first, with their confirmation count when known.
+  pub(crate) activity: Vec<(OutPoint, u64, Option<u32>)>,
+}
+
+impl AddressHtml {
+  fn doge(&self) -> String {
+    format!(
+      "{}.{:08}",
+      self.shibes / COIN_VALUE,
+      self.shibes % COIN_VALUE
+    )
+  }
+
+  fn partial(&self) -> bool {
+    u64::try_from(self.scanned).unwrap() < self.outputs
+  }
+}
+
+impl PageContent for AddressHtml {
+  fn title(&self) -> String {
+    format!("Address {}", self.address)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn empty() {
+    assert_regex_match!(
+      AddressHtml {
+        address: "DH5yaieqoZN36fDVciNyRueRGvGLR3mr7L".parse().unwrap(),
+        shibes: 0,
+        outputs: 0,
+        scanned: 0,
+        inscriptions: Vec::new(),
+        dunes: Vec::new(),
+        drc20: Vec::new(),
+        activity: Vec::new(),
+      },
+      "
+        <h1>Address <span class=monospace>DH5yaieqoZN36fDVciNyRueRGvGLR3mr7L</span></h1>
+        <dl>
+          <dt>balance</dt><dd>0.00000000 DOGE</dd>
+          <dt>outputs</dt><dd>0</dd>
+        </dl>
+      "
+      .unindent()
+    );
+  }
+
+  #[test]
+  fn with_balances() {
+    assert_regex_match!(
+      AddressHtml {
+        address: "DH5yaieqoZN36fDVciNyRueRGvGLR3mr7L".parse().unwrap(),
+        shibes: 150_000_000,
+        outputs: 1,
+        scanned: 1,
+        inscriptions: vec![inscription_id(1)],
+        dunes: vec![(
+          SpacedDune {
+            dune: Dune(26),
+            spacers: 0,
+          },
+          Pile {
+            amount: 1_000,
+            divisibility: 1,
+            symbol: None,
+          },
+        )],
+        drc20: vec![("dogi".into(), "10".into(), "2.5".into())],
+        activity: vec![(OutPoint { txid: txid(1), vout: 0 }, 150_000_000, Some(3))],
+      },
+      "
+        <h1>Address <span class=monospace>DH5yaieqoZN36fDVciNyRueRGvGLR3mr7L</span></h1>
+        <dl>
+          <dt>balance</dt><dd>1.50000000 DOGE</dd>
+          <dt>outputs</dt><dd>1</dd>
+        </dl>
+        <h2>Shibescriptions</h2>
+        <div class=thumbnails>
+          <a href=/shibescription/1{64}i1><iframe .* src=/preview/1{64}i1></iframe></a>
+        </div>
+        <h2>Dunes</h2>
+        <table>
+          <tr>
+            <th>dune</th>
+            <th>balance</th>
+          </tr>
+          <tr>
+            <td><a href=/dune/AA>AA</a></td>
+            <td>100\u{A0}¤</td>
+          </tr>
+        </table>
+        <h2>DRC-20</h2>
+        <table>
+          <tr>
+            <th>tick</th>
+            <th>available</th>
+            <th>transferable</th>
+          </tr>
+          <tr>
+            <td><a href=/drc20/tick/dogi>dogi</a></td>
+            <td>10</td>
+            <td>2.5</td>
+          </tr>
+        </table>
+        <h2>Recent Activity</h2>
+        <table>
+          <tr>
+            <th>output</th>
+            <th>value</th>
+            <th>confirmations</th>
+          </tr>
+          <tr>
+            <td><a class=monospace href=/output/1{64}:0>1{64}:0</a></td>
+            <td>150000000</td>
+            <td>3</td>
+          </tr>
+        </table>
+      "
+      .unindent()
+    );
+  }
+
+  #[test]
+  fn partial() {
+    assert_regex_match!(
+      AddressHtml {
+        address: "DH5yaieqoZN36fDVciNyRueRGvGLR3mr7L".parse().unwrap(),
+        shibes: 0,
+        outputs: 2000,
+        scanned: 1000,
+        inscriptions: Vec::new(),
+        dunes: Vec::new(),
+        drc20: Vec::new(),
+        activity: Vec::new(),
+      },
+      "
+        <h1>Address <span class=monospace>DH5yaieqoZN36fDVciNyRueRGvGLR3mr7L</span></h1>
+        <dl>
+          <dt>balance</dt><dd>0.00000000 DOGE</dd>
+          <dt>outputs</dt><dd>2000</dd>
+        </dl>
+        <p>Shibescriptions and dunes are listed for the 1000 most recent outputs. See <a href=/inscriptions/balance/DH5yaieqoZN36fDVciNyRueRGvGLR3mr7L>all shibescriptions</a> and <a href=/dunes/balance/DH5yaieqoZN36fDVciNyRueRGvGLR3mr7L>all dunes</a>.</p>
+      "
+      .unindent()
+    );
+  }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::auxpow::AuxPow;
 use std::collections::HashMap;
 
 impl Serialize for Height {
@@ -28,6 +29,7 @@ pub struct BlockJson {
   output_addresses_per_tx: HashMap<Txid, String>,
   output_scripts_per_tx: HashMap<Txid, String>,
   inscriptions_per_tx: HashMap<Txid, (InscriptionId, Option<String>, Option<Vec<u8>>)>,
+  auxpow: Option<AuxPow>,
 }
 
 impl BlockJson {
@@ -43,6 +45,7 @@ impl BlockJson {
     inscriptions_per_tx: HashMap<Txid, (InscriptionId, Option<String>, Option<Vec<u8>>)>,
     output_addresses_per_tx: HashMap<Txid, String>,
     output_scripts_per_tx: HashMap<Txid, String>,
+    auxpow: Option<AuxPow>,
   ) -> Self {
     let mut target = block.header.target().to_be_bytes();
     target.reverse();
@@ -57,7 +60,8 @@ impl BlockJson {
       output_values_per_tx,
       inscriptions_per_tx,
       output_addresses_per_tx,
-      output_scripts_per_tx
+      output_scripts_per_tx,
+      auxpow,
     }
   }
 }
@@ -76,6 +80,7 @@ pub(crate) struct BlockHtml {
   output_values_per_tx: HashMap<Txid, String>,
   output_addresses_per_tx: HashMap<Txid, String>,
   inscriptions_per_tx: HashMap<Txid, (InscriptionId, Option<String>, Option<Vec<u8>>)>,
+  auxpow: Option<AuxPow>,
 }
 
 impl BlockHtml {
@@ -90,6 +95,7 @@ impl BlockHtml {
       output_values_per_tx: HashMap<Txid, String>,
       inscriptions_per_tx: HashMap<Txid, (InscriptionId, Option<String>, Option<Vec<u8>>)>,
       output_addresses_per_tx: HashMap<Txid, String>,
+      auxpow: Option<AuxPow>,
   ) -> Self {
     let mut target = block.header.target().to_be_bytes();
     target.reverse();
@@ -107,6 +113,7 @@ impl BlockHtml {
       output_values_per_tx,
       inscriptions_per_tx,
       output_addresses_per_tx,
+      auxpow,
     }
   }
 }
@@ -147,6 +154,32 @@ mod tests {
     );
   }
 
+  #[test]
+  fn merge_mined() {
+    let auxpow = crate::auxpow::tests::auxpow();
+
+    assert_regex_match!(
+      BlockHtml::new(
+        Chain::Mainnet.genesis_block(),
+        Height(0),
+        Height(0),
+        HashMap::new(),
+        HashMap::new(),
+        HashMap::new(),
+        HashMap::new(),
+        HashMap::new(),
+        HashMap::new(),
+        HashMap::new(),
+        Some(auxpow.clone()),
+      ),
+      format!(
+        r".*<dt>parent block</dt><dd class=monospace>{}</dd>.*<dt>parent coinbase</dt><dd class=monospace>{}</dd>.*<dt>merged mining branch</dt><dd>2 hashes at index 2</dd>.*",
+        auxpow.parent_block.block_hash(),
+        auxpow.coinbase_tx.txid(),
+      ),
+    );
+  }
+
   #[test]
   fn next_active_when_not_last() {
     assert_regex_match!(
@@ -34,6 +34,14 @@ pub(crate) struct DRC20Balance {
   utxos: Option<Vec<DRC20Output>>,
 }
 
+/// Change of a balance that unconfirmed transactions would make.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DRC20PendingBalance {
+  pub tick: String,
+  pub transferable: String,
+  pub available: String,
+}
+
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub(crate) struct DRC20Output {
   #[serde(flatten)]
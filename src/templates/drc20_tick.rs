@@ -0,0 +1,147 @@
+use {
+  super::*,
+  crate::drc20::{format_balance, script_key::ScriptKey, Balance, Event, TickActivity, TokenInfo},
+};
+
+#[derive(Boilerplate)]
+pub(crate) struct Drc20TickHtml {
+  pub(crate) token: TokenInfo,
+  pub(crate) holders: usize,
+  pub(crate) top_holders: Vec<(ScriptKey, Balance)>,
+  pub(crate) activity: Vec<TickActivity>,
+}
+
+impl Drc20TickHtml {
+  fn amount(&self, amount: u128) -> String {
+    format_balance(amount, self.token.decimal)
+  }
+}
+
+impl PageContent for Drc20TickHtml {
+  fn title(&self) -> String {
+    format!("DRC-20 {}", self.token.tick)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    crate::drc20::{BurnEvent, MintEvent},
+  };
+
+  #[test]
+  fn display() {
+    let script_key = ScriptKey::from_script(&Script::new(), Network::Bitcoin);
+
+    assert_regex_match!(
+      Drc20TickHtml {
+        token: TokenInfo {
+          tick: "dogi".parse().unwrap(),
+          inscription_id: inscription_id(1),
+          inscription_number: 7,
+          supply: 2_000_000,
+          minted: 1_000,
+          burned: 250,
+          limit_per_mint: 1_000,
+          decimal: 2,
+          deploy_by: script_key.clone(),
+          deployed_number: 10,
+          deployed_timestamp: 0,
+          latest_mint_number: 8,
+        },
+        holders: 1,
+        top_holders: vec![(
+          script_key.clone(),
+          Balance {
+            tick: "dogi".parse().unwrap(),
+            overall_balance: 1_000,
+            transferable_balance: 250,
+          },
+        )],
+        activity: vec![
+          TickActivity {
+            height: 11,
+            inscription_id: inscription_id(2),
+            event: Event::Mint(MintEvent {
+              txid: None,
+              vout: 0,
+              to: script_key.clone(),
+              tick: "dogi".parse().unwrap(),
+              amount: 1_000,
+              msg: None,
+            }),
+            rule_version: 1,
+          },
+          TickActivity {
+            height: 12,
+            inscription_id: inscription_id(3),
+            event: Event::Burn(BurnEvent {
+              txid: None,
+              from: script_key,
+              tick: "dogi".parse().unwrap(),
+              amount: 250,
+            }),
+            rule_version: 1,
+          },
+        ],
+      },
+      "
+        <h1>DRC-20 <span class=monospace>dogi</span></h1>
+        <dl>
+          <dt>supply</dt><dd>20000</dd>
+          <dt>minted</dt><dd>10</dd>
+          <dt>burned</dt><dd>2.5</dd>
+          <dt>circulating</dt><dd>7.5</dd>
+          <dt>limit per mint</dt><dd>10</dd>
+          <dt>decimals</dt><dd>2</dd>
+          <dt>holders</dt><dd>1</dd>
+          <dt>deploy inscription</dt><dd><a class=monospace href=/shibescription/1{64}i1>7</a></dd>
+          <dt>deployed by</dt><dd class=monospace>.*</dd>
+          <dt>deploy height</dt><dd><a href=/block/10>10</a></dd>
+        </dl>
+        <h2>Top Holders</h2>
+        <table>
+          <tr>
+            <th>holder</th>
+            <th>available</th>
+            <th>transferable</th>
+          </tr>
+          <tr>
+            <td class=monospace>.*</td>
+            <td>7.5</td>
+            <td>2.5</td>
+          </tr>
+        </table>
+        <h2>Recent Activity</h2>
+        <table>
+          <tr>
+            <th>height</th>
+            <th>operation</th>
+            <th>from</th>
+            <th>to</th>
+            <th>amount</th>
+            <th>rules</th>
+          </tr>
+          <tr>
+            <td><a href=/block/11>11</a></td>
+            <td><a href=/shibescription/2{64}i2>mint</a></td>
+            <td></td>
+            <td class=monospace>.*</td>
+            <td>10</td>
+            <td>v1</td>
+          </tr>
+          <tr>
+            <td><a href=/block/12>12</a></td>
+            <td><a href=/shibescription/3{64}i3>burn</a></td>
+            <td class=monospace>.*</td>
+            <td></td>
+            <td>2.5</td>
+            <td>v1</td>
+          </tr>
+        </table>
+      "
+      .unindent()
+    );
+  }
+}
@@ -0,0 +1,90 @@
+use {
+  super::*,
+  crate::drc20::{format_balance, TokenInfo},
+};
+
+#[derive(Boilerplate)]
+pub(crate) struct Drc20TicksHtml {
+  pub(crate) ticks: Vec<Drc20TickSummary>,
+}
+
+pub(crate) struct Drc20TickSummary {
+  pub(crate) token: TokenInfo,
+  pub(crate) holders: usize,
+}
+
+impl Drc20TickSummary {
+  fn minted(&self) -> String {
+    format_balance(self.token.minted, self.token.decimal)
+  }
+
+  fn supply(&self) -> String {
+    format_balance(self.token.supply, self.token.decimal)
+  }
+
+  fn progress(&self) -> String {
+    if self.token.supply == 0 {
+      return "0.00".into();
+    }
+
+    let basis_points = self.token.minted.saturating_mul(10_000) / self.token.supply;
+
+    format!("{}.{:02}", basis_points / 100, basis_points % 100)
+  }
+}
+
+impl PageContent for Drc20TicksHtml {
+  fn title(&self) -> String {
+    "DRC-20".to_string()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::drc20::script_key::ScriptKey};
+
+  #[test]
+  fn display() {
+    assert_regex_match!(
+      Drc20TicksHtml {
+        ticks: vec![Drc20TickSummary {
+          token: TokenInfo {
+            tick: "dogi".parse().unwrap(),
+            inscription_id: inscription_id(1),
+            inscription_number: 0,
+            supply: 2_000,
+            minted: 500,
+            burned: 0,
+            limit_per_mint: 100,
+            decimal: 0,
+            deploy_by: ScriptKey::from_script(&Script::new(), Network::Bitcoin),
+            deployed_number: 4_600_001,
+            deployed_timestamp: 0,
+            latest_mint_number: 0,
+          },
+          holders: 3,
+        }],
+      },
+      "
+        <h1>DRC-20</h1>
+        <table>
+          <tr>
+            <th>tick</th>
+            <th>minted</th>
+            <th>progress</th>
+            <th>holders</th>
+            <th>deploy height</th>
+          </tr>
+          <tr>
+            <td><a href=/drc20/tick/dogi>dogi</a></td>
+            <td>500 / 2000</td>
+            <td><progress value=500 max=2000></progress> 25.00%</td>
+            <td>3</td>
+            <td><a href=/block/4600001>4600001</a></td>
+          </tr>
+        </table>
+      "
+      .unindent()
+    );
+  }
+}
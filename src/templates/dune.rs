@@ -39,8 +39,10 @@ pub(crate) struct DuneOutputJson {
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct DuneAddressJson {
   pub(crate) dunes: Vec<DuneBalance>,
-  pub(crate) total_dunes: usize,
-  pub(crate) total_elements: u32,
+  pub(crate) total_dunes: Option<usize>,
+  pub(crate) total_elements: Option<u32>,
+  pub(crate) height: u32,
+  pub(crate) next_cursor: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -48,8 +50,8 @@ pub(crate) struct DuneBalance {
   pub(crate) dune: SpacedDune,
   pub(crate) divisibility: u8,
   pub(crate) symbol: Option<char>,
-  pub(crate) total_balance: u128,
-  pub(crate) total_outputs: u128,
+  pub(crate) total_balance: Option<u128>,
+  pub(crate) total_outputs: Option<u128>,
   pub(crate) balances: Vec<DuneOutput>,
 }
 
@@ -8,6 +8,7 @@ pub(crate) struct OutputHtml {
   pub(crate) output: TxOut,
   pub(crate) inscriptions: Vec<InscriptionId>,
   pub(crate) dunes: Vec<(SpacedDune, Pile)>,
+  pub(crate) schedule: Arc<Schedule>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -83,13 +84,14 @@ mod tests {
           script_pubkey: Script::new_p2pkh(&PubkeyHash::all_zeros()),
         },
         dunes: Vec::new(),
+        schedule: Arc::new(Schedule::new(Chain::Mainnet)),
       },
       "
         <h1>Output <span class=monospace>1{64}:1</span></h1>
         <dl>
           <dt>value</dt><dd>3</dd>
           <dt>script pubkey</dt><dd class=monospace>OP_DUP OP_HASH160 OP_PUSHBYTES_20 0{40} OP_EQUALVERIFY OP_CHECKSIG</dd>
-          <dt>address</dt><dd class=monospace>1111111111111111111114oLvT2</dd>
+          <dt>address</dt><dd><a class=monospace href=/address/1111111111111111111114oLvT2>1111111111111111111114oLvT2</a></dd>
           <dt>transaction</dt><dd><a class=monospace href=/tx/1{64}>1{64}</a></dd>
         </dl>
         <h2>2 Sat Ranges</h2>
@@ -115,6 +117,7 @@ mod tests {
           script_pubkey: script::Builder::new().push_int(0).into_script(),
         },
         dunes: Vec::new(),
+        schedule: Arc::new(Schedule::new(Chain::Mainnet)),
       },
       "
         <h1>Output <span class=monospace>1{64}:1</span></h1>
@@ -142,6 +145,7 @@ mod tests {
           script_pubkey: Script::new_p2pkh(&PubkeyHash::all_zeros()),
         },
         dunes: Vec::new(),
+        schedule: Arc::new(Schedule::new(Chain::Mainnet)),
       }
       .to_string(),
       "
@@ -149,7 +153,7 @@ mod tests {
         <dl>
           <dt>value</dt><dd>3</dd>
           <dt>script pubkey</dt><dd class=monospace>OP_DUP OP_HASH160 OP_PUSHBYTES_20 0{40} OP_EQUALVERIFY OP_CHECKSIG</dd>
-          <dt>address</dt><dd class=monospace>1111111111111111111114oLvT2</dd>
+          <dt>address</dt><dd><a class=monospace href=/address/1111111111111111111114oLvT2>1111111111111111111114oLvT2</a></dd>
           <dt>transaction</dt><dd><a class=monospace href=/tx/1{64}>1{64}</a></dd>
         </dl>
       "
@@ -170,6 +174,7 @@ mod tests {
           script_pubkey: Script::new_p2pkh(&PubkeyHash::all_zeros()),
         },
         dunes: Vec::new(),
+        schedule: Arc::new(Schedule::new(Chain::Mainnet)),
       },
       "
         <h1>Output <span class=monospace>1{64}:1</span></h1>
@@ -205,6 +210,7 @@ mod tests {
             symbol: None,
           }
         )],
+        schedule: Arc::new(Schedule::new(Chain::Mainnet)),
       },
       "
         <h1>Output <span class=monospace>1{64}:1</span></h1>
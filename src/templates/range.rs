@@ -4,6 +4,7 @@ use super::*;
 pub(crate) struct RangeHtml {
   pub(crate) start: Sat,
   pub(crate) end: Sat,
+  pub(crate) schedule: Arc<Schedule>,
 }
 
 impl PageContent for RangeHtml {
@@ -22,6 +23,7 @@ mod tests {
       RangeHtml {
         start: Sat(0),
         end: Sat(1),
+        schedule: Arc::new(Schedule::new(Chain::Mainnet)),
       }
       .to_string(),
       "
@@ -41,6 +43,7 @@ mod tests {
       RangeHtml {
         start: Sat(1),
         end: Sat(10),
+        schedule: Arc::new(Schedule::new(Chain::Mainnet)),
       }
       .to_string(),
       "
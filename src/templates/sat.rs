@@ -8,6 +8,7 @@ pub(crate) struct SatHtml {
   pub(crate) satpoint: Option<SatPoint>,
   pub(crate) blocktime: Blocktime,
   pub(crate) inscription: Option<InscriptionId>,
+  pub(crate) schedule: Arc<Schedule>,
 }
 
 impl PageContent for SatHtml {
@@ -28,6 +29,7 @@ mod tests {
         satpoint: None,
         blocktime: Blocktime::confirmed(0),
         inscription: None,
+        schedule: Arc::new(Schedule::new(Chain::Mainnet)),
       },
       "
         <h1>Sat 0</h1>
@@ -37,6 +39,7 @@ mod tests {
           <dt>block</dt><dd><a href=/block/0>0</a></dd>
           <dt>offset</dt><dd>0</dd>
           <dt>rarity</dt><dd><span class=mythic>mythic</span></dd>
+          <dt>milestone</dt><dd>genesis</dd>
           <dt>timestamp</dt><dd><time>1970-01-01 00:00:00 UTC</time></dd>
         </dl>
         .*
@@ -56,11 +59,43 @@ mod tests {
         satpoint: None,
         blocktime: Blocktime::confirmed(0),
         inscription: None,
+        schedule: Arc::new(Schedule::new(Chain::Mainnet)),
       },
       r"<h1>Sat 1</h1>.*<a class=prev href=/sat/0>prev</a>\n<a class=next href=/sat/2>next</a>.*",
     );
   }
 
+  #[test]
+  fn sat_with_milestone() {
+    assert_regex_match!(
+      SatHtml {
+        sat: Height::AUXPOW.starting_sat(),
+        satpoint: None,
+        blocktime: Blocktime::confirmed(0),
+        inscription: None,
+        schedule: Arc::new(Schedule::new(Chain::Mainnet)),
+      },
+      r".*<dt>rarity</dt><dd><span class=rare>rare</span></dd>
+  <dt>milestone</dt><dd>AuxPoW activation</dd>
+  <dt>timestamp</dt>.*",
+    );
+  }
+
+  #[test]
+  fn sat_without_milestone() {
+    assert_regex_match!(
+      SatHtml {
+        sat: Sat(1),
+        satpoint: None,
+        blocktime: Blocktime::confirmed(0),
+        inscription: None,
+        schedule: Arc::new(Schedule::new(Chain::Mainnet)),
+      },
+      r".*<dt>rarity</dt><dd><span class=common>common</span></dd>
+  <dt>timestamp</dt>.*",
+    );
+  }
+
   #[test]
   fn sat_with_inscription() {
     assert_regex_match!(
@@ -69,6 +104,7 @@ mod tests {
         satpoint: None,
         blocktime: Blocktime::confirmed(0),
         inscription: Some(inscription_id(1)),
+        schedule: Arc::new(Schedule::new(Chain::Mainnet)),
       },
       r"<h1>Sat 0</h1>.*<dt>inscription</dt><dd class=thumbnails><a href=/shibescription/1{64}i1>.*</a></dd>.*",
     );
@@ -82,6 +118,7 @@ mod tests {
         satpoint: Some(satpoint(1, 0)),
         blocktime: Blocktime::confirmed(0),
         inscription: None,
+        schedule: Arc::new(Schedule::new(Chain::Mainnet)),
       },
       "<h1>Sat 0</h1>.*<dt>location</dt><dd class=monospace>1{64}:1:0</dd>.*",
     );
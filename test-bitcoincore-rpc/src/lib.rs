@@ -117,6 +117,7 @@ pub struct TransactionTemplate<'a> {
   pub inputs: &'a [(usize, usize, usize)],
   pub output_values: &'a [u64],
   pub outputs: usize,
+  pub script_sig: Script,
   pub witness: Witness,
 }
 
@@ -149,6 +150,7 @@ impl<'a> Default for TransactionTemplate<'a> {
       inputs: &[],
       output_values: &[],
       outputs: 1,
+      script_sig: Script::new(),
       witness: Witness::default(),
     }
   }
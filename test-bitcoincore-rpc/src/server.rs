@@ -363,13 +363,31 @@ impl Api for Server {
         None => Err(Self::not_found()),
       }
     } else {
-      match self.state().transactions.get(&txid) {
+      let state = self.state();
+
+      match state.transactions.get(&txid).or_else(|| {
+        state
+          .mempool()
+          .iter()
+          .find(|transaction| transaction.txid() == txid)
+      }) {
         Some(tx) => Ok(Value::String(hex::encode(serialize(tx)))),
         None => Err(Self::not_found()),
       }
     }
   }
 
+  fn get_raw_mempool(&self) -> Result<Vec<Txid>, jsonrpc_core::Error> {
+    Ok(
+      self
+        .state()
+        .mempool()
+        .iter()
+        .map(Transaction::txid)
+        .collect(),
+    )
+  }
+
   fn list_unspent(
     &self,
     minconf: Option<usize>,
@@ -9,6 +9,7 @@ fn json_with_satoshi_index() {
       r#"\{
   "blocks_indexed": 1,
   "branch_pages": \d+,
+  "cached_outputs": \d+,
   "fragmented_bytes": \d+,
   "index_file_size": \d+,
   "index_path": ".*\.redb",
@@ -41,6 +42,7 @@ fn json_without_satoshi_index() {
       r#"\{
   "blocks_indexed": 1,
   "branch_pages": \d+,
+  "cached_outputs": \d+,
   "fragmented_bytes": \d+,
   "index_file_size": \d+,
   "index_path": ".*\.redb",
@@ -6,7 +6,7 @@ fn genesis() {
     CommandBuilder::new("subsidy 0").output::<Output>(),
     Output {
       first: 0,
-      subsidy: 100000000000000,
+      subsidy: 8800000000,
     }
   );
 }
@@ -16,8 +16,8 @@ fn second_block() {
   assert_eq!(
     CommandBuilder::new("subsidy 1").output::<Output>(),
     Output {
-      first: 100000000000000,
-      subsidy: 100000000000000,
+      first: 8800000000,
+      subsidy: 6841600000000,
     }
   );
 }